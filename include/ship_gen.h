@@ -0,0 +1,38 @@
+/*
+ * ship_gen - random ASCII rocket generator
+ *
+ * Build the library with `cargo build --release --no-default-features --features ffi` and link
+ * against target/release/libship_gen.so (or .dylib or .dll). All strings are UTF-8 and NUL
+ * terminated. The same height and seed always give the same rocket.
+ */
+#ifndef SHIP_GEN_H
+#define SHIP_GEN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Writes the rocket into buf and returns its length in bytes, not counting the NUL. Returns 0 if
+ * height is below 3. If the rocket doesn't fit in buf_len bytes including the NUL, only an empty
+ * string is written. Pass a NULL buf and a buf_len of 0 to ask for the length alone.
+ */
+size_t ship_gen_generate(size_t height, uint64_t seed, char *buf, size_t buf_len);
+
+/*
+ * Returns the rocket as a newly allocated string, or NULL if height is below 3. The caller owns
+ * it and must release it with ship_gen_free(), not free().
+ */
+char *ship_gen_rocket(size_t height, uint64_t seed);
+
+/* Releases a string returned by ship_gen_rocket(). NULL is ignored. */
+void ship_gen_free(char *rocket);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SHIP_GEN_H */
@@ -0,0 +1,514 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::banner;
+use crate::grid::{Cell, Grid};
+use crate::palette::Palette;
+use crate::rocket::{Part, PartType, Rocket};
+
+/// Where the countdown starts
+const COUNTDOWN_FROM: usize = 10;
+/// Seconds from ignition until the rocket lets go of the pad
+const IGNITION: f64 = 2.0;
+/// How fast the rocket speeds up after liftoff, in lines per second per second
+const ACCELERATION: f64 = 8.0;
+/// Different flames `--animate exhaust` cycles through
+const FLICKERS: usize = 4;
+/// Times a second the flame changes in `--animate exhaust`
+const FLICKER_RATE: usize = 6;
+/// Lines a second the rocket falls at before its landing burn
+const DESCENT_SPEED: f64 = 12.0;
+/// Lines the landing legs reach below the engine
+const LEG_LENGTH: usize = 2;
+/// Seconds the rocket stands on the pad for after touchdown, twice over
+const SETTLE: f64 = 1.5;
+/// Characters smoke, steam and dust are drawn with, from thickest to about to clear
+const SMOKE: [char; 5] = ['@', 'O', 'o', '°', '.'];
+/// Seconds the booster burns for before staging
+const BURN: f64 = 1.5;
+/// Seconds between the booster cutting out and separating, and between separating and the upper
+/// stage lighting up
+const COAST: f64 = 0.75;
+/// How fast a spent booster falls back, in lines per second per second
+const GRAVITY: f64 = 6.0;
+/// Lines a second the booster drops away at as the stages separate
+const SEPARATION_SPEED: f64 = 2.0;
+/// Lines between the engine and the pad, for the flame to grow into
+const FLAME_ROOM: usize = 4;
+/// Lines of sky above the rocket on the pad
+const SKY: usize = 3;
+/// Columns either side of the rocket or countdown, whichever is wider
+const MARGIN: usize = 6;
+/// Characters a flickering flame is drawn with
+const FLAME: [char; 6] = ['^', '*', '\'', ':', '.', '"'];
+
+/// What an animation shows.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Animation {
+    /// A countdown under the pad, ignition, then liftoff until the rocket is out of sight
+    Launch,
+    /// A two stage rocket in flight dropping its booster, the upper stage flying on alone
+    Staging,
+    /// The rocket coming back down onto the pad, legs out and engine burning to slow it
+    Landing,
+    /// The rocket standing still with its engine burning, over and over
+    Exhaust,
+}
+
+impl Animation {
+    /// Whether the frames are meant to be played on a loop rather than once.
+    pub fn loops(&self) -> bool {
+        *self == Animation::Exhaust
+    }
+}
+
+impl fmt::Display for Animation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Animation::Launch => write!(f, "launch"),
+            Animation::Staging => write!(f, "staging"),
+            Animation::Landing => write!(f, "landing"),
+            Animation::Exhaust => write!(f, "exhaust"),
+        }
+    }
+}
+
+impl FromStr for Animation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "launch" => Ok(Animation::Launch),
+            "staging" => Ok(Animation::Staging),
+            "landing" => Ok(Animation::Landing),
+            "exhaust" => Ok(Animation::Exhaust),
+            _ => Err(format!("Unknown animation \"{}\"", s)),
+        }
+    }
+}
+
+/// Draws every frame of `animation` starring `rocket`, `fps` frames to a second. Flames flicker
+/// at random, drawn from the rocket's seed, so the same rocket always gets the same animation.
+/// Fails if the rocket can't play the part, like a single stage rocket asked to stage.
+pub fn frames(animation: Animation, rocket: &Rocket, palette: &Palette, fps: usize) -> Result<Vec<Grid>, String> {
+    let mut rng = ChaCha8Rng::seed_from_u64(rocket.seed.unwrap_or(0));
+    match animation {
+        Animation::Launch => Ok(launch(rocket, palette, fps.max(1), &mut rng)),
+        Animation::Staging => staging(rocket, fps.max(1), &mut rng),
+        Animation::Landing => Ok(landing(rocket, fps.max(1), &mut rng)),
+        Animation::Exhaust => Ok(exhaust(rocket, fps.max(1), &mut rng)),
+    }
+}
+
+/// A grid moving through the frame on its own, speeding up or slowing down as it goes.
+struct Sprite {
+    grid: Grid,
+    x: isize,
+    /// Line of the frame the top of the sprite is on
+    y: f64,
+    /// Lines a second, up being negative
+    speed: f64,
+    /// Lines a second per second
+    acceleration: f64,
+}
+
+impl Sprite {
+    fn new(grid: Grid, x: isize, y: usize) -> Sprite {
+        Sprite { grid, x, y: y as f64, speed: 0.0, acceleration: 0.0 }
+    }
+
+    fn advance(&mut self, fps: usize) {
+        self.speed += self.acceleration / fps as f64;
+        self.y += self.speed / fps as f64;
+    }
+
+    fn top(&self) -> isize {
+        self.y.round() as isize
+    }
+
+    fn bottom(&self) -> isize {
+        self.top() + self.grid.height() as isize
+    }
+
+    fn draw(&self, frame: &mut Grid) {
+        frame.overlay(self.x, self.top(), &self.grid);
+    }
+}
+
+/// A puff of smoke, steam or dust, drifting and thinning out until it's gone.
+struct Particle {
+    x: f64,
+    y: f64,
+    /// Columns and lines a second
+    speed: (f64, f64),
+    /// Seconds it lasts, and has lasted so far
+    life: f64,
+    age: f64,
+}
+
+/// Every puff in the air, moved and drawn together behind the rockets.
+#[derive(Default)]
+struct Particles(Vec<Particle>);
+
+impl Particles {
+    /// Adds `count` puffs at (`x`, `y`) heading off at speeds drawn from `speed` and lasting
+    /// for a time drawn from `life`.
+    fn emit(&mut self, rng: &mut ChaCha8Rng, count: usize, (x, y): (f64, f64), speed: (Range<f64>, Range<f64>), life: Range<f64>) {
+        for _ in 0..count {
+            let speed = (rng.gen_range(speed.0.clone()), rng.gen_range(speed.1.clone()));
+            self.0.push(Particle { x, y, speed, life: rng.gen_range(life.clone()), age: 0.0 });
+        }
+    }
+
+    /// Moves every puff on a frame, slowing as it spreads, and clears the ones that have gone.
+    fn advance(&mut self, fps: usize) {
+        let dt = 1.0 / fps as f64;
+        for particle in &mut self.0 {
+            particle.x += particle.speed.0 * dt;
+            particle.y += particle.speed.1 * dt;
+            particle.speed = (particle.speed.0 * (1.0 - 2.0 * dt).max(0.0), particle.speed.1 * (1.0 - dt).max(0.0));
+            particle.age += dt;
+        }
+        self.0.retain(|particle| particle.age < particle.life);
+    }
+
+    /// Draws the puffs behind whatever's already on the frame, and whatever's drawn after.
+    fn draw(&self, frame: &mut Grid) {
+        for particle in &self.0 {
+            let (x, y) = (particle.x.round() as isize, particle.y.round() as isize);
+            let inside = (0..frame.width() as isize).contains(&x) && (0..frame.height() as isize).contains(&y);
+            if inside && frame.get(x as usize, y as usize).is_blank() {
+                let ch = SMOKE[((particle.age / particle.life) * SMOKE.len() as f64) as usize % SMOKE.len()];
+                frame.set(x as usize, y as usize, Cell { ch, ..Cell::BLANK });
+            }
+        }
+    }
+}
+
+fn launch(rocket: &Rocket, palette: &Palette, fps: usize, rng: &mut ChaCha8Rng) -> Vec<Grid> {
+    let grid = rocket.to_grid();
+    let engine = engine(rocket);
+    let body = slice(&grid, lines(rocket, 0..engine + 1));
+    // A column of flame wider than the engine's nozzle either side
+    let flame_width = rocket.sections()[engine].bottom_width().max(1) + 2;
+    let countdown = banner::big_text(&COUNTDOWN_FROM.to_string(), palette.text());
+    let width = body.width().max(countdown.width()) + 2 * MARGIN;
+    let pad = SKY + body.height() + FLAME_ROOM;
+    let rocket_x = ((width - body.width()) / 2) as isize;
+
+    // The pad and the legs the rocket stands on, which stay behind
+    let mut ground = Grid::new(width, pad + 2 + countdown.height());
+    for x in 0..width {
+        ground.set(x, pad, Cell { ch: '═', ..Cell::BLANK });
+    }
+    let center = rocket_x as usize + body.width() / 2;
+    let legs = (body.width() / 2).max(1) + 1;
+    for y in SKY + body.height()..pad {
+        for x in [center - legs, center + legs] {
+            ground.set(x, y, Cell { ch: '║', ..Cell::BLANK });
+        }
+    }
+
+    let mut frames = Vec::new();
+    let mut smoke = Particles::default();
+    let pad_top = (pad - 1) as f64;
+    // Steam wisps rising from the foot of each leg
+    let steam = |smoke: &mut Particles, rng: &mut ChaCha8Rng| {
+        if rng.gen_bool(0.5) {
+            let x = if rng.gen_bool(0.5) { center - legs } else { center + legs };
+            smoke.emit(rng, 1, (x as f64, pad_top), (-2.0..2.0, -1.5..-0.5), 0.5..1.5);
+        }
+    };
+    for count in (1..=COUNTDOWN_FROM).rev() {
+        let text = banner::big_text(&count.to_string(), palette.text());
+        for _ in 0..fps {
+            steam(&mut smoke, rng);
+            let mut frame = ground.clone();
+            smoke.draw(&mut frame);
+            frame.overlay(rocket_x, SKY as isize, &body);
+            frame.overlay(((width - text.width()) / 2) as isize, (pad + 2) as isize, &text);
+            frames.push(frame);
+            smoke.advance(fps);
+        }
+    }
+
+    // Smoke billows out sideways along the pad as the flame reaches it
+    let ignition = (IGNITION * fps as f64).round() as usize;
+    for i in 0..ignition {
+        let rows = (FLAME_ROOM * (i + 1)).div_ceil(ignition);
+        if rows == FLAME_ROOM {
+            billow(&mut smoke, rng, center as f64, pad_top);
+        } else {
+            steam(&mut smoke, rng);
+        }
+        let mut frame = ground.clone();
+        smoke.draw(&mut frame);
+        frame.overlay(rocket_x, SKY as isize, &body);
+        draw_flame(&mut frame, center, (SKY + body.height()) as isize, flame_width, rows, rng);
+        frames.push(frame);
+        smoke.advance(fps);
+    }
+
+    // Climbing until the end of the exhaust has left the top of the frame, leaving puffs behind
+    let mut climbing = Sprite::new(grid, rocket_x, SKY);
+    climbing.acceleration = -ACCELERATION;
+    while climbing.bottom() + FLAME_ROOM as isize > 0 {
+        let trail = (climbing.bottom() + FLAME_ROOM as isize) as f64;
+        if trail >= pad_top {
+            billow(&mut smoke, rng, center as f64, pad_top);
+        } else {
+            smoke.emit(rng, 1, (center as f64, trail), (-1.5..1.5, 0.0..1.0), 1.0..2.5);
+        }
+        let mut frame = ground.clone();
+        smoke.draw(&mut frame);
+        climbing.draw(&mut frame);
+        draw_flame(&mut frame, center, climbing.bottom(), flame_width, FLAME_ROOM, rng);
+        frames.push(frame);
+        climbing.advance(fps);
+        smoke.advance(fps);
+    }
+    frames
+}
+
+/// Sends smoke rolling out both ways along the ground from under an engine.
+fn billow(smoke: &mut Particles, rng: &mut ChaCha8Rng, x: f64, y: f64) {
+    smoke.emit(rng, 2, (x, y), (-14.0..-5.0, -0.8..0.0), 1.5..3.0);
+    smoke.emit(rng, 2, (x, y), (5.0..14.0, -0.8..0.0), 1.5..3.0);
+}
+
+/// Lines `lines` of `grid`, as wide as all of it so they line up with the rest again.
+fn slice(grid: &Grid, lines: Range<usize>) -> Grid {
+    let mut slice = Grid::new(grid.width(), lines.len());
+    slice.overlay(0, -(lines.start as isize), grid);
+    slice
+}
+
+/// Lines the sections of `rocket` in `sections` take up.
+fn lines(rocket: &Rocket, sections: Range<usize>) -> Range<usize> {
+    let height = |sections: &[Arc<Part>]| sections.iter().map(|p| p.height()).sum::<usize>();
+    height(&rocket.sections()[..sections.start])..height(&rocket.sections()[..sections.end])
+}
+
+/// Where the engine is among the rocket's sections.
+fn engine(rocket: &Rocket) -> usize {
+    rocket.sections().iter().position(|p| p.part_type() == PartType::Engine).expect("every rocket has an engine")
+}
+
+fn staging(rocket: &Rocket, fps: usize, rng: &mut ChaCha8Rng) -> Result<Vec<Grid>, String> {
+    let sections = rocket.sections();
+    let engine = engine(rocket);
+    // Stages meet at the last change of width: a part that widens starts the booster, and one
+    // that narrows ends the upper stage
+    let split = sections[..engine].iter()
+        .rposition(|p| p.part_type() == PartType::Body && p.top_width() > 0 && p.top_width() != p.bottom_width())
+        .map(|i| if sections[i].bottom_width() > sections[i].top_width() { i } else { i + 1 })
+        .filter(|&split| sections[..split].iter().any(|p| p.part_type() == PartType::Body && p.top_width() > 0))
+        .ok_or("The rocket has only one stage, there's nothing to separate")?;
+
+    let grid = rocket.to_grid();
+    let width = grid.width() + 2 * MARGIN;
+    let height = SKY + grid.height() + FLAME_ROOM + SKY;
+    let center = MARGIN + grid.width() / 2;
+    let upper_lines = lines(rocket, 0..split);
+    let booster_lines = lines(rocket, split..engine + 1);
+    let mut upper = Sprite::new(slice(&grid, upper_lines.clone()), MARGIN as isize, SKY);
+    let mut booster = Sprite::new(slice(&grid, booster_lines.clone()), MARGIN as isize, SKY + upper_lines.end);
+    let exhaust = slice(&grid, booster_lines.end..grid.height());
+    let booster_flame = sections[engine].bottom_width().max(1) + 2;
+    let upper_flame = sections[split - 1].bottom_width().max(1);
+
+    let mut frames = Vec::new();
+    let seconds = |seconds: f64| (seconds * fps as f64).round() as usize;
+    // The booster burning, then cutting out
+    for _ in 0..seconds(BURN) {
+        let mut frame = Grid::new(width, height);
+        upper.draw(&mut frame);
+        booster.draw(&mut frame);
+        frame.overlay(booster.x, booster.bottom(), &exhaust);
+        draw_flame(&mut frame, center, booster.bottom() + exhaust.height() as isize, booster_flame, FLAME_ROOM, rng);
+        frames.push(frame);
+    }
+    for _ in 0..seconds(COAST) {
+        let mut frame = Grid::new(width, height);
+        upper.draw(&mut frame);
+        booster.draw(&mut frame);
+        frames.push(frame);
+    }
+
+    // The booster drifting back, then the upper stage lighting up and pulling away until both
+    // are out of sight
+    booster.speed = SEPARATION_SPEED;
+    booster.acceleration = GRAVITY;
+    for _ in 0..seconds(COAST) {
+        let mut frame = Grid::new(width, height);
+        upper.draw(&mut frame);
+        booster.draw(&mut frame);
+        frames.push(frame);
+        booster.advance(fps);
+    }
+    upper.acceleration = -ACCELERATION;
+    while upper.bottom() + FLAME_ROOM as isize > 0 || booster.top() < height as isize {
+        let mut frame = Grid::new(width, height);
+        upper.draw(&mut frame);
+        draw_flame(&mut frame, center, upper.bottom(), upper_flame, FLAME_ROOM, rng);
+        booster.draw(&mut frame);
+        frames.push(frame);
+        upper.advance(fps);
+        booster.advance(fps);
+    }
+    Ok(frames)
+}
+
+fn landing(rocket: &Rocket, fps: usize, rng: &mut ChaCha8Rng) -> Vec<Grid> {
+    let grid = rocket.to_grid();
+    let engine = engine(rocket);
+    let body = slice(&grid, lines(rocket, 0..engine + 1));
+    let width = body.width() + 2 * MARGIN;
+    let pad = SKY + body.height() + LEG_LENGTH;
+    let center = MARGIN + body.width() / 2;
+    let flame_width = rocket.sections()[engine].bottom_width().max(1) + 2;
+    // Legs stowed along the hull swing out from where they're folded, reaching down past the
+    // engine, and the others come out from under it
+    let (legs_from, spread) = match rocket.sections().iter().position(|p| p.stowed_legs()) {
+        Some(legs) => (lines(rocket, 0..legs).end, rocket.sections()[legs].top_width() / 2 + 2),
+        None => (body.height(), rocket.sections()[engine].top_width() / 2 + 1),
+    };
+
+    let mut ground = Grid::new(width, pad + 1);
+    for x in 0..width {
+        ground.set(x, pad, Cell { ch: '═', ..Cell::BLANK });
+    }
+
+    // Falling in from above the frame, then burning for the second half of the way to slow to a
+    // stop on the pad, legs coming out as the burn starts
+    let touchdown = (pad - LEG_LENGTH - body.height()) as f64;
+    let stowed = grid_fins(rocket, &body);
+    // Where the legs were folded is left bare once they're out
+    let mut deployed = stowed.clone();
+    if legs_from < body.height() {
+        for x in [stowed.width() / 2 - spread, stowed.width() / 2 + spread] {
+            deployed.set(x, legs_from, Cell::BLANK);
+        }
+    }
+    let reach = |legs: usize| body.height() - legs_from + legs;
+    let mut rocket = Sprite::new(stowed, MARGIN as isize - 1, 0);
+    rocket.y = -(rocket.grid.height() as f64);
+    rocket.speed = DESCENT_SPEED;
+    let burn_from = (rocket.y + touchdown) / 2.0;
+    let mut frames = Vec::new();
+    let mut legs = 0;
+    while rocket.y < touchdown && rocket.speed > 0.0 {
+        let mut frame = ground.clone();
+        let burning = rocket.y >= burn_from;
+        if burning {
+            rocket.acceleration = -DESCENT_SPEED * DESCENT_SPEED / (2.0 * (touchdown - burn_from));
+            if legs == 0 {
+                rocket.grid = deployed.clone();
+            }
+            legs = (legs + 1).min(LEG_LENGTH);
+        }
+        rocket.draw(&mut frame);
+        if burning {
+            draw_flame(&mut frame, center, rocket.bottom(), flame_width, FLAME_ROOM, rng);
+        }
+        if legs > 0 {
+            draw_legs(&mut frame, center, spread, rocket.top() + legs_from as isize, reach(legs));
+        }
+        frames.push(frame);
+        rocket.advance(fps);
+    }
+    rocket.y = touchdown;
+
+    // The dust kicked up settling, then the rocket standing on the pad
+    let mut dust = Particles::default();
+    billow(&mut dust, rng, center as f64, (pad - 1) as f64);
+    billow(&mut dust, rng, center as f64, (pad - 1) as f64);
+    for _ in 0..(SETTLE * 2.0 * fps as f64).round() as usize {
+        let mut frame = ground.clone();
+        dust.draw(&mut frame);
+        rocket.draw(&mut frame);
+        draw_legs(&mut frame, center, spread, rocket.top() + legs_from as isize, reach(LEG_LENGTH));
+        frames.push(frame);
+        dust.advance(fps);
+    }
+    frames
+}
+
+/// `body` a column wider each side, with grid fins out either side of the first straight line of
+/// hull under the nose to steer it back down, unless the rocket has grid fins of its own.
+fn grid_fins(rocket: &Rocket, body: &Grid) -> Grid {
+    let mut finned = Grid::new(body.width() + 2, body.height());
+    finned.overlay(1, 0, body);
+    let sections = rocket.sections();
+    if sections.iter().any(|p| p.grid_finned()) {
+        return finned;
+    }
+    let Some(hull) = sections.iter().position(|p| {
+        p.part_type() == PartType::Body && p.top_width() > 0 && p.top_width() == p.bottom_width() && !p.finned()
+    }) else {
+        return finned;
+    };
+    let y = lines(rocket, 0..hull).end;
+    let walls: Vec<usize> = (0..finned.width()).filter(|&x| matches!(finned.get(x, y).ch, '│' | '║' | '|')).collect();
+    if let (Some(&left), Some(&right)) = (walls.first(), walls.last()) {
+        let ch = if sections.iter().all(|p| p.chat_safe()) { '#' } else { '▦' };
+        let fin = Cell { ch, part_type: Some(PartType::Body), ..Cell::BLANK };
+        finned.set(left - 1, y, fin);
+        finned.set(right + 1, y, fin);
+    }
+    finned
+}
+
+/// A loop of the rocket burning in place, its exhaust swapped for a few flickering flames.
+fn exhaust(rocket: &Rocket, fps: usize, rng: &mut ChaCha8Rng) -> Vec<Grid> {
+    let grid = rocket.to_grid();
+    let engine = engine(rocket);
+    let body = slice(&grid, lines(rocket, 0..engine + 1));
+    let rows = (grid.height() - body.height()).max(2);
+    let center = grid.width() / 2;
+    let flame_width = rocket.sections()[engine].bottom_width().max(1) + 2;
+    let mut frames = Vec::new();
+    for _ in 0..FLICKERS {
+        let mut frame = Grid::new(grid.width(), body.height() + rows);
+        frame.overlay(0, 0, &body);
+        draw_flame(&mut frame, center, body.height() as isize, flame_width, rows, rng);
+        frames.extend(std::iter::repeat_n(frame, (fps / FLICKER_RATE).max(1)));
+    }
+    frames
+}
+
+/// Draws landing legs `length` lines out from under the engine, splaying out a column a line
+/// from `spread` columns either side of `center`.
+fn draw_legs(frame: &mut Grid, center: usize, spread: usize, top: isize, length: usize) {
+    for line in 0..length {
+        let y = top + line as isize;
+        if (0..frame.height() as isize).contains(&y) {
+            frame.set(center - spread - line, y as usize, Cell { ch: '/', ..Cell::BLANK });
+            frame.set(center + spread + line, y as usize, Cell { ch: '\\', ..Cell::BLANK });
+        }
+    }
+}
+
+/// Draws a flickering flame `rows` lines long from line `top` down, centered on `center`. It
+/// starts `width` wide and spreads a column each side every other line.
+fn draw_flame(frame: &mut Grid, center: usize, top: isize, width: usize, rows: usize, rng: &mut ChaCha8Rng) {
+    let widest = width + 2 * (rows / 2);
+    let mut grid = Grid::new(widest, rows);
+    for y in 0..rows {
+        let row_width = width + 2 * (y / 2);
+        let start = (widest - row_width) / 2;
+        for x in start..start + row_width {
+            // Flames thin out towards the tip
+            if rng.gen_range(0..rows + 1) > y {
+                let ch = FLAME[rng.gen_range(0..FLAME.len())];
+                grid.set(x, y, Cell { ch, part_type: Some(PartType::Exhaust), ..Cell::BLANK });
+            }
+        }
+    }
+    frame.overlay(center as isize - (widest / 2) as isize, top, &grid);
+}
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use crate::font::{self, GLYPH_SIZE};
+use crate::grid::{Cell, Grid};
+use crate::palette::Color;
+
+/// Columns of blank between two letters.
+const LETTER_SPACING: usize = 1;
+/// Width of a space, which has no set pixels to measure.
+const SPACE_WIDTH: usize = 3;
+/// Space between the rocket and the text.
+pub const GAP: usize = 2;
+
+/// Where the text goes relative to the rocket.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layout {
+    Beside,
+    Above,
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "beside" => Ok(Layout::Beside),
+            "above" => Ok(Layout::Above),
+            _ => Err(format!("Unknown layout \"{}\"", s)),
+        }
+    }
+}
+
+/// Draws `text` in large letters made of half blocks, 4 rows per line of text. Letters are
+/// trimmed to the columns they use, figlet style, instead of sitting in fixed 8 wide boxes.
+pub fn big_text(text: &str, color: Color) -> Grid {
+    let lines: Vec<Vec<[bool; GLYPH_SIZE]>> = text.lines().map(pixel_columns).collect();
+    let line_height = GLYPH_SIZE / 2;
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let height = (lines.len() * (line_height + 1)).saturating_sub(1);
+
+    let mut grid = Grid::new(width, height);
+    for (i, columns) in lines.iter().enumerate() {
+        for (x, column) in columns.iter().enumerate() {
+            for row in 0..line_height {
+                let ch = match (column[row * 2], column[row * 2 + 1]) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => continue,
+                };
+                grid.set(x, i * (line_height + 1) + row, Cell { ch, color: Some(color), ..Cell::BLANK });
+            }
+        }
+    }
+    grid
+}
+
+/// Splits a line of text into columns of font pixels, top to bottom.
+fn pixel_columns(line: &str) -> Vec<[bool; GLYPH_SIZE]> {
+    let mut columns = Vec::new();
+    for ch in line.chars() {
+        let glyph = font::glyph(ch);
+        let used: Vec<usize> = (0..GLYPH_SIZE)
+            .filter(|&x| (0..GLYPH_SIZE).any(|y| font::pixel(&glyph, x, y)))
+            .collect();
+        match (used.first(), used.last()) {
+            (Some(&first), Some(&last)) => {
+                for x in first..=last {
+                    let mut column = [false; GLYPH_SIZE];
+                    for (y, pixel) in column.iter_mut().enumerate() {
+                        *pixel = font::pixel(&glyph, x, y);
+                    }
+                    columns.push(column);
+                }
+                columns.extend(std::iter::repeat_n([false; GLYPH_SIZE], LETTER_SPACING));
+            }
+            _ => columns.extend(std::iter::repeat_n([false; GLYPH_SIZE], SPACE_WIDTH)),
+        }
+    }
+    // Drop the spacing after the last letter
+    while columns.last().is_some_and(|c| c.iter().all(|p| !p)) {
+        columns.pop();
+    }
+    columns
+}
+
+/// Puts the rocket and the text together, centering whichever is smaller along the other.
+pub fn compose(rocket: &Grid, text: &Grid, layout: Layout) -> Grid {
+    match layout {
+        Layout::Beside => {
+            let height = rocket.height().max(text.height());
+            let mut grid = Grid::new(rocket.width() + GAP + text.width(), height);
+            grid.draw(0, (height - rocket.height()) / 2, rocket);
+            grid.draw(rocket.width() + GAP, (height - text.height()) / 2, text);
+            grid
+        }
+        Layout::Above => {
+            let width = rocket.width().max(text.width());
+            let mut grid = Grid::new(width, text.height() + GAP / 2 + rocket.height());
+            grid.draw((width - text.width()) / 2, 0, text);
+            grid.draw((width - rocket.width()) / 2, text.height() + GAP / 2, rocket);
+            grid
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::grid::{Cell, Grid};
+
+/// Rows of pointer between the bubble and the rocket's nose.
+const POINTER_LENGTH: usize = 2;
+
+/// Greedily wraps `text` into lines no wider than `width` characters, splitting words that are
+/// too long to fit on a line of their own.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            let line_len = line.chars().count();
+            if line_len > 0 && line_len + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            while word.len() > width {
+                let rest = word.split_off(width);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draws a cowsay style speech bubble around the lines of text.
+pub fn bubble(lines: &[String]) -> Grid {
+    let inner = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut grid = Grid::new(inner + 4, lines.len() + 2);
+    let put = |grid: &mut Grid, x: usize, y: usize, ch: char| {
+        grid.set(x, y, Cell { ch, ..Cell::BLANK });
+    };
+    for x in 0..inner + 2 {
+        put(&mut grid, x + 1, 0, '_');
+        put(&mut grid, x + 1, lines.len() + 1, '-');
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let (left, right) = match (lines.len(), i) {
+            (1, _) => ('<', '>'),
+            (_, 0) => ('/', '\\'),
+            (n, i) if i == n - 1 => ('\\', '/'),
+            _ => ('|', '|'),
+        };
+        let y = i + 1;
+        put(&mut grid, 0, y, left);
+        for (x, ch) in line.chars().enumerate() {
+            put(&mut grid, x + 2, y, ch);
+        }
+        put(&mut grid, inner + 3, y, right);
+    }
+    grid
+}
+
+/// Puts a speech bubble saying `text` above and to the right of the rocket, with a pointer
+/// running down to its nose.
+pub fn say(rocket: &Grid, text: &str, width: usize) -> Grid {
+    let bubble = bubble(&wrap(text, width));
+    // Middle of the top row is where the nose is
+    let nose = rocket.rows().next()
+        .and_then(|row| {
+            let first = row.iter().position(|c| !c.is_blank())?;
+            let last = row.iter().rposition(|c| !c.is_blank())?;
+            Some((first + last) / 2)
+        })
+        .unwrap_or(0);
+    let bubble_x = nose + POINTER_LENGTH + 1;
+    let rocket_y = bubble.height() + POINTER_LENGTH;
+
+    let mut grid = Grid::new(rocket.width().max(bubble_x + bubble.width()), rocket_y + rocket.height());
+    grid.draw(bubble_x, 0, &bubble);
+    for i in 0..POINTER_LENGTH {
+        grid.set(bubble_x - 1 - i, bubble.height() + i, Cell { ch: '/', ..Cell::BLANK });
+    }
+    grid.draw(0, rocket_y, rocket);
+    grid
+}
@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+/// Most columns or lines a canvas can have, so a slip of the keyboard can't ask for gigabytes of
+/// blanks.
+pub const MAX_SIZE: usize = 1000;
+
+/// An exact size to pad or crop the output to, written `WxH` like `40x12`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl FromStr for Canvas {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected a canvas size like 40x12, not \"{}\"", s);
+        let lowercase = s.to_lowercase();
+        let (width, height) = lowercase.split_once('x').ok_or_else(error)?;
+        match (width.trim().parse(), height.trim().parse()) {
+            (Ok(width), Ok(height)) if width > MAX_SIZE || height > MAX_SIZE => {
+                Err(format!("Canvases are at most {}x{}, not {}x{}", MAX_SIZE, MAX_SIZE, width, height))
+            }
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Canvas { width, height }),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Where something goes across the room it's given.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl Align {
+    /// Columns in from the left to start something `width` wide in `room`, negative if it's too
+    /// wide and has to hang off the left. Centered things left over with an odd column lean left.
+    pub fn offset(self, width: usize, room: usize) -> isize {
+        let spare = room as isize - width as isize;
+        match self {
+            Align::Left => 0,
+            Align::Center => spare.div_euclid(2),
+            Align::Right => spare,
+        }
+    }
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "left" => Ok(Align::Left),
+            "center" | "centre" => Ok(Align::Center),
+            "right" => Ok(Align::Right),
+            _ => Err(format!("Unknown alignment \"{}\", expected left, center or right", s)),
+        }
+    }
+}
+
+/// Where something goes up and down the room it's given.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl VerticalAlign {
+    /// Lines down from the top to start something `height` tall in `room`, like [`Align::offset`].
+    pub fn offset(self, height: usize, room: usize) -> isize {
+        let spare = room as isize - height as isize;
+        match self {
+            VerticalAlign::Top => 0,
+            VerticalAlign::Middle => spare.div_euclid(2),
+            VerticalAlign::Bottom => spare,
+        }
+    }
+}
+
+/// Where the rocket goes on a [`Canvas`], written like `bottom-left`, or `top`, `left` or `center`
+/// to leave the other way in the middle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Placement {
+    pub horizontal: Align,
+    pub vertical: VerticalAlign,
+}
+
+impl Placement {
+    /// In the middle both ways
+    pub const CENTER: Placement = Placement { horizontal: Align::Center, vertical: VerticalAlign::Middle };
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut placement = Placement::CENTER;
+        for word in s.to_lowercase().split('-') {
+            match word {
+                "top" => placement.vertical = VerticalAlign::Top,
+                "middle" => placement.vertical = VerticalAlign::Middle,
+                "bottom" => placement.vertical = VerticalAlign::Bottom,
+                word => {
+                    placement.horizontal = word.parse()
+                        .map_err(|_| format!("Unknown placement \"{}\", expected something like top, bottom-left or center", s))?;
+                }
+            }
+        }
+        Ok(placement)
+    }
+}
+
+/// Pads `grid` out to exactly the canvas's size, or crops it down to it, putting it where
+/// `placement` says. Cropping keeps the side it's placed against, so a rocket placed at the bottom
+/// keeps its engine and loses its nose.
+pub fn fit(grid: &Grid, canvas: Canvas, placement: Placement) -> Grid {
+    let mut fitted = Grid::new(canvas.width, canvas.height);
+    fitted.overlay(
+        placement.horizontal.offset(grid.width(), canvas.width),
+        placement.vertical.offset(grid.height(), canvas.height),
+        grid,
+    );
+    fitted
+}
+
+/// Moves `grid` across a line `width` columns wide, like a terminal's, as `align` says. Grids
+/// already that wide or wider are left as they are.
+pub fn align(grid: &Grid, width: usize, align: Align) -> Grid {
+    if grid.width() >= width {
+        return grid.clone();
+    }
+    fit(grid, Canvas { width, height: grid.height() }, Placement { horizontal: align, vertical: VerticalAlign::Top })
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::path::PathBuf;
+
+use crate::toml::{self, Value};
+
+/// A setting from the config file, `key = value`. Keys are spelled like the command line options
+/// they stand for.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Setting {
+    Flag(bool),
+    Value(String),
+}
+
+/// Defaults and named presets read from a config file like this one:
+///
+/// ```toml
+/// palette = "america"
+/// height = 16
+///
+/// [preset.motd]
+/// motd = true
+/// columns = 60
+/// ```
+///
+/// Only the parts of TOML that [`toml`] reads are understood.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Config {
+    defaults: Vec<(String, Setting)>,
+    presets: Vec<(String, Vec<(String, Setting)>)>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for table in toml::parse(text).map_err(|e| e.to_string())? {
+            let settings = table.entries.into_iter()
+                .map(|entry| {
+                    let setting = match entry.value {
+                        Value::Bool(b) => Setting::Flag(b),
+                        value => Setting::Value(value.to_string()),
+                    };
+                    (entry.key.replace('_', "-"), setting)
+                })
+                .collect();
+            if table.name.is_empty() {
+                config.defaults = settings;
+            } else if let Some(name) = table.name.strip_prefix("preset.").filter(|_| !table.array) {
+                config.presets.push((name.to_string(), settings));
+            } else {
+                return Err(format!("line {}: Unknown table \"{}\", only [preset.NAME] tables are allowed", table.line, table.name));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The command line arguments the defaults and, if one is named, a preset stand for, to be put
+    /// ahead of the real arguments so those win.
+    pub fn args(&self, preset: Option<&str>) -> Result<Vec<String>, String> {
+        let mut settings = self.defaults.clone();
+        if let Some(name) = preset {
+            let (_, preset) = self.presets.iter()
+                .find(|(n, _)| n == name)
+                .ok_or_else(|| format!("No preset named \"{}\" in the config file", name))?;
+            settings.extend(preset.iter().cloned());
+        }
+        let mut args = Vec::new();
+        for (key, setting) in settings {
+            match setting {
+                Setting::Flag(true) => args.push(format!("--{}", key)),
+                Setting::Flag(false) => {}
+                Setting::Value(value) => args.push(format!("--{}={}", key, value)),
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Where the config file lives: `$XDG_CONFIG_HOME/ship_gen/config.toml`, falling back on
+/// `~/.config/ship_gen/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    Some(dir()?.join("config.toml"))
+}
+
+/// Where palette files live, `palettes/` next to the config file, each named for the palette it
+/// defines, like `mytheme.toml`.
+pub fn palettes_dir() -> Option<PathBuf> {
+    Some(dir()?.join("palettes"))
+}
+
+fn dir() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("ship_gen"))
+}
@@ -0,0 +1,92 @@
+/// What the console stdout is written to can show.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Support {
+    /// Escape codes for color and moving the cursor
+    pub ansi: bool,
+    /// UTF-8, in a font with box drawing characters
+    pub unicode: bool,
+}
+
+/// Gets the console ready for colored UTF-8 output where it needs it and says how far that got.
+/// Only Windows consoles need anything doing: they're switched to reading escape codes and to the
+/// UTF-8 code page, which old versions of conhost can't do, and which doesn't help a console
+/// still drawing in its raster font. The console is left that way when ship_gen exits, as Windows
+/// Terminal and recent versions of conhost already start out.
+pub fn setup() -> Support {
+    #[cfg(windows)]
+    {
+        windows::setup()
+    }
+    #[cfg(not(windows))]
+    {
+        Support { ansi: true, unicode: true }
+    }
+}
+
+/// Columns and lines of the terminal stdout is written to, or `None` if it isn't one.
+pub fn size() -> Option<(usize, usize)> {
+    #[cfg(unix)]
+    {
+        // Safety: TIOCGWINSZ only fills in the winsize it's given
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        let found = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        Some((size.ws_col as usize, size.ws_row as usize)).filter(|&(columns, lines)| found && columns > 0 && lines > 0)
+    }
+    #[cfg(windows)]
+    {
+        windows::size()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::env;
+
+    use windows_sys::Win32::Globalization::CP_UTF8;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetConsoleScreenBufferInfo, GetCurrentConsoleFontEx, GetStdHandle, SetConsoleMode,
+        SetConsoleOutputCP, CONSOLE_FONT_INFOEX, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_OUTPUT_HANDLE,
+    };
+
+    use super::Support;
+
+    pub(super) fn setup() -> Support {
+        // Safety: these only read and set the console's settings, through a handle Windows owns
+        unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                // Not a console, so a pipe or a file, which take whatever they're given
+                return Support { ansi: true, unicode: true };
+            }
+            let ansi = mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0;
+            let utf8 = SetConsoleOutputCP(CP_UTF8) != 0;
+            // Windows Terminal draws every character itself, whatever conhost says its font is
+            let raster_font = env::var_os("WT_SESSION").is_none() && {
+                let mut font: CONSOLE_FONT_INFOEX = std::mem::zeroed();
+                font.cbSize = std::mem::size_of::<CONSOLE_FONT_INFOEX>() as u32;
+                GetCurrentConsoleFontEx(handle, 0, &mut font) != 0
+                    && String::from_utf16_lossy(&font.FaceName).trim_end_matches('\0') == "Terminal"
+            };
+            Support { ansi, unicode: utf8 && !raster_font }
+        }
+    }
+
+    pub(super) fn size() -> Option<(usize, usize)> {
+        // Safety: this only reads the console's settings into the struct it's given
+        unsafe {
+            let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+            if GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) == 0 {
+                return None;
+            }
+            let window = info.srWindow;
+            Some(((window.Right - window.Left + 1) as usize, (window.Bottom - window.Top + 1) as usize))
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::grid::{Cell, Grid};
+use crate::palette::Color;
+use crate::rocket::{Part, Rocket};
+
+/// Columns between the two rockets, with the marker in the middle
+const GUTTER: usize = 5;
+/// Marks lines where the rockets have different sections
+const MARKER: char = '≠';
+/// Color for the sections both rockets share, so the ones that differ stand out
+const SHARED: Color = Color::rgb(110, 110, 110);
+
+/// Draws `a` and `b` side by side, their sections lined up from the top. Sections that both have
+/// starting on the same line are grayed out, and lines where either has a section the other
+/// doesn't are marked between them. Returns the drawing and how many sections differ.
+pub fn side_by_side(a: &Rocket, b: &Rocket) -> (Grid, usize) {
+    let (left, left_differs) = draw(a, b);
+    let (right, right_differs) = draw(b, a);
+    let mut grid = Grid::new(left.width() + GUTTER + right.width(), left.height().max(right.height()));
+    grid.draw(0, 0, &left);
+    grid.draw(left.width() + GUTTER, 0, &right);
+    for y in 0..grid.height() {
+        if left_differs.get(y).copied().unwrap_or(true) || right_differs.get(y).copied().unwrap_or(true) {
+            grid.set(left.width() + GUTTER / 2, y, Cell { ch: MARKER, ..Cell::BLANK });
+        }
+    }
+    let differing = unshared(a, b).len() + unshared(b, a).len();
+    (grid, differing)
+}
+
+/// Draws `rocket`, graying out the sections `other` shares, and says for each line whether it's
+/// part of a section `other` doesn't have.
+fn draw(rocket: &Rocket, other: &Rocket) -> (Grid, Vec<bool>) {
+    let mut grid = rocket.to_grid();
+    let unshared = unshared(rocket, other);
+    let mut differs = Vec::with_capacity(grid.height());
+    for (i, part) in rocket.sections().iter().enumerate() {
+        for _ in 0..part.height() {
+            let y = differs.len();
+            differs.push(unshared.contains(&i));
+            if !unshared.contains(&i) {
+                for x in 0..grid.width() {
+                    let cell = grid.get(x, y);
+                    grid.set(x, y, Cell { color: Some(SHARED), ..cell });
+                }
+            }
+        }
+    }
+    (grid, differs)
+}
+
+/// Indexes of the sections of `rocket` that `other` doesn't have starting on the same line.
+fn unshared(rocket: &Rocket, other: &Rocket) -> Vec<usize> {
+    let others = starts(other);
+    starts(rocket).into_iter()
+        .enumerate()
+        .filter(|(_, section)| !others.contains(section))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Each section with the line it starts on.
+fn starts(rocket: &Rocket) -> Vec<(usize, &Part)> {
+    let mut y = 0;
+    rocket.sections().iter()
+        .map(|part| {
+            let start = y;
+            y += part.height();
+            (start, &**part)
+        })
+        .collect()
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use crate::rocket::{BuildOptions, Part, PartType, Profile, Rocket};
+
+/// One in this many rockets built from a random seed is swapped for an easter egg.
+pub const ODDS: u64 = 100;
+
+/// A whole rocket drawn by hand rather than built from parts, which now and then turns up in place
+/// of a built one. Its sections are parts only so it's drawn, colored and described like any other
+/// rocket; they aren't in any pack and nothing else is built from them.
+#[derive(Clone, Copy, Debug)]
+pub struct Egg {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Each section's kind, top and bottom widths and shape, nose first. Shapes are padded out to
+    /// the same width, so they stay lined up as drawn rather than each being centered.
+    sections: &'static [(PartType, usize, usize, &'static str)],
+}
+
+pub const EGGS: [Egg; 3] = [
+    Egg {
+        id: "duck",
+        name: "Rubber Duck",
+        sections: &[
+        (PartType::Tip, 0, 0, "     __    "),
+        (PartType::Body, 0, 3, "   <(o )___\n    ( ._> /"),
+        (PartType::Body, 3, 3, "    |`---'|\n    |     |\n    |  O  |\n    |     |"),
+        (PartType::Engine, 3, 1, "     \\___/ "),
+        (PartType::Exhaust, 1, 0, "     (   ) "),
+        (PartType::Exhaust, 0, 0, "      ' '  "),
+        ],
+    },
+    Egg {
+        id: "banana",
+        name: "Banana",
+        sections: &[
+        (PartType::Tip, 0, 0, "     _ "),
+        (PartType::Body, 0, 3, "    // \n   //| \n  // | "),
+        (PartType::Body, 3, 3, " ((  | \n ((  | "),
+        (PartType::Body, 3, 1, "  \\\\ | \n   \\\\| "),
+        (PartType::Engine, 1, 1, "   \\_/ "),
+        (PartType::Exhaust, 1, 0, "   ( ) "),
+        (PartType::Exhaust, 0, 0, "    '  "),
+        ],
+    },
+    Egg {
+        id: "saturn_v",
+        name: "Saturn V",
+        sections: &[
+        (PartType::Tip, 0, 0, "    |    "),
+        (PartType::Body, 0, 1, "   /_\\   "),
+        (PartType::Body, 1, 1, "   |=|   \n   |U|   \n   |S|   \n   |A|   "),
+        (PartType::Body, 1, 3, "  /===\\  "),
+        (PartType::Body, 3, 3, "  |   |  \n  |   |  "),
+        (PartType::Body, 3, 5, " /=====\\ "),
+        (PartType::Body, 5, 5, " |     | \n |     | \n |     | "),
+        (PartType::Body, 5, 7, "/_______\\"),
+        (PartType::Engine, 7, 5, "/_\\/_\\/_\\"),
+        (PartType::Exhaust, 5, 0, " '  '  ' "),
+        ],
+    },
+];
+
+impl Egg {
+    pub fn height(&self) -> usize {
+        self.sections.iter().map(|&(_, _, _, shape)| shape.lines().count()).sum()
+    }
+
+    pub fn width(&self) -> usize {
+        self.sections.iter()
+            .flat_map(|&(_, _, _, shape)| shape.lines())
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The egg as a rocket, with no seed since it wasn't built from one.
+    pub fn rocket(&self) -> Rocket {
+        let sections = self.sections.iter().enumerate()
+            .map(|(i, &(part_type, top_width, bottom_width, shape))| {
+                let height = shape.lines().count();
+                let part = Part::new(part_type, top_width, bottom_width, height, 1, shape);
+                Arc::new(part.named(format!("egg_{}_{}", self.id, i), self.name))
+            })
+            .collect();
+        Rocket::assembled(sections)
+    }
+}
+
+/// An easter egg to swap in for a rocket `max_height` lines tall, about one roll of `dice` in
+/// [`ODDS`], or `None` to keep the built one. Only eggs that fit the height, `--max-width` and
+/// profile are rolled for, so chat rockets only get plain ASCII ones.
+pub fn roll(dice: u64, max_height: usize, options: &BuildOptions) -> Option<Rocket> {
+    if !dice.is_multiple_of(ODDS) {
+        return None;
+    }
+    let fits: Vec<Rocket> = EGGS.iter()
+        .filter(|egg| egg.height() <= max_height && options.max_width.is_none_or(|width| egg.width() <= width))
+        .map(Egg::rocket)
+        .filter(|rocket| options.profile != Profile::Chat || rocket.sections().iter().all(|p| p.chat_safe()))
+        .collect();
+    if fits.is_empty() {
+        return None;
+    }
+    let pick = (dice / ODDS % fits.len() as u64) as usize;
+    fits.into_iter().nth(pick)
+}
@@ -0,0 +1,59 @@
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use crate::render::Options;
+use crate::rocket::Rocket;
+
+/// Renders the plain text art for `height` and `seed`, or `None` if the rocket can't be built.
+fn art(height: usize, seed: u64) -> Option<String> {
+    Rocket::generate_with(height, seed, &Options::default()).ok()
+}
+
+/// Writes the rocket for `height` and `seed` into `buf` as a NUL terminated UTF-8 string and
+/// returns its length in bytes, not counting the NUL. Returns 0 if `height` is below 3.
+///
+/// If the art doesn't fit in `buf_len` bytes including the NUL, nothing but an empty string is
+/// written, so `buf` never holds a partial character. Passing a null `buf` and a `buf_len` of 0
+/// asks for the length alone; allocate one byte more than it and call again.
+///
+/// # Safety
+///
+/// `buf` must be null or point to `buf_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ship_gen_generate(height: usize, seed: u64, buf: *mut c_char, buf_len: usize) -> usize {
+    let art = match art(height, seed) {
+        Some(art) => art,
+        None => return 0,
+    };
+    if buf.is_null() || buf_len == 0 {
+        return art.len();
+    }
+    if art.len() < buf_len {
+        ptr::copy_nonoverlapping(art.as_ptr(), buf.cast(), art.len());
+        *buf.add(art.len()) = 0;
+    } else {
+        *buf = 0;
+    }
+    art.len()
+}
+
+/// Returns the rocket for `height` and `seed` as a newly allocated NUL terminated UTF-8 string,
+/// or null if `height` is below 3. Release it with [`ship_gen_free`], not `free()`.
+#[no_mangle]
+pub extern "C" fn ship_gen_rocket(height: usize, seed: u64) -> *mut c_char {
+    art(height, seed)
+        .and_then(|art| CString::new(art).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Releases a string returned by [`ship_gen_rocket`]. Null is ignored.
+///
+/// # Safety
+///
+/// `rocket` must be null or a pointer from [`ship_gen_rocket`] that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ship_gen_free(rocket: *mut c_char) {
+    if !rocket.is_null() {
+        drop(CString::from_raw(rocket));
+    }
+}
@@ -0,0 +1,22 @@
+use crate::grid::{Cell, Grid};
+use crate::rocket;
+
+/// The seed for a build fingerprint, like a commit hash: the text seed of it without any spaces
+/// or newline around it, so `$(git rev-parse HEAD)` and a CI variable holding the same hash agree.
+pub fn seed(fingerprint: &str) -> u64 {
+    rocket::seed_from_text(fingerprint.trim())
+}
+
+/// Writes the fingerprint under the rocket, the two centered on each other, so a build log shows
+/// which build the rocket stands for.
+pub fn label(rocket: &Grid, fingerprint: &str) -> Grid {
+    let text: Vec<char> = fingerprint.trim().chars().collect();
+    let width = rocket.width().max(text.len());
+    let mut grid = Grid::new(width, rocket.height() + 1);
+    grid.draw((width - rocket.width()) / 2, 0, rocket);
+    let x = (width - text.len()) / 2;
+    for (i, &ch) in text.iter().enumerate() {
+        grid.set(x + i, rocket.height(), Cell { ch, ..Cell::BLANK });
+    }
+    grid
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+/// Columns between rockets standing side by side
+const GAP: usize = 3;
+
+/// How a row of rockets flies together, each dropped back some rows from the one ahead of it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Formation {
+    /// Led from the middle, the rest dropping back either side of it
+    V,
+    /// Led from the left, each one further back than the last
+    Diagonal,
+    /// Every other one dropped back
+    Stagger,
+}
+
+impl Formation {
+    /// How many steps back from the front the `i`th of `count` rockets flies.
+    fn steps(self, i: usize, count: usize) -> usize {
+        match self {
+            // Doubled so an even number of rockets can be led by the middle two
+            Formation::V => (2 * i).abs_diff(count.saturating_sub(1)) / 2,
+            Formation::Diagonal => i,
+            Formation::Stagger => i % 2,
+        }
+    }
+}
+
+impl FromStr for Formation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v" => Ok(Formation::V),
+            "diag" | "diagonal" => Ok(Formation::Diagonal),
+            "stagger" => Ok(Formation::Stagger),
+            _ => Err(format!("Unknown formation \"{}\"", s)),
+        }
+    }
+}
+
+/// Stands the rockets side by side, left to right, with their grounds on the same line as if they
+/// shared a launch pad, or flies them in `formation`, each step back a third of the tallest
+/// rocket's height. Each is given with the row of it that's its ground, see
+/// [`crate::render::ground`].
+pub fn row(rockets: &[(Grid, usize)], formation: Option<Formation>) -> Grid {
+    let step = (rockets.iter().map(|(grid, _)| grid.height()).max().unwrap_or(0) / 3).max(1);
+    let ground = rockets.iter().map(|&(_, ground)| ground).max().unwrap_or(0);
+    let tops: Vec<usize> = rockets.iter()
+        .enumerate()
+        .map(|(i, &(_, g))| ground - g + formation.map_or(0, |formation| step * formation.steps(i, rockets.len())))
+        .collect();
+    let height = rockets.iter().zip(&tops).map(|((grid, _), top)| top + grid.height()).max().unwrap_or(0);
+    let width = rockets.iter().map(|(grid, _)| grid.width()).sum::<usize>() + GAP * rockets.len().saturating_sub(1);
+    let mut pad = Grid::new(width, height);
+    let mut x = 0;
+    for ((grid, _), &top) in rockets.iter().zip(&tops) {
+        pad.draw(x, top, grid);
+        x += grid.width() + GAP;
+    }
+    pad
+}
@@ -0,0 +1,37 @@
+use font8x8::{UnicodeFonts, BASIC_FONTS, BLOCK_FONTS, BOX_FONTS, LATIN_FONTS};
+
+/// Width and height in pixels of one glyph in the embedded font.
+pub const GLYPH_SIZE: usize = 8;
+
+/// Looks up the 8x8 bitmap for a character. Each byte is one row, top to bottom, with the least
+/// significant bit as the leftmost pixel. Characters the font doesn't cover come back as `?` so
+/// a missing glyph is visible instead of silently blank.
+pub fn glyph(ch: char) -> [u8; GLYPH_SIZE] {
+    BASIC_FONTS.get(ch)
+        .or_else(|| BOX_FONTS.get(ch))
+        .or_else(|| LATIN_FONTS.get(ch))
+        .or_else(|| BLOCK_FONTS.get(ch))
+        .or_else(|| braille(ch))
+        .or_else(|| BASIC_FONTS.get('?'))
+        .unwrap_or_default()
+}
+
+/// The font has no Braille patterns, but they're regular enough to draw: each of the eight dots
+/// is a 2x1 pixel block in a 2 column by 4 row layout.
+fn braille(ch: char) -> Option<[u8; GLYPH_SIZE]> {
+    let bits = (ch as u32).checked_sub(0x2800).filter(|&b| b <= 0xff)?;
+    // Dot number to (column, row), in the order of the pattern's bits
+    const DOTS: [(usize, usize); 8] = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)];
+    let mut glyph = [0; GLYPH_SIZE];
+    for (i, (column, row)) in DOTS.iter().enumerate() {
+        if bits & (1 << i) != 0 {
+            glyph[row * 2] |= 0b11 << (column * 4 + 1);
+        }
+    }
+    Some(glyph)
+}
+
+/// Whether the pixel at (`x`, `y`) is set in a glyph bitmap.
+pub fn pixel(glyph: &[u8; GLYPH_SIZE], x: usize, y: usize) -> bool {
+    glyph[y] & (1 << x) != 0
+}
@@ -0,0 +1,63 @@
+use crate::bubble;
+use crate::grid::{Cell, Grid};
+
+/// Narrowest the quote is wrapped to, however narrow the rocket, so it isn't a word a line.
+pub const MIN_WIDTH: usize = 24;
+
+/// Quotes about space and engineering, each with who said it on a line of its own.
+pub const QUOTES: [&str; 16] = [
+    "That's one small step for man, one giant leap for mankind.\n— Neil Armstrong",
+    "Houston, Tranquility Base here. The Eagle has landed.\n— Neil Armstrong",
+    "Houston, we've had a problem.\n— Jim Lovell, Apollo 13",
+    "Roger, zero G and I feel fine.\n— John Glenn",
+    "Godspeed, John Glenn.\n— Scott Carpenter",
+    "Poyekhali! (Let's go!)\n— Yuri Gagarin",
+    "Earth is the cradle of humanity, but one cannot live in the cradle forever.\n— Konstantin Tsiolkovsky",
+    "We choose to go to the Moon in this decade and do the other things, not because they are easy, but because they are hard.\n— John F. Kennedy",
+    "Once you get to Earth orbit, you're halfway to anywhere in the solar system.\n— Robert A. Heinlein",
+    "Any sufficiently advanced technology is indistinguishable from magic.\n— Arthur C. Clarke",
+    "Space is big. You just won't believe how vastly, hugely, mind-bogglingly big it is.\n— Douglas Adams",
+    "We are made of star-stuff.\n— Carl Sagan",
+    "If I have seen further it is by standing on the shoulders of giants.\n— Isaac Newton",
+    "I have loved the stars too fondly to be fearful of the night.\n— Sarah Williams",
+    "The first 90 percent of the code accounts for the first 90 percent of the development time. The remaining 10 percent of the code accounts for the other 90 percent of the development time.\n— Tom Cargill",
+    "Keep it simple, stupid.\n— Kelly Johnson",
+];
+
+/// Reads quotes from a file in the format `fortune` uses: quotes separated by lines holding only
+/// `%`. Empty ones are skipped.
+pub fn parse(text: &str) -> Vec<String> {
+    let mut quotes = vec![String::new()];
+    for line in text.lines() {
+        if line.trim_end() == "%" {
+            quotes.push(String::new());
+        } else {
+            let quote = quotes.last_mut().expect("there's always a quote being read");
+            quote.push_str(line);
+            quote.push('\n');
+        }
+    }
+    quotes.into_iter().map(|quote| quote.trim().to_string()).filter(|quote| !quote.is_empty()).collect()
+}
+
+/// Puts `quote` under the rocket, a line apart from it, wrapped to the rocket's width or
+/// [`MIN_WIDTH`] if that's wider. Lines starting with a dash, like `— Neil Armstrong` or
+/// `-- Mark Twain`, say who said it, and are lined up on the right.
+pub fn under(rocket: &Grid, quote: &str) -> Grid {
+    let width = rocket.width().max(MIN_WIDTH);
+    let lines: Vec<(String, bool)> = quote.lines()
+        .flat_map(|line| {
+            let attribution = line.starts_with('—') || line.starts_with("--");
+            bubble::wrap(line, width).into_iter().map(move |line| (line, attribution))
+        })
+        .collect();
+    let mut grid = Grid::new(width, rocket.height() + 1 + lines.len());
+    grid.draw(0, 0, rocket);
+    for (i, (line, attribution)) in lines.iter().enumerate() {
+        let x = if *attribution { width - line.chars().count() } else { 0 };
+        for (j, ch) in line.chars().enumerate() {
+            grid.set(x + j, rocket.height() + 1 + i, Cell { ch, ..Cell::BLANK });
+        }
+    }
+    grid
+}
@@ -0,0 +1,503 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::rocket::{Part, PartType};
+use crate::toml::{self, Value};
+
+/// Most times a `{min,max}` repeat can ask for
+const MAX_REPEAT: usize = 100;
+/// Most states a grammar compiles to, so repeats inside repeats can't take forever to build from
+const MAX_STATES: usize = 10_000;
+
+/// The grammar `--engine grammar` builds from unless given another.
+pub const BUILTIN: &str = r#"# Up to two tips on a nose cone, one to three stages, then the engine and a short trail of exhaust
+rocket = "tip{0,2} nose stage{1,3} engine exhaust{0,3}"
+# Stages change width at the top, or narrow to the next width at the bottom
+stage = "straight+ fins? | widen straight+ fins? | straight+ narrow"
+"#;
+
+/// Kinds of part a grammar can ask for.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Class {
+    Type(PartType),
+    /// A body that starts from a point
+    Nose,
+    /// A body as wide at the bottom as at the top, without fins
+    Straight,
+    /// A body with fins sticking out past its hull
+    Fins,
+    Widen,
+    Narrow,
+}
+
+impl Class {
+    fn named(name: &str) -> Option<Class> {
+        Some(match name {
+            "tip" => Class::Type(PartType::Tip),
+            "body" => Class::Type(PartType::Body),
+            "engine" => Class::Type(PartType::Engine),
+            "exhaust" => Class::Type(PartType::Exhaust),
+            "nose" => Class::Nose,
+            "straight" => Class::Straight,
+            "fins" => Class::Fins,
+            "widen" => Class::Widen,
+            "narrow" => Class::Narrow,
+            _ => return None,
+        })
+    }
+
+    fn matches(&self, part: &Part) -> bool {
+        let (top, bottom) = (part.top_width(), part.bottom_width());
+        let body = part.part_type() == PartType::Body;
+        match self {
+            Class::Type(part_type) => part.part_type() == *part_type,
+            Class::Nose => body && top == 0,
+            Class::Straight => body && top > 0 && top == bottom && !part.finned(),
+            Class::Fins => body && top > 0 && top == bottom && part.finned(),
+            Class::Widen => body && top > 0 && bottom > top,
+            Class::Narrow => body && bottom < top,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Class(Class),
+    Rule(String),
+    Seq(Vec<Expr>),
+    Alt(Vec<Expr>),
+    Repeat(Box<Expr>, usize, Option<usize>),
+}
+
+/// Rules for what order parts go in, written like regular expressions over kinds of part:
+///
+/// ```toml
+/// rocket = "tip? nose stage{1,3} engine exhaust*"
+/// stage = "straight+ fins? | widen straight+"
+/// ```
+///
+/// Building starts from the `rocket` rule. Rules can use each other but not themselves, grouped with
+/// parentheses, repeated with `?`, `*`, `+` or `{min,max}` up to 100 times and given alternatives
+/// with `|`. Parts are asked for as `tip`, `body`, `engine` and `exhaust`, or as the kinds of body
+/// `nose`, `straight`, `fins`, `widen` and `narrow`. Each part still has to be as wide at the top as
+/// the one above it is at the bottom, and the rocket as tall as asked, so only the ways through the
+/// rules that can be finished are taken.
+#[derive(Clone, Debug)]
+pub struct Grammar {
+    /// Every way through the rules, as states joined by the kinds of part between them
+    states: Vec<Vec<(Class, usize)>>,
+    accepting: Vec<bool>,
+}
+
+impl Grammar {
+    pub fn builtin() -> Grammar {
+        Grammar::parse(BUILTIN).expect("the built in grammar is valid")
+    }
+
+    pub fn parse(text: &str) -> Result<Grammar, String> {
+        let mut rules: Vec<(String, Expr)> = Vec::new();
+        for table in toml::parse(text).map_err(|e| e.to_string())? {
+            if !table.name.is_empty() {
+                return Err(format!("line {}: Grammars are rules only, not tables", table.line));
+            }
+            for entry in table.entries {
+                let error = |message: String| format!("line {}: {}", entry.line, message);
+                let Value::String(rule) = &entry.value else {
+                    return Err(error(format!("Rule \"{}\" must be a string", entry.key)));
+                };
+                if Class::named(&entry.key).is_some() {
+                    return Err(error(format!("\"{}\" is a kind of part, rules need other names", entry.key)));
+                }
+                if rules.iter().any(|(name, _)| *name == entry.key) {
+                    return Err(error(format!("Rule \"{}\" is defined twice", entry.key)));
+                }
+                let expr = Parser { tokens: tokenize(rule).map_err(error)?, pos: 0 }.rule().map_err(error)?;
+                rules.push((entry.key, expr));
+            }
+        }
+        if !rules.iter().any(|(name, _)| name == "rocket") {
+            return Err("The grammar has no \"rocket\" rule to start from".to_string());
+        }
+        let rocket = inline(&Expr::Rule("rocket".to_string()), &rules, &mut Vec::new())?;
+        if states(&rocket) > MAX_STATES {
+            return Err(format!("The grammar has too many ways through it, more than {} states, try smaller repeats", MAX_STATES));
+        }
+        Ok(Grammar::compile(&rocket))
+    }
+
+    /// Turns the rules into states, with a state for every point between two parts.
+    fn compile(rocket: &Expr) -> Grammar {
+        let mut nfa = Nfa::default();
+        let start = nfa.state();
+        let end = nfa.add(rocket, start);
+        // Skipping the states that are only ways around others leaves edges that each take a part,
+        // and only the states those edges lead to are kept
+        let mut kept = vec![start];
+        // Where each of the NFA's states is in `kept`, if it's been kept
+        let mut places = vec![None; nfa.edges.len()];
+        places[start] = Some(0);
+        let mut grammar = Grammar { states: Vec::new(), accepting: Vec::new() };
+        while let Some(&state) = kept.get(grammar.states.len()) {
+            let closure = nfa.closure(state);
+            let mut edges: Vec<(Class, usize)> = closure.iter().flat_map(|&s| nfa.edges[s].iter().copied()).collect();
+            for (_, next) in &mut edges {
+                *next = *places[*next].get_or_insert_with(|| {
+                    kept.push(*next);
+                    kept.len() - 1
+                });
+            }
+            grammar.states.push(edges);
+            grammar.accepting.push(closure.contains(&end));
+        }
+        grammar
+    }
+
+    /// Picks the parts for a rocket `max_height` lines tall, nose first, out of every way of
+    /// building one, with each part as likely as its weight among the others of its kind.
+    pub fn build<R: Rng + ?Sized>(&self, max_height: usize, parts: &[Arc<Part>], rng: &mut R) -> Result<Vec<Arc<Part>>, String> {
+        let widths = parts.iter().map(|p| p.top_width().max(p.bottom_width())).max().unwrap_or(0) + 1;
+        let index = |state: usize, width: usize, height: usize| (state * widths + width) * (max_height + 1) + height;
+
+        // How likely each part is to be picked, for each kind of part and width it can go under
+        let mut classes: Vec<(Class, Choices)> = Vec::new();
+        for &(class, _) in self.states.iter().flatten() {
+            if classes.iter().all(|(c, _)| *c != class) {
+                let by_width = (0..widths).map(|width| {
+                    let fitting: Vec<&Arc<Part>> = parts.iter().filter(|p| p.top_width() == width && class.matches(p)).collect();
+                    let total: usize = fitting.iter().map(|p| p.selection_weight()).sum();
+                    fitting.into_iter().map(|p| (p, p.selection_weight() as f64 / total as f64)).collect()
+                });
+                classes.push((class, by_width.collect()));
+            }
+        }
+        let choices = |class: Class, width: usize| {
+            let (_, by_width) = classes.iter().find(|(c, _)| *c == class).expect("every class in the grammar is listed");
+            by_width[width].iter().copied()
+        };
+
+        // The chance of finishing a rocket from each state, width and height, tallest first
+        let mut finish = vec![0.0; self.states.len() * widths * (max_height + 1)];
+        for height in (0..=max_height).rev() {
+            for (state, edges) in self.states.iter().enumerate() {
+                for width in 0..widths {
+                    let mut chance = if self.accepting[state] && height == max_height { 1.0 } else { 0.0 };
+                    for &(class, next) in edges {
+                        for (part, p) in choices(class, width).filter(|(part, _)| height + part.height() <= max_height) {
+                            chance += p * finish[index(next, part.bottom_width(), height + part.height())];
+                        }
+                    }
+                    finish[index(state, width, height)] = chance;
+                }
+            }
+        }
+        if finish[index(0, 0, 0)] == 0.0 {
+            return Err(format!("The grammar can't build a rocket {} lines tall from these parts", max_height));
+        }
+
+        let (mut state, mut width, mut height) = (0, 0, 0);
+        let mut sections = Vec::new();
+        while height < max_height {
+            // Drawn from the top 53 bits, as many as an f64 holds exactly
+            let mut pick = finish[index(state, width, height)] * (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+            let mut chosen = None;
+            'pick: for &(class, next) in &self.states[state] {
+                for (part, p) in choices(class, width).filter(|(part, _)| height + part.height() <= max_height) {
+                    let chance = p * finish[index(next, part.bottom_width(), height + part.height())];
+                    if chance > 0.0 {
+                        // Rounding can leave the pick past every chance, so the last one catches it
+                        chosen = Some((part, next));
+                        if pick < chance {
+                            break 'pick;
+                        }
+                        pick -= chance;
+                    }
+                }
+            }
+            let (part, next) = chosen.expect("a state that can finish has a way on");
+            sections.push(Arc::clone(part));
+            (state, width, height) = (next, part.bottom_width(), height + part.height());
+        }
+        Ok(sections)
+    }
+}
+
+/// The parts of some kind that can go under each width, and how likely each is.
+type Choices<'a> = Vec<Vec<(&'a Arc<Part>, f64)>>;
+
+#[derive(Default)]
+struct Nfa {
+    edges: Vec<Vec<(Class, usize)>>,
+    /// Ways from one state to another without taking a part
+    skips: Vec<Vec<usize>>,
+}
+
+impl Nfa {
+    fn state(&mut self) -> usize {
+        self.edges.push(Vec::new());
+        self.skips.push(Vec::new());
+        self.edges.len() - 1
+    }
+
+    /// Adds the states for `expr` starting from `from`, returning the state it ends on.
+    fn add(&mut self, expr: &Expr, from: usize) -> usize {
+        match expr {
+            Expr::Class(class) => {
+                let to = self.state();
+                self.edges[from].push((*class, to));
+                to
+            }
+            Expr::Rule(_) => unreachable!("rules are filled in before compiling"),
+            Expr::Seq(exprs) => exprs.iter().fold(from, |at, expr| self.add(expr, at)),
+            Expr::Alt(exprs) => {
+                let to = self.state();
+                for expr in exprs {
+                    let start = self.state();
+                    self.skips[from].push(start);
+                    let end = self.add(expr, start);
+                    self.skips[end].push(to);
+                }
+                to
+            }
+            Expr::Repeat(expr, min, max) => {
+                let mut at = from;
+                for _ in 0..*min {
+                    at = self.add(expr, at);
+                }
+                match max {
+                    None => {
+                        let start = self.state();
+                        self.skips[at].push(start);
+                        let end = self.add(expr, start);
+                        self.skips[end].push(start);
+                        start
+                    }
+                    Some(max) => {
+                        let to = self.state();
+                        for _ in *min..*max {
+                            self.skips[at].push(to);
+                            at = self.add(expr, at);
+                        }
+                        self.skips[at].push(to);
+                        to
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every state reachable from `state` without taking a part, itself included.
+    fn closure(&self, state: usize) -> Vec<usize> {
+        let mut reached = vec![state];
+        let mut seen = HashSet::from([state]);
+        let mut i = 0;
+        while i < reached.len() {
+            for &next in &self.skips[reached[i]] {
+                if seen.insert(next) {
+                    reached.push(next);
+                }
+            }
+            i += 1;
+        }
+        reached
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Name(String),
+    Bar,
+    Open,
+    Close,
+    Repeat(usize, Option<usize>),
+}
+
+fn tokenize(rule: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = rule.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ' ' | '\t' | '\n' => {}
+            '|' => tokens.push(Token::Bar),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '?' => tokens.push(Token::Repeat(0, Some(1))),
+            '*' => tokens.push(Token::Repeat(0, None)),
+            '+' => tokens.push(Token::Repeat(1, None)),
+            '{' => {
+                let mut bounds = String::new();
+                let mut closed = false;
+                for ch in chars.by_ref() {
+                    if ch == '}' {
+                        closed = true;
+                        break;
+                    }
+                    bounds.push(ch);
+                }
+                if !closed {
+                    return Err(format!("Unclosed \"{{{}\" in rule", bounds));
+                }
+                let number = |s: &str| s.trim().parse::<usize>().map_err(|_| format!("Can't read repeat \"{{{}}}\"", bounds));
+                let (min, max) = match bounds.split_once(',') {
+                    Some((min, "")) => (number(min)?, None),
+                    Some((min, max)) => (number(min)?, Some(number(max)?)),
+                    None => (number(&bounds)?, Some(number(&bounds)?)),
+                };
+                if max.is_some_and(|max| max < min) {
+                    return Err(format!("Repeat \"{{{}}}\" has a minimum above its maximum", bounds));
+                }
+                if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+                    return Err(format!("Repeat \"{{{}}}\" goes over the limit of {}", bounds, MAX_REPEAT));
+                }
+                tokens.push(Token::Repeat(min, max));
+            }
+            ch if ch.is_alphanumeric() || ch == '_' || ch == '-' => {
+                let mut name = ch.to_string();
+                while let Some(&ch) = chars.peek().filter(|ch| ch.is_alphanumeric() || **ch == '_' || **ch == '-') {
+                    name.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+            }
+            _ => return Err(format!("Unexpected \"{}\" in rule", ch)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// About how many states `expr` compiles to, never less, for turning away grammars too big to build
+/// from before compiling them.
+fn states(expr: &Expr) -> usize {
+    match expr {
+        Expr::Class(_) => 1,
+        Expr::Rule(_) => unreachable!("rules are filled in before counting"),
+        Expr::Seq(exprs) => exprs.iter().fold(0, |total, e| total.saturating_add(states(e))),
+        Expr::Alt(exprs) => exprs.iter().fold(1, |total, e| total.saturating_add(states(e)).saturating_add(1)),
+        Expr::Repeat(expr, min, max) => states(expr).saturating_mul(max.unwrap_or(min + 1).max(1)).saturating_add(1),
+    }
+}
+
+/// Fills in the rules `expr` names, `using` being the ones already being filled in.
+fn inline(expr: &Expr, rules: &[(String, Expr)], using: &mut Vec<String>) -> Result<Expr, String> {
+    Ok(match expr {
+        Expr::Class(class) => Expr::Class(*class),
+        Expr::Rule(name) => {
+            if using.contains(name) {
+                return Err(format!("Rule \"{}\" uses itself", name));
+            }
+            let (_, rule) = rules.iter()
+                .find(|(n, _)| n == name)
+                .ok_or_else(|| format!("\"{}\" isn't a kind of part or a rule", name))?;
+            using.push(name.clone());
+            let rule = inline(rule, rules, using)?;
+            using.pop();
+            rule
+        }
+        Expr::Seq(exprs) => Expr::Seq(exprs.iter().map(|e| inline(e, rules, using)).collect::<Result<_, _>>()?),
+        Expr::Alt(exprs) => Expr::Alt(exprs.iter().map(|e| inline(e, rules, using)).collect::<Result<_, _>>()?),
+        Expr::Repeat(expr, min, max) => Expr::Repeat(Box::new(inline(expr, rules, using)?), *min, *max),
+    })
+}
+
+/// Reads a rule, `alternative ('|' alternative)*`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn rule(mut self) -> Result<Expr, String> {
+        let expr = self.alternatives()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some(_) => Err("Unmatched \")\" in rule".to_string()),
+        }
+    }
+
+    fn alternatives(&mut self) -> Result<Expr, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.tokens.get(self.pos) == Some(&Token::Bar) {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Expr::Alt(alternatives) })
+    }
+
+    fn sequence(&mut self) -> Result<Expr, String> {
+        let mut items = Vec::new();
+        loop {
+            let mut item = match self.tokens.get(self.pos).cloned() {
+                Some(Token::Name(name)) => {
+                    self.pos += 1;
+                    Class::named(&name).map_or(Expr::Rule(name), Expr::Class)
+                }
+                Some(Token::Open) => {
+                    self.pos += 1;
+                    let expr = self.alternatives()?;
+                    if self.tokens.get(self.pos) != Some(&Token::Close) {
+                        return Err("Unclosed \"(\" in rule".to_string());
+                    }
+                    self.pos += 1;
+                    expr
+                }
+                Some(Token::Repeat(..)) => return Err("Nothing to repeat in rule".to_string()),
+                _ => break,
+            };
+            while let Some(&Token::Repeat(min, max)) = self.tokens.get(self.pos) {
+                self.pos += 1;
+                item = Expr::Repeat(Box::new(item), min, max);
+            }
+            items.push(item);
+        }
+        if items.is_empty() {
+            return Err("Empty alternative in rule".to_string());
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Expr::Seq(items) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Grammar::parse(text).expect_err("the grammar is turned away")
+    }
+
+    #[test]
+    fn rules_cant_use_themselves() {
+        assert_eq!(error("rocket = \"nose stage engine\"\nstage = \"straight stage?\""), "Rule \"stage\" uses itself");
+    }
+
+    #[test]
+    fn alternatives_cant_be_empty() {
+        assert_eq!(error("rocket = \"nose | \""), "line 1: Empty alternative in rule");
+    }
+
+    #[test]
+    fn repeats_are_bounded() {
+        assert_eq!(error("rocket = \"nose straight{3,1} engine\""), "line 1: Repeat \"{3,1}\" has a minimum above its maximum");
+        assert_eq!(error("rocket = \"nose straight{2\""), "line 1: Unclosed \"{2\" in rule");
+        assert_eq!(error("rocket = \"nose straight{0,3000} engine\""), "line 1: Repeat \"{0,3000}\" goes over the limit of 100");
+        assert_eq!(error("rocket = \"nose (straight{0,100}){0,100} engine\""),
+                   "The grammar has too many ways through it, more than 10000 states, try smaller repeats");
+    }
+
+    #[test]
+    fn builds_parts_in_order() {
+        let part = |part_type, top, bottom, shape| Arc::new(Part::new(part_type, top, bottom, 1, 1, shape));
+        let (nose, straight, engine) = (part(PartType::Body, 0, 1, "/'\\"), part(PartType::Body, 1, 1, "| |"), part(PartType::Engine, 1, 1, "/_\\"));
+        let parts = [part(PartType::Tip, 0, 0, "│"), Arc::clone(&nose), Arc::clone(&straight),
+                     part(PartType::Body, 1, 1, "/| |\\"), Arc::clone(&engine), part(PartType::Exhaust, 1, 1, "'")];
+        let grammar = Grammar::parse("rocket = \"nose straight+ engine\"").unwrap();
+        let built = grammar.build(4, &parts, &mut ChaCha8Rng::seed_from_u64(7)).unwrap();
+        let shapes: Vec<&str> = built.iter().map(|part| part.shape()).collect();
+        assert_eq!(shapes, [nose.shape(), straight.shape(), straight.shape(), engine.shape()]);
+        assert_eq!(grammar.build(1, &parts, &mut ChaCha8Rng::seed_from_u64(7)).unwrap_err(),
+                   "The grammar can't build a rocket 1 lines tall from these parts");
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+
+use crate::palette::{Color, Palette};
+use crate::rocket::PartType;
+
+/// One character of rendered output, remembering which kind of part drew it so renderers can
+/// color it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cell {
+    pub ch: char,
+    pub part_type: Option<PartType>,
+    /// Foreground color to use instead of the palette's color for `part_type`
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell { ch: ' ', part_type: None, color: None, background: None };
+
+    pub fn is_blank(&self) -> bool {
+        self.ch == ' '
+    }
+
+    /// Color the character should be drawn in, if any.
+    pub fn foreground(&self, palette: &Palette) -> Option<Color> {
+        self.color.or_else(|| self.part_type.map(|t| palette.color(t)))
+    }
+}
+
+/// A rectangular block of cells, the intermediate form every output format is rendered from.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Grid {
+        Grid { width, height, cells: vec![Cell::BLANK; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        self.cells[y * self.width + x] = cell;
+    }
+
+    /// Copies every non-blank cell of `other` onto this grid with its top left corner at
+    /// (`x`, `y`), clipping anything that falls outside.
+    pub fn draw(&mut self, x: usize, y: usize, other: &Grid) {
+        for (row, cells) in other.rows().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                if *cell != Cell::BLANK && x + col < self.width && y + row < self.height {
+                    self.set(x + col, y + row, *cell);
+                }
+            }
+        }
+    }
+
+    /// Like [`Grid::draw`], but `other` can start above or left of this grid, for sprites sliding
+    /// in and out of a frame.
+    pub fn overlay(&mut self, x: isize, y: isize, other: &Grid) {
+        for (row, cells) in other.rows().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let (x, y) = (x + col as isize, y + row as isize);
+                if *cell != Cell::BLANK && (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y) {
+                    self.set(x as usize, y as usize, *cell);
+                }
+            }
+        }
+    }
+
+    /// Cuts the grid down to at most `width` by `height` cells, keeping the top left.
+    pub fn crop(&self, width: usize, height: usize) -> Grid {
+        let mut cropped = Grid::new(self.width.min(width), self.height.min(height));
+        cropped.draw(0, 0, self);
+        cropped
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        // `max(1)` keeps `chunks` happy for an empty grid, which simply yields no rows
+        self.cells.chunks(self.width.max(1))
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row.iter().map(|c| c.ch).collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
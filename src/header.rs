@@ -0,0 +1,132 @@
+use crate::banner::{self, Layout};
+use crate::bubble;
+use crate::grid::{Cell, Grid};
+use crate::palette::Color;
+
+/// How a language writes comments.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Comment {
+    /// Every line starts with this, like `//`
+    Line(&'static str),
+    /// A block opened and closed on lines of their own, the lines between starting with `middle`
+    Block { open: &'static str, middle: &'static str, close: &'static str },
+}
+
+impl Comment {
+    /// Columns a commented out line loses to the comment marks and the space after them.
+    pub fn indent(self) -> usize {
+        match self {
+            Comment::Line(marks) | Comment::Block { middle: marks, .. } => marks.chars().count() + 1,
+        }
+    }
+
+    /// Comments out every line of `text`. Blank lines get the comment marks alone, so none of the
+    /// lines end in spaces, and a block comment's closing marks in `text` are broken up with a
+    /// space, like `* /` or `-- >`, so they can't end the comment early.
+    pub fn wrap(self, text: &str) -> String {
+        let marks = match self {
+            Comment::Line(marks) | Comment::Block { middle: marks, .. } => marks,
+        };
+        let mut commented = String::new();
+        if let Comment::Block { open, .. } = self {
+            commented.push_str(open);
+            commented.push('\n');
+        }
+        for line in text.lines() {
+            let line = match self {
+                Comment::Block { close, .. } => {
+                    let close = close.trim();
+                    let (head, last) = close.split_at(close.len() - close.chars().next_back().map_or(0, char::len_utf8));
+                    line.replace(close, &format!("{} {}", head, last))
+                }
+                Comment::Line(_) => line.to_string(),
+            };
+            commented.push_str(format!("{} {}", marks, line).trim_end());
+            commented.push('\n');
+        }
+        if let Comment::Block { close, .. } = self {
+            commented.push_str(close);
+            commented.push('\n');
+        }
+        commented
+    }
+}
+
+/// A language and how it writes comments.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Language {
+    /// What it's called, then anything else it goes by, like its file extension
+    pub names: &'static [&'static str],
+    pub comment: Comment,
+}
+
+const SLASHES: Comment = Comment::Line("//");
+const HASH: Comment = Comment::Line("#");
+const DASHES: Comment = Comment::Line("--");
+const C_BLOCK: Comment = Comment::Block { open: "/*", middle: " *", close: " */" };
+
+/// Every language headers can be written for, in the order `header --lang` lists them.
+pub const LANGUAGES: [Language; 23] = [
+    Language { names: &["rust", "rs"], comment: SLASHES },
+    Language { names: &["c", "h"], comment: C_BLOCK },
+    Language { names: &["cpp", "c++", "cc", "hpp"], comment: SLASHES },
+    Language { names: &["csharp", "cs", "c#"], comment: SLASHES },
+    Language { names: &["css"], comment: C_BLOCK },
+    Language { names: &["go"], comment: SLASHES },
+    Language { names: &["java"], comment: SLASHES },
+    Language { names: &["javascript", "js"], comment: SLASHES },
+    Language { names: &["typescript", "ts"], comment: SLASHES },
+    Language { names: &["kotlin", "kt"], comment: SLASHES },
+    Language { names: &["swift"], comment: SLASHES },
+    Language { names: &["python", "py"], comment: HASH },
+    Language { names: &["ruby", "rb"], comment: HASH },
+    Language { names: &["shell", "sh", "bash", "zsh"], comment: HASH },
+    Language { names: &["perl", "pl"], comment: HASH },
+    Language { names: &["yaml", "yml"], comment: HASH },
+    Language { names: &["toml"], comment: HASH },
+    Language { names: &["make", "makefile", "mk"], comment: HASH },
+    Language { names: &["lua"], comment: DASHES },
+    Language { names: &["sql"], comment: DASHES },
+    Language { names: &["haskell", "hs"], comment: DASHES },
+    Language { names: &["html", "xml", "svg", "markdown", "md"], comment: Comment::Block { open: "<!--", middle: "  ", close: "-->" } },
+    Language { names: &["lisp", "clojure", "clj", "scheme", "el"], comment: Comment::Line(";;") },
+];
+
+/// The language called `name`, or that has it as a file extension, whatever its case.
+pub fn language(name: &str) -> Result<&'static Language, String> {
+    let name = name.to_lowercase();
+    LANGUAGES.iter()
+        .find(|language| language.names.contains(&name.as_str()))
+        .ok_or_else(|| {
+            let names: Vec<&str> = LANGUAGES.iter().map(|language| language.names[0]).collect();
+            format!("Unknown language \"{}\", expected one of {}", name, names.join(", "))
+        })
+}
+
+/// Puts `text` beside the rocket in large letters, or plainly when they'd make it wider than
+/// `width` columns, wrapping it to fit if it needs to.
+pub fn compose(rocket: &Grid, text: &str, width: usize) -> Grid {
+    let big = banner::compose(rocket, &banner::big_text(text, Color::rgb(255, 255, 255)), Layout::Beside);
+    if big.width() <= width {
+        return big;
+    }
+    let lines = bubble::wrap(text, width.saturating_sub(rocket.width() + banner::GAP));
+    let mut plain = Grid::new(lines.iter().map(|line| line.chars().count()).max().unwrap_or(0), lines.len());
+    for (y, line) in lines.iter().enumerate() {
+        for (x, ch) in line.chars().enumerate() {
+            plain.set(x, y, Cell { ch, ..Cell::BLANK });
+        }
+    }
+    banner::compose(rocket, &plain, Layout::Beside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_comments_cant_be_closed_early() {
+        assert_eq!(language("c").unwrap().comment.wrap("ends */ here\n\n"), "/*\n * ends * / here\n *\n */\n");
+        assert_eq!(language("html").unwrap().comment.wrap("ends --> here"), "<!--\n   ends -- > here\n-->\n");
+    }
+}
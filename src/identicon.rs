@@ -0,0 +1,72 @@
+use crate::palette::Palette;
+use crate::render::{self, ColorDepth, Options, View};
+use crate::rocket::{self, BuildOptions, Rocket};
+
+/// How big an identicon is drawn. Every identicon of a size is the same width and height, frame
+/// and all, so they line up in a list like avatars.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IconSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl IconSize {
+    /// Lines of rocket inside the frame.
+    pub fn height(self) -> usize {
+        match self {
+            IconSize::Small => 6,
+            IconSize::Medium => 10,
+            IconSize::Large => 16,
+        }
+    }
+}
+
+/// Draws the rocket standing for `input`, like a user name or an email address, framed as a badge
+/// and colored in true color escape codes in the shades of its [`hue`]. The same input always
+/// gets the same identicon, and inputs a character apart get rockets shaped apart, or for the few
+/// small ones sharing a shape, almost always colored apart, so it's hard to pass one name off as
+/// another.
+pub fn identicon(input: &str, size: IconSize) -> String {
+    let options = Options {
+        view: View::Badge,
+        palette: palette(input),
+        color: true,
+        depth: ColorDepth::TrueColor,
+        ..Options::default()
+    };
+    let grid = render::draw(&rocket(input, size), &options).expect("badges are always drawn with glyphs");
+    render::text(&grid, &options)
+}
+
+/// The rocket an identicon draws for `input`, for drawing it some other way.
+pub fn rocket(input: &str, size: IconSize) -> Rocket {
+    Rocket::build_with(size.height(), seed(input), &BuildOptions::default())
+        .expect("the built in parts build rockets of every icon size")
+}
+
+/// The palette an identicon is colored in, see [`Palette::from_hue`].
+pub fn palette(input: &str) -> Palette {
+    Palette::from_hue(hue(input))
+}
+
+/// The seed of the rocket for `input`. Text seeds hash with FNV-1a, which leaves inputs differing
+/// only in their last character with seeds close together, so it's mixed further with
+/// SplitMix64's finalizer until every bit of the seed depends on every bit of the input.
+pub fn seed(input: &str) -> u64 {
+    mix(rocket::seed_from_text(input))
+}
+
+/// Degrees round the color wheel, from 0 to 359, of the identicon for `input`. It's taken from a
+/// different mix of the input than the [`seed`], so two inputs whose rockets come out alike are
+/// still likely to be colored apart.
+pub fn hue(input: &str) -> u16 {
+    ((mix(seed(input)) >> 32) % 360) as u16
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
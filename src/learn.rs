@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::json::{self, Value};
+use crate::rocket::{self, Part, PartType};
+
+/// How often each part followed each other in a set of example rockets, for building new ones in
+/// the same style. Parts are told apart by kind, shape and widths, not weight, so examples saved
+/// as JSON match up with the parts they were built from.
+#[derive(Clone, Debug, Default)]
+pub struct Model {
+    /// Every distinct part the examples use
+    parts: Vec<Part>,
+    /// Counts of each part following another, `None` standing for the top and bottom of a rocket
+    transitions: HashMap<(Option<usize>, Option<usize>), u32>,
+    rockets: usize,
+}
+
+impl Model {
+    /// Learns from a rocket saved with `--format json`.
+    pub fn learn_blueprint(&mut self, text: &str) -> Result<(), String> {
+        let blueprint = json::parse(text)?;
+        let Some(Value::Array(sections)) = blueprint.get("sections") else {
+            return Err("The blueprint has no \"sections\" list".to_string());
+        };
+        let parts = sections.iter().enumerate()
+            .map(|(i, section)| blueprint_part(section).map_err(|message| format!("Section {}: {}", i, message)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.learn(&parts);
+        Ok(())
+    }
+
+    /// Learns from a rocket's parts, nose first.
+    pub fn learn(&mut self, sections: &[Part]) {
+        let mut previous = None;
+        for part in sections {
+            let index = self.parts.iter().position(|p| same(p, part)).unwrap_or_else(|| {
+                self.parts.push(part.clone());
+                self.parts.len() - 1
+            });
+            *self.transitions.entry((previous, Some(index))).or_default() += 1;
+            previous = Some(index);
+        }
+        *self.transitions.entry((previous, None)).or_default() += 1;
+        self.rockets += 1;
+    }
+
+    /// How many rockets the model has learned from.
+    pub fn rockets(&self) -> usize {
+        self.rockets
+    }
+
+    /// Picks the parts for a rocket `max_height` lines tall, nose first, each part following the
+    /// last about as often as it did in the examples. Parts the examples never put together can
+    /// still follow each other, as likely as a single example by weight, so that a handful of
+    /// examples can build rockets of any height.
+    pub fn build<R: Rng + ?Sized>(&self, max_height: usize, parts: &[Arc<Part>], rng: &mut R) -> Result<Vec<Arc<Part>>, String> {
+        let learned: Vec<Option<usize>> = parts.iter().map(|part| self.parts.iter().position(|p| same(p, part))).collect();
+        // The chance of each part, or of the rocket ending, coming after each part, the last
+        // entry standing for the top of the rocket
+        let next: Vec<Vec<(Option<usize>, f64)>> = (0..=parts.len())
+            .map(|from| {
+                let previous = parts.get(from);
+                let allowed: Vec<usize> = (0..parts.len()).filter(|&to| rocket::fits_under(previous.map(|p| &**p), &parts[to])).collect();
+                let can_end = previous.is_some_and(|p| matches!(p.part_type(), PartType::Engine | PartType::Exhaust));
+                // Parts the examples never used have no counts to go on
+                let learned_from = match previous {
+                    None => Some(None),
+                    Some(_) => learned[from].map(Some),
+                };
+                let count = |to: Option<usize>| match (learned_from, to) {
+                    (None, _) => 0,
+                    (Some(from), None) => self.transitions.get(&(from, None)).copied().unwrap_or(0),
+                    (Some(from), Some(to)) => learned[to].map_or(0, |to| self.transitions.get(&(from, Some(to))).copied().unwrap_or(0)),
+                };
+                let total_weight: usize = allowed.iter().map(|&to| parts[to].selection_weight()).sum();
+                let share = if can_end { 0.5 } else { 1.0 };
+                let mut options: Vec<(Option<usize>, f64, u32)> = allowed.iter()
+                    .map(|&to| (Some(to), share * parts[to].selection_weight() as f64 / total_weight as f64, count(Some(to))))
+                    .collect();
+                if can_end {
+                    options.push((None, if allowed.is_empty() { 1.0 } else { 0.5 }, count(None)));
+                }
+                let total: u32 = options.iter().map(|&(_, _, count)| count).sum();
+                options.into_iter()
+                    .map(|(to, prior, count)| (to, (count as f64 + prior) / (total as f64 + 1.0)))
+                    .collect()
+            })
+            .collect();
+        let start = parts.len();
+        let index = |from: usize, height: usize| from * (max_height + 1) + height;
+
+        // The chance of finishing a rocket after each part at each height, tallest first
+        let mut finish = vec![0.0; (parts.len() + 1) * (max_height + 1)];
+        for height in (0..=max_height).rev() {
+            for (from, options) in next.iter().enumerate() {
+                finish[index(from, height)] = options.iter()
+                    .map(|&(to, p)| match to {
+                        None if height == max_height => p,
+                        Some(to) if height + parts[to].height() <= max_height => p * finish[index(to, height + parts[to].height())],
+                        _ => 0.0,
+                    })
+                    .sum();
+            }
+        }
+        if finish[index(start, 0)] == 0.0 {
+            return Err(format!("The model can't build a rocket {} lines tall from these parts", max_height));
+        }
+
+        let (mut from, mut height) = (start, 0);
+        let mut sections = Vec::new();
+        while height < max_height {
+            // Drawn from the top 53 bits, as many as an f64 holds exactly
+            let mut pick = finish[index(from, height)] * (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+            let mut chosen = None;
+            for &(to, p) in &next[from] {
+                let Some(to) = to.filter(|&to| height + parts[to].height() <= max_height) else { continue };
+                let chance = p * finish[index(to, height + parts[to].height())];
+                if chance > 0.0 {
+                    // Rounding can leave the pick past every chance, so the last one catches it
+                    chosen = Some(to);
+                    if pick < chance {
+                        break;
+                    }
+                    pick -= chance;
+                }
+            }
+            let to = chosen.expect("a part that can finish has a way on");
+            sections.push(Arc::clone(&parts[to]));
+            (from, height) = (to, height + parts[to].height());
+        }
+        Ok(sections)
+    }
+}
+
+fn same(a: &Part, b: &Part) -> bool {
+    a.part_type() == b.part_type() && a.shape() == b.shape() && a.top_width() == b.top_width() && a.bottom_width() == b.bottom_width()
+}
+
+fn blueprint_part(section: &Value) -> Result<Part, String> {
+    let text = |key: &str| match section.get(key) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        _ => Err(format!("\"{}\" must be a string", key)),
+    };
+    let number = |key: &str| match section.get(key) {
+        Some(Value::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as usize),
+        _ => Err(format!("\"{}\" must be a whole number", key)),
+    };
+    let part_type = text("type")?.parse()?;
+    let part = Part::new(part_type, number("top_width")?, number("bottom_width")?, number("height")?, 1, text("shape")?);
+    let part = match section.get("feature") {
+        Some(Value::String(feature)) => part.with_feature(feature.parse()?),
+        _ => part,
+    };
+    // Blueprints saved before parts had ids get made up ones
+    match (section.get("id"), section.get("name")) {
+        (Some(Value::String(id)), Some(Value::String(name))) => Ok(part.named(id.clone(), name.clone())),
+        _ => Ok(part),
+    }
+}
@@ -0,0 +1,41 @@
+pub mod animation;
+pub mod banner;
+pub mod bubble;
+pub mod canvas;
+pub mod config;
+pub mod console;
+pub mod date;
+pub mod diff;
+pub mod egg;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+pub mod fleet;
+pub mod font;
+pub mod fortune;
+pub mod grammar;
+pub mod grid;
+pub mod header;
+pub mod identicon;
+pub mod json;
+pub mod learn;
+pub mod motd;
+pub mod pack;
+pub mod palette;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quantize;
+pub mod raster;
+pub mod registry;
+pub mod render;
+pub mod rocket;
+#[cfg(feature = "entropy")]
+pub mod serve;
+pub mod stats;
+pub mod toml;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod wallpaper;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod webhook;
@@ -1,20 +1,457 @@
-use clap::Parser;
+use std::alloc::{self, GlobalAlloc, System};
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::env;
+use std::hint::black_box;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use rocket::rocket::Rocket;
+use clap::{AppSettings, Args, Parser, Subcommand};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-mod rocket;
+use ship_gen::animation::{self, Animation};
+use ship_gen::banner::{self, Layout};
+use ship_gen::bubble;
+use ship_gen::canvas::{self, Align, Canvas, Placement};
+use ship_gen::date::Date;
+use ship_gen::diff;
+use ship_gen::egg;
+use ship_gen::fingerprint;
+use ship_gen::fleet::{self, Formation};
+use ship_gen::fortune;
+use ship_gen::grid::{Cell, Grid};
+use ship_gen::header;
+use ship_gen::config::{self, Config};
+use ship_gen::console::{self, Support};
+use ship_gen::motd;
+use ship_gen::pack;
+use ship_gen::serve;
+use ship_gen::palette::{Gradient, Palette};
+use ship_gen::registry;
+use ship_gen::render::{self, ColorDepth, Decal, Encoding, Flag, Format, Options, Orientation, Renderer, View};
+use ship_gen::grammar::Grammar;
+use ship_gen::learn::Model;
+use ship_gen::rocket::{self, BuildOptions, Design, Generator, Mission, Nose, Part, PartType, Profile, Rocket, Style, Tag};
+use ship_gen::stats::Summary;
+use ship_gen::wallpaper;
+use ship_gen::webhook;
 
-enum Palette {
-    America,
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("Unknown color choice \"{}\"", s)),
+        }
+    }
+}
+
+/// A palette by name, or any of them picked at random.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PaletteChoice {
+    Named(Palette),
+    Random,
+}
+
+impl PaletteChoice {
+    fn pick(&self) -> Palette {
+        match self {
+            PaletteChoice::Named(palette) => *palette,
+            PaletteChoice::Random => Palette::ALL[rand::random::<usize>() % Palette::ALL.len()],
+        }
+    }
+}
+
+impl FromStr for PaletteChoice {
+    type Err = String;
+
+    /// Reads `random`, a built in or custom palette, or the name of a palette file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("random") {
+            return Ok(PaletteChoice::Random);
+        }
+        let unknown = match s.parse() {
+            Ok(palette) => return Ok(PaletteChoice::Named(palette)),
+            Err(e) => e,
+        };
+        match palette_files().into_iter().find(|(name, _)| name == s) {
+            Some((_, path)) => {
+                let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                let palette = Palette::from_file(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+                Ok(PaletteChoice::Named(palette))
+            }
+            None => Err(unknown),
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
-#[clap(name = "rocket")]
+#[clap(name = "rocket", setting = AppSettings::AllArgsOverrideSelf)]
 struct RocketOpts {
-    #[clap(short, long)]
-    height: usize,
-    #[clap(short, long, default_value="america")]
-    palette: String,
+    /// Rocket height in lines [default: 20, 12 for banners, --motd and --fingerprint, 10 for --host-icon, 8 for --fill and header, or 6 for --signature]
+    #[clap(short, long, global = true)]
+    height: Option<usize>,
+    /// Colors to draw with: one `ship_gen palettes` lists, custom:COLOR,... or random to pick one each run
+    #[clap(short, long, global = true, default_value="america")]
+    palette: PaletteChoice,
+    /// Replace some of the palette's colors, like "tip=white body=silver engine=grey exhaust=orange"
+    #[clap(long, global = true, value_name = "COLORS")]
+    colors: Option<String>,
+    /// Paint a pattern onto the body's wide sections: checker, stripes or nose-band
+    #[clap(long, global = true)]
+    decal: Option<Decal>,
+    /// Stamp a flag on the body by country code: us, ca, fr, it, ie, be, de, nl, ru, in, ua, jp or cn
+    #[clap(long, global = true, value_name = "COUNTRY")]
+    flag: Option<Flag>,
+    /// Blend the body from one color at the top to another at the bottom, like "#ffffff..#003366"
+    #[clap(long, global = true, value_name = "COLORS")]
+    paint: Option<Gradient>,
+    /// Output format: text, html, ans, json, markdown, png, gif or cast (gif needs the cargo feature of the same name)
+    #[clap(short, long, global = true, default_value="text")]
+    format: Format,
+    /// Color text output: auto (only on a terminal without NO_COLOR set), always or never
+    #[clap(short, long, global = true, default_value="auto")]
+    color: ColorChoice,
+    /// Colors the terminal can show: truecolor, 256 or 16 [default: from COLORTERM and TERM]
+    #[clap(long, global = true, value_name = "DEPTH")]
+    color_depth: Option<ColorDepth>,
+    /// How to draw the rocket: glyphs, braille, blocks, or sixel, kitty or iterm for terminals that show images
+    #[clap(short, long, global = true, default_value="glyphs")]
+    render: Renderer,
+    /// How to lay the rocket out: assembled, exploded to space out and label its parts, or framed as a badge
+    #[clap(long, global = true, default_value="assembled")]
+    view: View,
+    /// Lean the nose this many columns downwind, negative to the left, and stream the exhaust off the other way
+    #[clap(long, global = true, value_name = "STRENGTH", default_value = "0", allow_hyphen_values = true, parse(try_from_str = parse_wind))]
+    wind: f32,
+    /// Which way the rocket flies: up, left or right to turn it on its side, or down to nosedive
+    #[clap(long, global = true, default_value = "up")]
+    orientation: Orientation,
+    /// Pad or crop the output to exactly this many columns and lines, like 40x12, up to 1000x1000
+    #[clap(long, global = true, value_name = "WxH")]
+    canvas: Option<Canvas>,
+    /// Where the rocket goes on the --canvas: top, middle or bottom, left, center or right, or both like bottom-left [default: center]
+    #[clap(long, global = true, value_name = "PLACE", requires = "canvas")]
+    canvas_align: Option<Placement>,
+    /// Put the rocket at the left, center or right of the terminal, or of $COLUMNS or 80 columns when not writing to one
+    #[clap(long, global = true, value_name = "ALIGN")]
+    align: Option<Align>,
+    /// Start every line of text output with this, like "# " to drop the rocket into a comment
+    #[clap(long, global = true, value_name = "TEXT", conflicts_with = "indent")]
+    prefix: Option<String>,
+    /// Indent every line of text output by this many spaces, up to 1000
+    #[clap(long, global = true, value_name = "N", parse(try_from_str = parse_indent))]
+    indent: Option<usize>,
+    /// Magnification for braille, blocks, png and gif output and the image renderers
+    #[clap(long, global = true)]
+    scale: Option<usize>,
+    /// Character encoding for ans output: cp437 or utf8
+    #[clap(long, global = true, default_value="cp437")]
+    encoding: Encoding,
+    /// Seed for the random generator, the same seed and height always build the same rocket
+    #[clap(short, long, global = true)]
+    seed: Option<u64>,
+    /// Seed from any text instead, so a name always gets the same rocket
+    #[clap(long, global = true, value_name = "TEXT", conflicts_with = "seed")]
+    seed_text: Option<String>,
+    /// Build the rocket of the day, seeded from today's UTC date so everyone gets the same one
+    #[clap(long, global = true, conflicts_with_all = &["seed", "seed-text"])]
+    daily: bool,
+    /// Give --daily a rocket of the day of its own, like a team name, implies --daily
+    #[clap(long, global = true, value_name = "SCOPE", conflicts_with_all = &["seed", "seed-text"])]
+    daily_scope: Option<String>,
+    /// Seed a compact rocket from a build's identity, like a commit hash, and print it underneath for CI logs
+    #[clap(long, global = true, value_name = "TEXT", conflicts_with_all = &["seed", "seed-text", "daily", "daily-scope"])]
+    fingerprint: Option<String>,
+    /// Draw this machine's badge, a small framed rocket seeded from its hostname or the identifier given
+    #[clap(long, global = true, value_name = "ID", min_values = 0, max_values = 1, conflicts_with_all = &["seed", "seed-text", "daily", "daily-scope", "fingerprint"])]
+    host_icon: Option<Option<String>>,
+    /// Add height, width, part counts and a thrust and mass estimate under text output or to json
+    #[clap(long, global = true)]
+    stats: bool,
+    /// Name the rocket and its seed above --format markdown output
+    #[clap(long, global = true)]
+    title: bool,
+    /// Build this many candidates for each rocket and keep the best looking, see Rocket::score
+    #[clap(long, global = true, value_name = "N", default_value = "1")]
+    best_of: usize,
+    /// Print each rocket's seed to stderr, so a random rocket can be built again
+    #[clap(long, global = true)]
+    show_seed: bool,
+    /// Parts to build from: standard, or chat for only plain ASCII parts that line up in chat clients [default: chat on a terminal that can't show box drawing, else standard]
+    #[clap(long, global = true)]
+    profile: Option<Profile>,
+    /// Parts to favor: plain, sleek, chunky or busy
+    #[clap(long, global = true, default_value="plain")]
+    style: Style,
+    /// Keep each rocket to one look, rounded or angular, instead of mixing them
+    #[clap(long, global = true)]
+    coherent: bool,
+    /// Now and then build with an uncommon, rare or legendary part, whatever the weights, and say so under the rocket
+    #[clap(long, global = true)]
+    loot: bool,
+    /// Build a rocket small enough for an email signature: at most 6 lines and 20 columns of plain ASCII, without color
+    #[clap(long, global = true, conflicts_with = "profile")]
+    signature: bool,
+    /// Never swap in one of the hand drawn rockets that turn up about 1 in 100 times without --seed
+    #[clap(long, global = true)]
+    no_easter_eggs: bool,
+    /// Build from parts tagged rounded, angular, porthole, greeble, retro, gimbaled, gridfin or docking wherever there's one to use, can be repeated
+    #[clap(long, global = true, value_name = "TAG", multiple_occurrences = true)]
+    require_tag: Vec<Tag>,
+    /// Build from parts without this tag wherever there's one to use, can be repeated
+    #[clap(long, global = true, value_name = "TAG", multiple_occurrences = true)]
+    forbid_tag: Vec<Tag>,
+    /// Build with the engine gimbaled to one side and its exhaust angled to match, like --require-tag gimbaled
+    #[clap(long, global = true)]
+    gimbal: bool,
+    /// Put a launch escape tower above the nose, as on crewed capsules
+    #[clap(long, global = true)]
+    escape_tower: bool,
+    /// Spread solar panel wings from the upper stage of every rocket, which some get anyway
+    #[clap(long, global = true)]
+    solar_panels: bool,
+    /// Build with a capsule, fairing, probe or spike nose, or a random one of them for each rocket
+    #[clap(long, global = true, value_name = "NOSE")]
+    nose: Option<Nose>,
+    /// Build for a crew, with a capsule, windows and an escape tower, for cargo, with a big fairing and no windows, or for a probe, small with antennas and solar panels
+    #[clap(long, global = true, value_name = "MISSION")]
+    mission: Option<Mission>,
+    /// Never build from these parts, given by the ids `parts list` shows, like tip_double,body_eyes
+    #[clap(long, global = true, value_name = "PARTS")]
+    exclude_part: Option<String>,
+    /// Build every rocket with each of these parts at least once, like body_stage
+    #[clap(long, global = true, value_name = "PARTS")]
+    force_part: Option<String>,
+    /// Leave out parts wider than this many columns
+    #[clap(long, global = true)]
+    max_width: Option<usize>,
+    /// How to put parts in order: greedy, or grammar to follow a grammar's rules
+    #[clap(long, global = true, default_value="greedy")]
+    engine: Generator,
+    /// Build from the rules in this grammar file, implies --engine grammar
+    #[clap(long, global = true, value_name = "FILE")]
+    grammar: Option<PathBuf>,
+    /// Build in the style of the --format json rockets saved in this directory
+    #[clap(long, global = true, value_name = "DIR")]
+    learn: Option<PathBuf>,
+    /// Height left for tips and exhaust per line of body, from 0 for all body; random when unset
+    #[clap(long, global = true, value_name = "RATIO")]
+    decor_ratio: Option<f32>,
+    /// Write to this file instead of stdout, or into numbered files if it's a directory
+    #[clap(short, long, global = true, alias = "output")]
+    out: Option<PathBuf>,
+    /// Add to the end of --out instead of replacing it
+    #[clap(long, global = true)]
+    append: bool,
+    /// Build this many rockets, counting up from --seed if one is given
+    #[clap(short = 'n', long)]
+    count: Option<usize>,
+    /// Print totals for the --count rockets instead of the rockets: their heights, widths and how often each part was used
+    #[clap(long)]
+    summary: bool,
+    /// Stand the --count rockets side by side on the same launch pad instead of one after another
+    #[clap(long)]
+    row: bool,
+    /// Fly the --row in formation: v, diag or stagger, implies --row
+    #[clap(long)]
+    formation: Option<Formation>,
+    /// Redraw the screen with a fresh rocket every this many seconds, 5 if not given, until interrupted
+    #[clap(long = "loop", value_name = "SECONDS", min_values = 0, max_values = 1, default_missing_value = "5")]
+    loop_seconds: Option<f64>,
+    /// Play an animation of the rocket in the terminal: launch, landing, exhaust, or staging for rockets of two stages or more
+    #[clap(long, global = true, value_name = "ANIMATION")]
+    animate: Option<Animation>,
+    /// Frames a second for --animate
+    #[clap(long, global = true, default_value = "12")]
+    fps: usize,
+    /// Have the rocket say something in a speech bubble
+    #[clap(long)]
+    say: Option<String>,
+    /// Wrap speech bubble text at this many columns
+    #[clap(long, default_value="30")]
+    say_width: usize,
+    /// Put a quote about space or engineering under the rocket, wrapped to fit
+    #[clap(long)]
+    fortune: bool,
+    /// Take --fortune quotes from this file instead, separated by lines of % like fortune's, implies --fortune
+    #[clap(long, value_name = "FILE")]
+    fortunes: Option<PathBuf>,
+    /// Print a login message: cropped to --columns, with a line of system info underneath
+    #[clap(long)]
+    motd: bool,
+    /// Widest line --motd may print
+    #[clap(long, default_value="80")]
+    columns: usize,
+    /// Cover the whole terminal in small rockets, like wrapping paper
+    #[clap(long)]
+    fill: bool,
+    /// Post to a Discord or Slack incoming webhook instead of printing (needs the `webhook` feature)
+    #[clap(long, global = true, value_name = "URL")]
+    webhook: Option<String>,
+    /// Title for --webhook posts, {name}, {seed} and {height} are filled in
+    #[clap(long, global = true, value_name = "TEMPLATE", default_value="{name} (seed {seed})")]
+    webhook_message: String,
+    /// Also add the rocket, its name, seed and stats to the GitHub Actions job summary, in $GITHUB_STEP_SUMMARY
+    #[clap(long, global = true)]
+    github_summary: bool,
+    /// Read one rocket spec per line of stdin, like `height=20 seed=7 palette=america`, counting up from --seed for lines without one
+    #[clap(long)]
+    batch: bool,
+    /// Line written after each --batch or --count rocket, in formats that can hold more than one
+    #[clap(long, default_value="---")]
+    delimiter: String,
+    /// Read defaults and presets from this file instead of ~/.config/ship_gen/config.toml
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+    /// Apply the settings of a [preset.NAME] table in the config file
+    #[clap(long, global = true)]
+    preset: Option<String>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a rocket with a message in large letters
+    Banner(BannerOpts),
+    /// Time building and rendering rockets without printing them
+    Bench(BenchOpts),
+    /// Print the rockets for two seeds side by side, marking the sections that differ
+    Diff(DiffOpts),
+    /// Print a family of rockets in different sizes side by side, sharing the first one's design
+    Family(FamilyOpts),
+    /// Print a rocket in a comment, ready to paste at the top of a source file
+    Header(HeaderOpts),
+    /// List the built in palettes, with a swatch of each when colored
+    Palettes,
+    /// Look through the parts rockets are built from
+    Parts(PartsOpts),
+    /// Serve rockets over HTTP at /rocket?height=20&palette=america&seed=42
+    Serve(ServeOpts),
+    /// Put a rocket together by hand, choosing each part from the ones that fit (needs the `tui` feature)
+    Tui,
+}
+
+#[derive(Args, Debug)]
+struct BannerOpts {
+    /// Message to print, lines can be separated with newlines
+    text: String,
+    /// Where to put the message: beside or above the rocket
+    #[clap(short, long, default_value="beside")]
+    layout: Layout,
+}
+
+#[derive(Args, Debug)]
+struct BenchOpts {
+    /// Number of rockets to build, seeded counting up from --seed or 0
+    #[clap(short = 'n', long, default_value="10000")]
+    count: usize,
+}
+
+#[derive(Args, Debug)]
+struct DiffOpts {
+    /// Seed of the rocket on the left
+    #[clap(long)]
+    seed_a: u64,
+    /// Seed of the rocket on the right
+    #[clap(long)]
+    seed_b: u64,
+}
+
+#[derive(Args, Debug)]
+struct FamilyOpts {
+    /// Heights of the rockets in the family, separated by commas
+    #[clap(long, value_name = "HEIGHTS", default_value = "12,20,30")]
+    heights: String,
+}
+
+#[derive(Args, Debug)]
+struct HeaderOpts {
+    /// Language to write the comment in, like rust, python or c
+    #[clap(long)]
+    lang: String,
+    /// Text to put beside the rocket, in large letters if they fit
+    #[clap(long)]
+    text: Option<String>,
+    /// Widest line the header may have, comment marks and all
+    #[clap(long, default_value = "80")]
+    width: usize,
+}
+
+#[derive(Args, Debug)]
+struct PartsOpts {
+    #[clap(subcommand)]
+    command: PartsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum PartsCommand {
+    /// Print every part with its type, widths, weight and shape
+    List(ListOpts),
+    /// Check a part pack file for mistakes
+    Validate(ValidateOpts),
+}
+
+#[derive(Args, Debug)]
+struct ValidateOpts {
+    /// TOML file with a [[part]] table for each part
+    file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ListOpts {
+    /// Only list parts of this type: tip, body, engine or exhaust
+    #[clap(short = 't', long = "type")]
+    part_type: Option<PartType>,
+    /// Only list parts from this pack: builtin or chat
+    #[clap(long)]
+    pack: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ServeOpts {
+    /// Address to listen on
+    #[clap(short, long, default_value="127.0.0.1:8080")]
+    bind: String,
+}
+
+/// Most lines a --signature rocket can be
+const SIGNATURE_HEIGHT: usize = 6;
+/// Most columns a --signature rocket can be
+const SIGNATURE_WIDTH: usize = 20;
+/// Most spaces --indent can put before each line
+const MAX_INDENT: usize = 1000;
+
+/// A --wind strength, any number but infinity or NaN.
+fn parse_wind(s: &str) -> Result<f32, String> {
+    s.parse().ok().filter(|wind: &f32| wind.is_finite()).ok_or_else(|| format!("Invalid wind \"{}\"", s))
+}
+
+/// An --indent, at most [`MAX_INDENT`] spaces.
+fn parse_indent(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(indent) if indent <= MAX_INDENT => Ok(indent),
+        _ => Err(format!("Expected a number of spaces up to {}, not \"{}\"", MAX_INDENT, s)),
+    }
 }
 
 fn main() {
@@ -22,8 +459,907 @@ fn main() {
     // Height
     // End must be > "1"
     // Different sections might have couplers to join different widths
-    let args = RocketOpts::parse();
+    let mut args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    };
+    let console = console::setup();
+    if args.profile.is_none() && !unicode_output(&args, console) {
+        args.profile = Some(Profile::Chat);
+    }
+    if args.signature {
+        if let Some(height) = args.height.filter(|&height| height > SIGNATURE_HEIGHT) {
+            eprintln!("error: --signature rockets are at most {} lines tall, not {}", SIGNATURE_HEIGHT, height);
+            process::exit(1);
+        }
+        // Built to fit, out of parts that are plain ASCII already, rather than cut down after
+        args.height = Some(args.height.unwrap_or(SIGNATURE_HEIGHT));
+        args.max_width = Some(args.max_width.map_or(SIGNATURE_WIDTH, |width| width.min(SIGNATURE_WIDTH)));
+        args.profile = Some(Profile::Chat);
+        args.color = ColorChoice::Never;
+    }
+    if args.host_icon.is_some() {
+        args.view = View::Badge;
+        if args.host_icon == Some(None) {
+            let Some(host) = motd::hostname() else {
+                eprintln!("error: Can't find this machine's hostname, give --host-icon an identifier instead");
+                process::exit(1);
+            };
+            args.host_icon = Some(Some(host));
+        }
+    }
+
+    if let Err(e) = run(&args, console) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Parses the command line with the config file's settings in front of it, so anything given on
+/// the command line wins over the config file.
+fn parse_args() -> Result<RocketOpts, Box<dyn Error>> {
+    let mut args: Vec<String> = env::args().collect();
+    let (path, required) = match flag_value(&args, "--config") {
+        Some(path) => (Some(PathBuf::from(path)), true),
+        None => (config::default_path(), false),
+    };
+    let config = match path {
+        Some(path) if required || path.exists() => {
+            let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        _ => Config::default(),
+    };
+    let settings = config.args(flag_value(&args, "--preset").as_deref())?;
+    args.splice(1..1, settings);
+    Ok(RocketOpts::parse_from(args))
+}
+
+/// Finds the value of a `--name value` or `--name=value` option ahead of clap, which needs to be
+/// given the config file's settings up front.
+fn flag_value(args: &[String], name: &str) -> Option<String> {
+    let mut args = args.iter().skip(1).take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+fn run(args: &RocketOpts, console: Support) -> Result<(), Box<dyn Error>> {
+    let mut options = Options {
+        format: args.format,
+        renderer: match args.render {
+            Renderer::Kitty if !kitty_graphics(args) => Renderer::Glyphs,
+            renderer => renderer,
+        },
+        view: args.view,
+        palette: palette(args)?,
+        encoding: args.encoding,
+        color: match args.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // Recordings are played back in a terminal, wherever they're written
+            ColorChoice::Auto if args.format == Format::Cast => env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Auto => {
+                args.out.is_none()
+                    && io::stdout().is_terminal()
+                    && console.ansi
+                    && env::var_os("NO_COLOR").is_none()
+                    && env::var("TERM").map_or(true, |term| term != "dumb")
+            }
+        },
+        depth: args.color_depth.unwrap_or_else(color_depth),
+        scale: args.scale,
+        stats: args.stats,
+        decal: args.decal,
+        flag: args.flag,
+        paint: args.paint,
+        wind: args.wind,
+        orientation: args.orientation,
+        title: args.title,
+        pad: args.canvas.is_some(),
+    };
+
+    let height = args.height.unwrap_or(match &args.command {
+        Some(Command::Banner(_)) => 12,
+        Some(Command::Bench(bench_args)) => {
+            return bench(bench_args.count, args.height.unwrap_or(20), seed(args).unwrap_or(0), &build_options(args)?);
+        }
+        Some(Command::Diff(diff_args)) => return diff(args, diff_args, &options),
+        Some(Command::Family(family_args)) => return family(args, family_args, &options),
+        Some(Command::Header(header_args)) => return header(args, header_args, args.height.unwrap_or(8), &options),
+        Some(Command::Palettes) => return list_palettes(&options),
+        Some(Command::Parts(PartsOpts { command: PartsCommand::List(list_args) })) => return list_parts(list_args),
+        Some(Command::Parts(PartsOpts { command: PartsCommand::Validate(validate_args) })) => {
+            return validate_pack(&validate_args.file);
+        }
+        Some(Command::Tui) => return tui(args, &options),
+        Some(Command::Serve(serve_args)) => {
+            // Requests pick their own height, and whether they're colored has nothing to do with
+            // where the server's own output is going
+            options.color = args.color == ColorChoice::Always;
+            options.depth = args.color_depth.unwrap_or(ColorDepth::TrueColor);
+            return Ok(serve::serve(&serve_args.bind, &options)?);
+        }
+        None if args.motd || args.fingerprint.is_some() => 12,
+        None if args.host_icon.is_some() => 10,
+        None if args.fill => 8,
+        None => 20,
+    });
+    if args.batch {
+        return batch(args, height, &options);
+    }
+    if let Some(seconds) = args.loop_seconds {
+        return screensaver(args, height, seconds, &options);
+    }
+    if let Some(animation) = args.animate {
+        return animate(args, animation, height, &options);
+    }
+    if args.fill {
+        return wallpaper(args, height, &options);
+    }
+    let rockets = fleet(height, args.count.unwrap_or(1), args.best_of, seed(args), !args.no_easter_eggs, &build_options(args)?)?;
+    if args.summary {
+        return write_summary(args, height, &rockets, &options);
+    }
+    if args.row || args.formation.is_some() {
+        return write_row(args, &rockets, &options);
+    }
+    write_rockets(args, &rockets, &options)
+}
+
+/// Writes the totals for a fleet of rockets `height` lines tall, as text or JSON.
+fn write_summary(args: &RocketOpts, height: usize, rockets: &[Rocket], options: &Options) -> Result<(), Box<dyn Error>> {
+    let summary = Summary::of(rockets, &build_options(args)?.parts(height)?);
+    let mut out = output(args)?;
+    match options.format {
+        Format::Text => write!(out, "{}", summary)?,
+        Format::Json => writeln!(out, "{}", summary.to_json())?,
+        format => return Err(format!("--summary only writes text and json, not {}", format).into()),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes the rockets side by side, standing on the same line or in --formation.
+fn write_row(args: &RocketOpts, rockets: &[Rocket], options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("--row only writes text".into());
+    }
+    let drawn = rockets.iter()
+        .map(|rkt| {
+            if let Some(seed) = rkt.seed.filter(|_| args.show_seed) {
+                eprintln!("seed: {}", seed);
+            }
+            let grid = render::draw(rkt, options)?;
+            let ground = render::ground(rkt, &grid, options);
+            Ok((grid, ground))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let mut out = output(args)?;
+    write!(out, "{}", render::text(&fleet::row(&drawn, args.formation), options))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes the rockets to --out, or into numbered files if it's a directory.
+fn write_rockets(args: &RocketOpts, rockets: &[Rocket], options: &Options) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = args.out.as_ref().filter(|path| is_directory(path)) {
+        fs::create_dir_all(dir)?;
+        let mut number = last_number(dir)?;
+        for rkt in rockets {
+            number += 1;
+            let path = dir.join(format!("rocket_{:03}.{}", number, options.format.extension()));
+            let mut out = BufWriter::new(File::create(&path)?);
+            emit(args, rkt, options, &mut out)?;
+            out.flush()?;
+        }
+        return Ok(());
+    }
+
+    if options.format.whole_file() && rockets.len() > 1 {
+        return Err(format!("{} output holds one rocket, so --count needs --out to be a directory to write them into", options.format).into());
+    }
+    let mut out = output(args)?;
+    for rkt in rockets {
+        emit(args, rkt, options, &mut out)?;
+        if args.count.is_some() && !options.format.whole_file() {
+            writeln!(out, "{}", args.delimiter)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Opens --out, or stdout without it.
+fn output(args: &RocketOpts) -> io::Result<Box<dyn Write>> {
+    Ok(match &args.out {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .append(args.append)
+                .truncate(!args.append)
+                .open(path)?;
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    })
+}
+
+/// Whether the rockets' box drawing characters will show, which only a console that couldn't be
+/// set up for them or a terminal whose locale names a character set other than UTF-8 is known not
+/// to. Output going anywhere else is written for whatever reads it later.
+fn unicode_output(args: &RocketOpts, console: Support) -> bool {
+    if args.out.is_some() || !io::stdout().is_terminal() {
+        return true;
+    }
+    if !console.unicode {
+        return false;
+    }
+    // The first of these that's set decides, as it does for the C library
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    locale.is_none_or(|locale| {
+        let locale = locale.to_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
+
+/// How many colors the terminal can show, going by what it advertises: true color in COLORTERM,
+/// or 256 colors in TERM, and 16 otherwise.
+fn color_depth() -> ColorDepth {
+    if env::var("COLORTERM").is_ok_and(|colorterm| matches!(colorterm.as_str(), "truecolor" | "24bit")) {
+        ColorDepth::TrueColor
+    } else if env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+/// The --palette, picked again each call if it's random, with any --colors put in.
+fn palette(args: &RocketOpts) -> Result<Palette, String> {
+    let palette = args.palette.pick();
+    match &args.colors {
+        Some(colors) => palette.with_colors(colors),
+        None => Ok(palette),
+    }
+}
+
+/// Whether the rocket is going straight to a terminal that shows kitty graphics: kitty itself, or
+/// one of the others that speak its protocol and say so. Anywhere else `--render kitty` falls
+/// back to glyphs.
+fn kitty_graphics(args: &RocketOpts) -> bool {
+    args.out.is_none()
+        && io::stdout().is_terminal()
+        && (env::var_os("KITTY_WINDOW_ID").is_some()
+            || env::var("TERM").is_ok_and(|term| term == "xterm-kitty")
+            || env::var("TERM_PROGRAM").is_ok_and(|program| matches!(program.as_str(), "WezTerm" | "ghostty")))
+}
+
+/// Whether --out names a directory to write numbered files into: one that exists already, or a
+/// path ending in a separator.
+fn is_directory(path: &Path) -> bool {
+    path.is_dir() || path.as_os_str().to_string_lossy().ends_with(std::path::is_separator)
+}
+
+/// Highest number among the `rocket_NNN` files already in `dir`, so new files carry on after them.
+fn last_number(dir: &Path) -> io::Result<usize> {
+    let mut last = 0;
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name();
+        let number = name.to_str()
+            .and_then(|name| name.strip_prefix("rocket_"))
+            .and_then(|rest| rest.split('.').next())
+            .and_then(|number| number.parse().ok());
+        last = last.max(number.unwrap_or(0));
+    }
+    Ok(last)
+}
+
+/// Counts allocations so `bench` can report them.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Builds and renders `count` rockets as text, then reports throughput, allocations and how
+/// long single rockets took.
+fn bench(count: usize, height: usize, seed: u64, build_options: &BuildOptions) -> Result<(), Box<dyn Error>> {
+    if count == 0 {
+        return Err("Nothing to time with a count of 0".into());
+    }
+    // One untimed rocket checks the arguments and warms up caches
+    build(height, Some(seed), build_options)?;
+
+    let mut latencies = Vec::with_capacity(count);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 0..count {
+        let rocket_start = Instant::now();
+        let rkt = Rocket::build_with(height, seed.wrapping_add(i as u64), build_options)?;
+        black_box(rkt.to_grid().to_string());
+        latencies.push(rocket_start.elapsed());
+    }
+    let elapsed = start.elapsed();
+    // The latencies were preallocated, so everything counted is the rockets' own
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+
+    latencies.sort_unstable();
+    let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    println!("{} rockets {} lines tall in {:.3?}", count, height, elapsed);
+    println!("{:.0} rockets/s", count as f64 / elapsed.as_secs_f64());
+    println!("{:.1} allocations and {:.0} bytes per rocket", allocations as f64 / count as f64, bytes as f64 / count as f64);
+    println!("latency p50 {:.2?}  p90 {:.2?}  p99 {:.2?}  max {:.2?}",
+             percentile(50), percentile(90), percentile(99), percentile(100));
+    Ok(())
+}
+
+/// Prints the rockets for both seeds side by side, and how many of their sections differ.
+fn diff(args: &RocketOpts, diff_args: &DiffOpts, options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("diff only writes text".into());
+    }
+    let height = args.height.unwrap_or(20);
+    let build_options = build_options(args)?;
+    let a = build(height, Some(diff_args.seed_a), &build_options)?;
+    let b = build(height, Some(diff_args.seed_b), &build_options)?;
+    let (grid, differing) = diff::side_by_side(&a, &b);
+    let mut out = output(args)?;
+    writeln!(out, "{}", render::text(&grid, options))?;
+    writeln!(out, "{} of {} sections differ", differing, a.sections().len() + b.sections().len())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Prints a family of rockets side by side, one for each of --heights, all from the same seed. The
+/// rest favor the first one's parts, so they look like bigger and smaller versions of it.
+fn family(args: &RocketOpts, family_args: &FamilyOpts, options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("family only writes text".into());
+    }
+    let heights = family_args.heights.split(',')
+        .map(|height| height.trim().parse().map_err(|_| format!("Invalid height \"{}\"", height)))
+        .collect::<Result<Vec<usize>, _>>()?;
+    let seed = seed(args).unwrap_or_else(rand::random);
+    if args.show_seed {
+        eprintln!("seed: {}", seed);
+    }
+    let mut build_options = build_options(args)?;
+    let mut drawn = Vec::new();
+    for &height in &heights {
+        let rkt = Rocket::build_with(height, seed, &build_options)?;
+        warn_seed_moved(seed, &rkt);
+        build_options.design.get_or_insert_with(|| Design::of(&rkt));
+        let grid = render::draw(&rkt, options)?;
+        let ground = render::ground(&rkt, &grid, options);
+        drawn.push((grid, ground));
+    }
+    let mut out = output(args)?;
+    write!(out, "{}", render::text(&fleet::row(&drawn, None), options))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes a rocket `height` lines tall in a comment in the language asked for, with the text beside
+/// it, no wider than asked.
+fn header(args: &RocketOpts, header_args: &HeaderOpts, height: usize, options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("header only writes text".into());
+    }
+    let comment = header::language(&header_args.lang)?.comment;
+    let width = header_args.width.saturating_sub(comment.indent());
+    let rockets = fleet(height, 1, args.best_of, seed(args), !args.no_easter_eggs, &build_options(args)?)?;
+    let mut grid = render::draw(&rockets[0], options)?;
+    if grid.width() > width {
+        return Err(format!("A rocket {} lines tall doesn't fit in a {} column header, try a lower --height", height, header_args.width).into());
+    }
+    if let Some(text) = &header_args.text {
+        grid = header::compose(&grid, text, width);
+    }
+    let mut out = output(args)?;
+    // Escape codes have no place in source files
+    write!(out, "{}", comment.wrap(&render::text(&grid, &Options { color: false, ..options.clone() })))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Runs the hand builder, then writes out what was built as text or a --format json blueprint.
+#[cfg(feature = "tui")]
+fn tui(args: &RocketOpts, options: &Options) -> Result<(), Box<dyn Error>> {
+    use ship_gen::tui::{self, Export};
+
+    let height = args.height.unwrap_or(20);
+    let parts = build_options(args)?.parts(height)?;
+    let Some((rkt, export)) = tui::run(height, &parts, &options.palette, options.depth)? else {
+        return Ok(());
+    };
+    let format = match export {
+        Export::Text => Format::Text,
+        Export::Blueprint => Format::Json,
+    };
+    write_rockets(args, &[rkt], &Options { format, ..options.clone() })
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui(_args: &RocketOpts, _options: &Options) -> Result<(), Box<dyn Error>> {
+    Err("The tui is not available, rebuild ship_gen with `--features tui`".into())
+}
+
+/// The palette files in the config directory's `palettes/`, by the name each is chosen with.
+fn palette_files() -> Vec<(String, PathBuf)> {
+    let Some(entries) = config::palettes_dir().and_then(|dir| fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| Some((path.file_stem()?.to_str()?.to_string(), path)))
+        .collect();
+    files.sort();
+    files
+}
+
+fn list_palettes(options: &Options) -> Result<(), Box<dyn Error>> {
+    let mut out = io::stdout().lock();
+    let mut palettes: Vec<(String, Palette, String)> = Palette::ALL.iter()
+        .map(|palette| (palette.to_string(), *palette, palette.description().to_string()))
+        .collect();
+    for (name, path) in palette_files() {
+        // A broken file is listed with what's wrong with it rather than stopping the list
+        let loaded = fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| Palette::from_file(&text));
+        match loaded {
+            Ok(palette) => palettes.push((name, palette, format!("From {}", path.display()))),
+            Err(e) => palettes.push((name, Palette::Mono, format!("Can't be read from {}: {}", path.display(), e))),
+        }
+    }
+    for (name, palette, description) in palettes {
+        let line = format!("{:<12} {}", name, description);
+        if !options.color {
+            writeln!(out, "{}", line)?;
+            continue;
+        }
+        // A block in each part's color, then the name and description in the palette's text color
+        let swatch = [PartType::Tip, PartType::Body, PartType::Engine, PartType::Exhaust].map(|t| ('█', palette.color(t)));
+        let cells = swatch.into_iter().chain(iter::once((' ', palette.text()))).chain(line.chars().map(|ch| (ch, palette.text())));
+        let mut grid = Grid::new(line.chars().count() + swatch.len() + 1, 1);
+        for (x, (ch, color)) in cells.enumerate() {
+            grid.set(x, 0, Cell { ch, color: Some(color), ..Cell::BLANK });
+        }
+        writeln!(out, "{}", render::text(&grid, options))?;
+    }
+    Ok(())
+}
+
+/// Prints the parts bin as a table, shapes drawn in the last column.
+fn list_parts(args: &ListOpts) -> Result<(), Box<dyn Error>> {
+    let registry = registry::builtin();
+    if let Some(pack) = args.pack.as_deref().filter(|&pack| registry.pack(pack).is_none()) {
+        return Err(format!("Unknown pack \"{}\"", pack).into());
+    }
+    let listed = |pack: &str, part: &Part| {
+        args.pack.as_deref().is_none_or(|p| p == pack) && args.part_type.is_none_or(|t| t == part.part_type())
+    };
+    let widest = |column: fn(&Part) -> &str, heading: &str| {
+        registry.packs()
+            .flat_map(|(pack, parts)| parts.iter().filter(move |part| listed(pack, part)))
+            .map(|part| column(part).chars().count())
+            .fold(heading.len(), usize::max)
+    };
+    let (id_width, name_width) = (widest(Part::id, "ID"), widest(Part::name, "NAME"));
+    let mut out = io::stdout().lock();
+    writeln!(out, "{:<8} {:>3}  {:<id_width$}  {:<name_width$}  {:<8} {:>3} {:>6} {:>6} {:>6}  {:<9}  SHAPE",
+             "PACK", "#", "ID", "NAME", "TYPE", "TOP", "BOTTOM", "HEIGHT", "WEIGHT", "RARITY")?;
+    for (pack, parts) in registry.packs() {
+        for (number, part) in parts.iter().enumerate() {
+            if !listed(pack, part) {
+                continue;
+            }
+            let columns = format!("{:<8} {:>3}  {:<id_width$}  {:<name_width$}  {:<8} {:>3} {:>6} {:>6} {:>6}  {:<9}  ", pack,
+                                  number, part.id(), part.name(), part.part_type().to_string(), part.top_width(),
+                                  part.bottom_width(), part.height(), part.selection_weight(), part.rarity().to_string());
+            for (i, line) in part.shape().lines().enumerate() {
+                let indent = if i == 0 { columns.clone() } else { " ".repeat(columns.len()) };
+                writeln!(out, "{}{}", indent, line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints every problem in a part pack, one per line like a compiler would.
+fn validate_pack(path: &Path) -> Result<(), Box<dyn Error>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let problems = match pack::parse(&text) {
+        Ok(parts) => {
+            let problems = pack::validate(&parts);
+            if problems.is_empty() {
+                println!("{}: {} parts OK", path.display(), parts.len());
+                return Ok(());
+            }
+            problems
+        }
+        Err(problems) => problems,
+    };
+    for problem in &problems {
+        eprintln!("{}:{}: {}", path.display(), problem.line, problem.message);
+    }
+    Err(format!("{} problems in {}", problems.len(), path.display()).into())
+}
+
+/// The seed --seed, --seed-text, --fingerprint, --host-icon or --daily asks for.
+fn seed(args: &RocketOpts) -> Option<u64> {
+    args.seed
+        .or_else(|| args.seed_text.as_deref().map(rocket::seed_from_text))
+        .or_else(|| args.fingerprint.as_deref().map(fingerprint::seed))
+        .or_else(|| args.host_icon.as_ref().and_then(|id| id.as_deref()).map(rocket::seed_from_text))
+        .or_else(|| {
+            (args.daily || args.daily_scope.is_some()).then(|| rocket::daily_seed(Date::today(), args.daily_scope.as_deref()))
+        })
+}
+
+/// The parts --profile, --style and --max-width ask for, put in order by --engine, --grammar or
+/// --learn.
+fn build_options(args: &RocketOpts) -> Result<BuildOptions, Box<dyn Error>> {
+    let generator = match (&args.grammar, &args.learn) {
+        (Some(_), Some(_)) => return Err("--grammar and --learn can't be used together".into()),
+        (Some(path), None) => {
+            let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Generator::Grammar(Arc::new(Grammar::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?))
+        }
+        (None, Some(dir)) => Generator::Learned(Arc::new(learn(dir)?)),
+        (None, None) => args.engine.clone(),
+    };
+    Ok(BuildOptions {
+        profile: args.profile.unwrap_or(Profile::Standard),
+        style: args.style,
+        generator,
+        max_width: args.max_width,
+        decor_ratio: args.decor_ratio,
+        design: None,
+        coherent: args.coherent,
+        require_tags: args.require_tag.iter().copied().chain(args.gimbal.then_some(Tag::Gimbaled)).collect(),
+        forbid_tags: args.forbid_tag.clone(),
+        exclude_parts: find_parts(args.exclude_part.as_deref())?,
+        force_parts: find_parts(args.force_part.as_deref())?,
+        loot: args.loot,
+        escape_tower: args.escape_tower,
+        solar_panels: args.solar_panels,
+        nose: args.nose,
+        mission: args.mission,
+    })
+}
+
+/// The built in parts a comma separated list of ids names, see [`registry::PartRegistry::find`].
+fn find_parts(ids: Option<&str>) -> Result<Vec<Arc<Part>>, String> {
+    ids.map_or(Ok(Vec::new()), |ids| {
+        ids.split(',').map(|id| registry::builtin().find(id.trim()).cloned()).collect()
+    })
+}
+
+/// Learns from every `.json` rocket in `dir`.
+fn learn(dir: &Path) -> Result<Model, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+    // Sorted so the model, and the rockets it builds, don't depend on the order the files are listed in
+    paths.sort();
+    let mut model = Model::default();
+    for path in &paths {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        model.learn_blueprint(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    if model.rockets() == 0 {
+        return Err(format!("{} has no .json rockets to learn from", dir.display()).into());
+    }
+    Ok(model)
+}
+
+fn build(height: usize, seed: Option<u64>, build_options: &BuildOptions) -> Result<Rocket, Box<dyn Error>> {
+    Ok(Rocket::build_with(height, seed.unwrap_or_else(rand::random), build_options)?)
+}
+
+/// Builds `count` rockets, seeded counting up from `seed` so a whole run can be reproduced from
+/// one --seed, or each from a random seed without one. Each is the best scoring of `best_of`
+/// candidates, the first of them on a tie. Without a seed, and with `eggs`, any of them can be
+/// swapped for an easter egg, see [`egg::roll`].
+fn fleet(height: usize, count: usize, best_of: usize, seed: Option<u64>, eggs: bool, build_options: &BuildOptions) -> Result<Vec<Rocket>, Box<dyn Error>> {
+    if best_of == 0 {
+        return Err("--best-of needs at least 1 candidate".into());
+    }
+    let candidates = count * best_of;
+    let seeds: Vec<u64> = match seed {
+        Some(seed) => (0..candidates as u64).map(|i| seed.wrapping_add(i)).collect(),
+        None => (0..candidates).map(|_| rand::random()).collect(),
+    };
+    let candidates = Rocket::fleet(height, &seeds, build_options)?;
+    if seed.is_some() {
+        seeds.iter().zip(&candidates).for_each(|(&asked, rkt)| warn_seed_moved(asked, rkt));
+    }
+    let mut candidates = candidates.into_iter();
+    let best = (0..count).map(|_| {
+        candidates.by_ref()
+            .take(best_of)
+            .reduce(|best, rocket| if rocket.score() > best.score() { rocket } else { best })
+            .expect("best_of is at least 1")
+    });
+    if seed.is_some() || !eggs {
+        return Ok(best.collect());
+    }
+    Ok(best.map(|rocket| egg::roll(rand::random(), height, build_options).unwrap_or(rocket)).collect())
+}
+
+/// Warns when a rocket was built from another seed than the one asked for, to fit in every
+/// --force-part or --solar-panels, so it can be built again from the seed it was.
+fn warn_seed_moved(asked: u64, rkt: &Rocket) {
+    if let Some(seed) = rkt.seed.filter(|&seed| seed != asked) {
+        eprintln!("warning: Seed {} had no room for everything asked for, so this rocket is seed {}", asked, seed);
+    }
+}
+
+/// A --fortune quote for the rocket, the same one every time for a seed.
+fn pick_fortune(args: &RocketOpts, rkt: &Rocket) -> Result<String, Box<dyn Error>> {
+    let mut quotes = match &args.fortunes {
+        Some(path) => fortune::parse(&fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?),
+        None => fortune::QUOTES.iter().map(|quote| quote.to_string()).collect(),
+    };
+    if quotes.is_empty() {
+        return Err(format!("{} has no quotes", args.fortunes.as_deref().unwrap_or(Path::new("")).display()).into());
+    }
+    let pick = rkt.seed.map_or_else(rand::random, |seed| ChaCha8Rng::seed_from_u64(seed).next_u64());
+    let quote = quotes.swap_remove((pick % quotes.len() as u64) as usize);
+    // Dashes like fortune's, for chat clients that only line up ASCII
+    Ok(if args.profile == Some(Profile::Chat) { quote.replace('—', "--") } else { quote })
+}
+
+/// Decorates the rocket as the arguments ask and writes it out, or posts it to the webhook.
+fn emit(args: &RocketOpts, rkt: &Rocket, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    if let Some(seed) = rkt.seed.filter(|_| args.show_seed) {
+        eprintln!("seed: {}", seed);
+    }
+    let mut grid = render::draw(rkt, options)?;
+    if let Some(text) = &args.say {
+        grid = bubble::say(&grid, text, args.say_width);
+    }
+    if let Some(Command::Banner(banner_args)) = &args.command {
+        let text = banner::big_text(&banner_args.text, options.palette.text());
+        grid = banner::compose(&grid, &text, banner_args.layout);
+    }
+    if let Some(fingerprint) = &args.fingerprint {
+        grid = fingerprint::label(&grid, fingerprint);
+    }
+    if args.fortune || args.fortunes.is_some() {
+        grid = fortune::under(&grid, &pick_fortune(args, rkt)?);
+    }
+    if args.github_summary {
+        step_summary(&grid, rkt, options)?;
+    }
+    if args.webhook.is_some() && options.format != Format::Text {
+        return Err("--webhook only posts text".into());
+    }
+    if args.motd {
+        if options.format != Format::Text {
+            return Err("--motd only writes text".into());
+        }
+        grid = motd::compose(&grid, &motd::system_info(), args.columns);
+    }
+    if let Some(canvas) = args.canvas {
+        grid = canvas::fit(&grid, canvas, args.canvas_align.unwrap_or(Placement::CENTER));
+    }
+    let prefix = args.prefix.clone().or_else(|| args.indent.map(|indent| " ".repeat(indent)));
+    if prefix.is_some() && !matches!(options.format, Format::Text | Format::Markdown) {
+        return Err("--prefix and --indent only go on text and markdown output".into());
+    }
+    if let Some(align) = args.align {
+        // Lined up in what's left of the line after the prefix
+        let width = terminal_width(args).saturating_sub(prefix.as_deref().map_or(0, |prefix| prefix.chars().count()));
+        grid = canvas::align(&grid, width, align);
+    }
+    if let Some(url) = &args.webhook {
+        // Chat clients show escape codes literally
+        let art = render::text(&grid, &Options { color: false, ..options.clone() });
+        return webhook::post(url, &webhook::message(&args.webhook_message, rkt, &art));
+    }
+
+    let mut art = Vec::new();
+    if args.motd {
+        write!(art, "{}", render::text(&grid, options))?;
+    } else {
+        render::write_grid(&grid, rkt, options, &mut art)?;
+    }
+    match prefix {
+        Some(prefix) => {
+            for line in String::from_utf8_lossy(&art).lines() {
+                writeln!(out, "{}{}", prefix, line)?;
+            }
+        }
+        None => out.write_all(&art)?,
+    }
+    Ok(())
+}
+
+/// Columns wide the terminal being written to is, or when it isn't one, what $COLUMNS says, as
+/// shells set it for scripts, or 80.
+fn terminal_width(args: &RocketOpts) -> usize {
+    console::size().filter(|_| args.out.is_none())
+        .map(|(columns, _)| columns)
+        .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80)
+}
+
+/// Adds the rocket to the end of the GitHub Actions job summary as markdown, titled and with its
+/// stats, whatever it's being written out as. Outside of Actions there's no summary to add to, which
+/// is only worth a warning, so the same workflow still runs on a laptop.
+fn step_summary(grid: &Grid, rkt: &Rocket, options: &Options) -> Result<(), Box<dyn Error>> {
+    let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") else {
+        eprintln!("GITHUB_STEP_SUMMARY isn't set, so there's no job summary to add the rocket to");
+        return Ok(());
+    };
+    let options = Options {
+        format: Format::Markdown,
+        renderer: Renderer::Glyphs,
+        color: false,
+        stats: true,
+        title: true,
+        ..options.clone()
+    };
+    let mut summary = OpenOptions::new().create(true).append(true).open(&path)
+        .map_err(|e| format!("Couldn't open the job summary {}: {}", PathBuf::from(&path).display(), e))?;
+    render::write_grid(grid, rkt, &options, &mut summary)?;
+    // Kept apart from whatever's added next
+    writeln!(summary)?;
+    Ok(())
+}
+
+/// Draws a fresh rocket every `seconds` until the process is stopped, counting up from --seed if
+/// one is given, and in a different palette each time with `--palette random`.
+fn screensaver(args: &RocketOpts, height: usize, seconds: f64, options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("--loop only writes text".into());
+    }
+    let interval = Duration::try_from_secs_f64(seconds).map_err(|_| format!("Invalid --loop interval \"{}\"", seconds))?;
+    let build_options = build_options(args)?;
+    let mut out = io::stdout().lock();
+    for frame in 0u64.. {
+        let seed = seed(args).map(|seed| seed.wrapping_add(frame));
+        let rockets = fleet(height, 1, args.best_of, seed, !args.no_easter_eggs, &build_options)?;
+        // With --palette random every rocket gets its own
+        let options = Options { palette: palette(args)?, ..options.clone() };
+        let mut art = Vec::new();
+        emit(args, &rockets[0], &options, &mut art)?;
+        draw_over(&mut out, &String::from_utf8_lossy(&art))?;
+        thread::sleep(interval);
+    }
+    Ok(())
+}
+
+/// Plays `animation` of the rocket --seed picks at --fps, until interrupted if it loops, or writes
+/// it to --out in any other format than text.
+fn animate(args: &RocketOpts, animation: Animation, height: usize, options: &Options) -> Result<(), Box<dyn Error>> {
+    if args.fps == 0 {
+        return Err("--fps must be at least 1".into());
+    }
+    let rockets = fleet(height, 1, args.best_of, seed(args), !args.no_easter_eggs, &build_options(args)?)?;
+    let frames = animation::frames(animation, &rockets[0], &options.palette, args.fps)?;
+    if options.format != Format::Text {
+        let mut out = output(args)?;
+        render::write_animation(&frames, args.fps, animation.loops(), options, &mut out)?;
+        out.flush()?;
+        return Ok(());
+    }
+    let mut out = io::stdout().lock();
+    let plays = if animation.loops() { usize::MAX } else { 1 };
+    for frame in iter::repeat_n(&frames, plays).flatten() {
+        draw_over(&mut out, &render::text(frame, options))?;
+        thread::sleep(Duration::from_secs(1) / args.fps as u32);
+    }
+    Ok(())
+}
+
+/// Draws `art` over what's on the terminal in one write, rather than clearing the screen first,
+/// so redrawing it doesn't flicker.
+fn draw_over(out: &mut dyn Write, art: &str) -> io::Result<()> {
+    // Home the cursor, clear what's left of each line and everything under the last
+    write!(out, "\x1b[H{}\x1b[J", art.replace('\n', "\x1b[K\n"))?;
+    out.flush()
+}
+
+/// Covers the terminal, or an 80 by 24 screen when not writing to one, in rockets seeded counting
+/// up from --seed, laid out from it too so the same seed tiles the same screen.
+fn wallpaper(args: &RocketOpts, height: usize, options: &Options) -> Result<(), Box<dyn Error>> {
+    if options.format != Format::Text {
+        return Err("--fill only writes text".into());
+    }
+    let (columns, lines) = console::size().filter(|_| args.out.is_none()).unwrap_or((80, 24));
+    let seed = seed(args).unwrap_or_else(rand::random);
+    let build_options = build_options(args)?;
+    let mut rockets = (0..).map(|i| -> Result<Grid, Box<dyn Error>> {
+        let rkt = Rocket::build_with(height, seed.wrapping_add(i), &build_options)?;
+        render::draw(&rkt, options)
+    });
+    // A line short, so the prompt afterwards doesn't push the top off the screen
+    let grid = wallpaper::tile(columns, lines.saturating_sub(1), height, &mut rockets, &mut ChaCha8Rng::seed_from_u64(seed))?;
+    let mut out = output(args)?;
+    write!(out, "{}", render::text(&grid, options))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Reads one rocket spec per line of stdin, like `height=20 seed=7 palette=america`, and writes
+/// each rocket followed by the delimiter. Anything a spec leaves out comes from the arguments.
+fn batch(args: &RocketOpts, height: usize, defaults: &Options) -> Result<(), Box<dyn Error>> {
+    let build_defaults = build_options(args)?;
+    let mut out = output(args)?;
+    // Lines without a seed of their own count up from --seed, one per rocket, as --count does
+    let mut seeds = (0..).map(|i| seed(args).map(|seed| seed.wrapping_add(i)));
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        let spec = line.trim();
+        if spec.is_empty() || spec.starts_with('#') {
+            continue;
+        }
+        let spec_error = |e: Box<dyn Error>| format!("line {}: {}", i + 1, e);
+        let seed = seeds.next().expect("the seeds never run out");
+        let (rkt, options) = spec_rocket(spec, height, seed, defaults, &build_defaults).map_err(spec_error)?;
+        if options.format.whole_file() {
+            return Err(spec_error(format!("{} output holds one rocket, so it can't be batched", options.format).into()).into());
+        }
+        emit(args, &rkt, &options, &mut out).map_err(spec_error)?;
+        writeln!(out, "{}", args.delimiter)?;
+    }
+    out.flush()?;
+    Ok(())
+}
 
-    let rkt = Rocket::new(args.height);
-    println!("{}", rkt);
+fn spec_rocket(spec: &str, mut height: usize, mut seed: Option<u64>, defaults: &Options, build_defaults: &BuildOptions) -> Result<(Rocket, Options), Box<dyn Error>> {
+    let mut options = defaults.clone();
+    let mut build_options = build_defaults.clone();
+    for pair in spec.split_whitespace() {
+        let (key, value) = pair.split_once('=').ok_or_else(|| format!("Expected key=value, not \"{}\"", pair))?;
+        match key {
+            "height" => height = value.parse().map_err(|_| format!("Invalid height \"{}\"", value))?,
+            "seed" => seed = Some(value.parse().map_err(|_| format!("Invalid seed \"{}\"", value))?),
+            "seed-text" => seed = Some(rocket::seed_from_text(value)),
+            "palette" => options.palette = value.parse()?,
+            "format" => options.format = value.parse()?,
+            "render" => options.renderer = value.parse()?,
+            "view" => options.view = value.parse()?,
+            "wind" => options.wind = parse_wind(value)?,
+            "orientation" => options.orientation = value.parse()?,
+            "profile" => build_options.profile = value.parse()?,
+            "style" => build_options.style = value.parse()?,
+            "engine" => build_options.generator = value.parse()?,
+            "decor-ratio" => build_options.decor_ratio = Some(value.parse().map_err(|_| format!("Invalid decor-ratio \"{}\"", value))?),
+            "max-width" => build_options.max_width = Some(value.parse().map_err(|_| format!("Invalid max-width \"{}\"", value))?),
+            _ => return Err(format!("Unknown key \"{}\"", key).into()),
+        }
+    }
+    Ok((build(height, seed, &build_options)?, options))
 }
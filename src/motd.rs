@@ -0,0 +1,71 @@
+use std::fs;
+
+use crate::grid::{Cell, Grid};
+
+/// Puts the system info line under the rocket and crops everything to `columns`, so the result
+/// can be dropped into a login message without wrapping on a narrow console.
+pub fn compose(rocket: &Grid, info: &str, columns: usize) -> Grid {
+    let info: Vec<char> = info.chars().collect();
+    let mut grid = Grid::new(rocket.width().max(info.len()), rocket.height() + 1);
+    grid.draw(0, 0, rocket);
+    for (x, &ch) in info.iter().enumerate() {
+        grid.set(x, rocket.height(), Cell { ch, ..Cell::BLANK });
+    }
+    grid.crop(columns, grid.height())
+}
+
+/// One line about the machine: hostname, kernel, uptime and load. Anything that can't be read,
+/// like `/proc` on systems that don't have it, is left out rather than failing.
+pub fn system_info() -> String {
+    let mut info = Vec::new();
+    if let Some(host) = hostname() {
+        info.push(host);
+    }
+    let os = match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "macOS",
+        "windows" => "Windows",
+        other => other,
+    };
+    match read_trimmed("/proc/sys/kernel/osrelease") {
+        Some(release) => info.push(format!("{} {}", os, release)),
+        None => info.push(os.to_string()),
+    }
+    let uptime = read_trimmed("/proc/uptime")
+        .and_then(|u| u.split_whitespace().next()?.parse::<f64>().ok());
+    if let Some(seconds) = uptime {
+        info.push(format!("up {}", format_uptime(seconds as u64)));
+    }
+    let load = read_trimmed("/proc/loadavg")
+        .map(|l| l.split_whitespace().take(3).collect::<Vec<_>>().join(" "));
+    if let Some(load) = load {
+        info.push(format!("load {}", load));
+    }
+    info.join(" · ")
+}
+
+/// The machine's hostname, from `/proc` or `/etc/hostname` on Unix and the environment elsewhere.
+pub fn hostname() -> Option<String> {
+    read_trimmed("/proc/sys/kernel/hostname")
+        .or_else(|| read_trimmed("/etc/hostname"))
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .filter(|host| !host.is_empty())
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn format_uptime(seconds: u64) -> String {
+    let days = seconds / 86_400;
+    let hours = seconds % 86_400 / 3600;
+    let minutes = seconds % 3600 / 60;
+    match days {
+        0 => format!("{}:{:02}", hours, minutes),
+        1 => format!("1 day, {}:{:02}", hours, minutes),
+        _ => format!("{} days, {}:{:02}", days, hours, minutes),
+    }
+}
@@ -0,0 +1,402 @@
+use std::fmt;
+
+use crate::rocket::{self, Feature, PartType, Rarity, MIN_WIDTH};
+use crate::toml::{self, Table, Value};
+
+/// A part read from a part pack, a TOML file with one `[[part]]` table per part:
+///
+/// ```toml
+/// [[part]]
+/// id = "body_stage"
+/// name = "Finned stage"
+/// type = "body"
+/// top_width = 3
+/// bottom_width = 3
+/// height = 2
+/// weight = 1
+/// shape = '''
+/// /│ ^ │\
+/// /_│ | │_\'''
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct PackPart {
+    /// See [`crate::rocket::Part::id`], made up when it's left out
+    pub id: Option<String>,
+    /// Goes by the id when it's left out
+    pub name: Option<String>,
+    /// Common when it's left out
+    pub rarity: Rarity,
+    /// See [`Feature`], none when it's left out
+    pub feature: Option<Feature>,
+    pub part_type: PartType,
+    pub top_width: usize,
+    pub bottom_width: usize,
+    pub height: usize,
+    pub selection_weight: usize,
+    pub shape: String,
+    /// Line of the `[[part]]` header, for pointing at the part in messages
+    pub line: usize,
+    /// Line of the `shape` key
+    shape_line: usize,
+    /// Line of the `weight` key
+    weight_line: usize,
+    /// Line of the `id` key
+    id_line: usize,
+    /// Line of the `feature` key
+    feature_line: usize,
+}
+
+/// Something wrong with a part pack, and the line it's on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Reads every part out of a part pack, reporting all the entries that can't be read rather than
+/// stopping at the first.
+pub fn parse(text: &str) -> Result<Vec<PackPart>, Vec<Problem>> {
+    let tables = toml::parse(text).map_err(|e| vec![Problem { line: e.line, message: e.message }])?;
+    let mut parts = Vec::new();
+    let mut problems = Vec::new();
+    for table in tables {
+        match (table.name.as_str(), table.array) {
+            ("", _) if table.entries.is_empty() => continue,
+            ("part", true) => match part(&table) {
+                Ok(part) => parts.push(part),
+                Err(mut p) => problems.append(&mut p),
+            },
+            ("", _) => problems.push(Problem {
+                line: table.entries[0].line,
+                message: "Keys belong in a [[part]] table".to_string(),
+            }),
+            (name, _) => problems.push(Problem {
+                line: table.line,
+                message: format!("Unknown table \"{}\", parts go in [[part]] tables", name),
+            }),
+        }
+    }
+    if problems.is_empty() {
+        return Ok(parts);
+    }
+    problems.sort_by_key(|p| p.line);
+    Err(problems)
+}
+
+fn part(table: &Table) -> Result<PackPart, Vec<Problem>> {
+    let mut problems = Vec::new();
+    for entry in &table.entries {
+        if !["id", "name", "rarity", "feature", "type", "top_width", "bottom_width", "height", "weight", "shape"].contains(&entry.key.as_str()) {
+            problems.push(Problem { line: entry.line, message: format!("Unknown key \"{}\"", entry.key) });
+        }
+    }
+    let mut field = |key: &str| match table.get(key) {
+        Some(entry) => Some(entry),
+        None => {
+            problems.push(Problem { line: table.line, message: format!("Part is missing \"{}\"", key) });
+            None
+        }
+    };
+    let (part_type, shape) = (field("type"), field("shape"));
+    let (top_width, bottom_width, height, weight) = (field("top_width"), field("bottom_width"), field("height"), field("weight"));
+
+    let mut number = |entry: Option<&toml::Entry>| match entry.map(|e| (e, &e.value)) {
+        Some((_, Value::Integer(n))) if *n >= 0 => Some(*n as usize),
+        Some((e, _)) => {
+            problems.push(Problem { line: e.line, message: format!("\"{}\" must be a whole number", e.key) });
+            None
+        }
+        None => None,
+    };
+    let (top_width, bottom_width, height, selection_weight) = (number(top_width), number(bottom_width), number(height), number(weight));
+
+    let part_type = match part_type.map(|e| (e, &e.value)) {
+        Some((e, Value::String(s))) => s.parse().map_err(|message| problems.push(Problem { line: e.line, message })).ok(),
+        Some((e, _)) => {
+            problems.push(Problem { line: e.line, message: "\"type\" must be a string".to_string() });
+            None
+        }
+        None => None,
+    };
+    let mut optional_text = |key: &str| match table.get(key).map(|e| (e, &e.value)) {
+        Some((_, Value::String(s))) => Some(s.clone()),
+        Some((e, _)) => {
+            problems.push(Problem { line: e.line, message: format!("\"{}\" must be a string", key) });
+            None
+        }
+        None => None,
+    };
+    let (id, name, rarity, feature) = (optional_text("id"), optional_text("name"), optional_text("rarity"), optional_text("feature"));
+    let rarity = match rarity.map(|r| r.parse()) {
+        Some(Ok(rarity)) => rarity,
+        Some(Err(message)) => {
+            problems.push(Problem { line: table.get("rarity").map_or(table.line, |e| e.line), message });
+            Rarity::Common
+        }
+        None => Rarity::Common,
+    };
+    let feature = match feature.map(|f| f.parse()) {
+        Some(Ok(feature)) => Some(feature),
+        Some(Err(message)) => {
+            problems.push(Problem { line: table.get("feature").map_or(table.line, |e| e.line), message });
+            None
+        }
+        None => None,
+    };
+    let shape_entry = shape;
+    let shape = match shape_entry.map(|e| (e, &e.value)) {
+        Some((_, Value::String(s))) => Some(s.clone()),
+        Some((e, _)) => {
+            problems.push(Problem { line: e.line, message: "\"shape\" must be a string".to_string() });
+            None
+        }
+        None => None,
+    };
+
+    match (part_type, top_width, bottom_width, height, selection_weight, shape) {
+        (Some(part_type), Some(top_width), Some(bottom_width), Some(height), Some(selection_weight), Some(shape)) if problems.is_empty() => {
+            Ok(PackPart {
+                id,
+                name,
+                rarity,
+                feature,
+                part_type,
+                top_width,
+                bottom_width,
+                height,
+                selection_weight,
+                shape,
+                line: table.line,
+                shape_line: shape_entry.map_or(table.line, |e| e.line),
+                weight_line: table.get("weight").map_or(table.line, |e| e.line),
+                id_line: table.get("id").map_or(table.line, |e| e.line),
+                feature_line: table.get("feature").map_or(table.line, |e| e.line),
+            })
+        }
+        _ => Err(problems),
+    }
+}
+
+/// Checks that every part's declared geometry matches its shape, and that rockets can always be
+/// finished from the pack's parts.
+pub fn validate(parts: &[PackPart]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        problems.extend(check_part(part));
+        if let Some(id) = &part.id {
+            if parts[..i].iter().any(|p| p.id.as_ref() == Some(id)) {
+                problems.push(Problem { line: part.id_line, message: format!("Another part already has the id \"{}\"", id) });
+            }
+        }
+    }
+
+    if !parts.iter().any(|p| p.part_type == PartType::Body && p.top_width == 0) {
+        problems.push(pack_problem(parts, "No body part with a top_width of 0 to start rockets from the nose"));
+    }
+    if !parts.iter().any(|p| p.part_type == PartType::Engine) {
+        problems.push(pack_problem(parts, "No engine parts"));
+    }
+
+    // Widths a rocket can grow to below the nose cone, following the bodies
+    let bodies = |width: usize| parts.iter().filter(move |p| p.part_type == PartType::Body && p.top_width == width);
+    let hull_widths = spread(bodies(0).map(|p| p.bottom_width).collect(), |width| bodies(width).map(|p| p.bottom_width).collect());
+    for part in parts.iter().filter(|p| matches!(p.part_type, PartType::Body | PartType::Engine)) {
+        if part.top_width > 0 && !hull_widths.contains(&part.top_width) {
+            problems.push(Problem {
+                line: part.line,
+                message: format!("Nothing leads to a top_width of {}, so this {} is never used", part.top_width, part.part_type),
+            });
+        }
+    }
+    for &width in &hull_widths {
+        if !bodies(width).any(|p| p.height <= 2) {
+            problems.push(pack_problem(parts, &format!(
+                "Rockets can reach a width of {} but there's no body of height 1 or 2 to carry on from it", width)));
+        }
+        if !parts.iter().any(|p| p.part_type == PartType::Engine && p.top_width == width && p.height == 1) {
+            problems.push(pack_problem(parts, &format!(
+                "Rockets can reach a width of {} but there's no height 1 engine to finish them", width)));
+        }
+    }
+
+    // Decorations are matched against the bottom of the engine, or of the exhaust below it
+    let decorations = |width: usize| parts.iter().filter(move |p| {
+        matches!(p.part_type, PartType::Tip | PartType::Exhaust) && p.top_width == width && p.height == 1
+    });
+    let engine_widths = parts.iter()
+        .filter(|p| p.part_type == PartType::Engine && hull_widths.contains(&p.top_width))
+        .map(|p| p.bottom_width)
+        .collect();
+    let tail_widths = spread(engine_widths, |width| {
+        decorations(width).filter(|p| p.part_type == PartType::Exhaust).map(|p| p.bottom_width).collect()
+    });
+    for &width in &tail_widths {
+        if decorations(width).next().is_none() {
+            problems.push(pack_problem(parts, &format!(
+                "No height 1 tip or exhaust with a top_width of {} to fill out rockets ending that wide", width)));
+        }
+    }
+    problems.sort_by_key(|p| p.line);
+    problems
+}
+
+fn check_part(part: &PackPart) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let shape = |message: String| Problem { line: part.shape_line, message };
+    if part.selection_weight == 0 {
+        problems.push(Problem { line: part.weight_line, message: "\"weight\" must be more than 0 or the part is never picked".to_string() });
+    }
+    if let Some(id) = &part.id {
+        // Ids are given in comma separated lists after a pack's name and a colon
+        if id.is_empty() || id.contains([':', ',']) || id.contains(char::is_whitespace) {
+            problems.push(Problem { line: part.id_line, message: "\"id\" can't be empty or have spaces, colons or commas".to_string() });
+        }
+    }
+    if let Some(feature) = part.feature.filter(|f| f.part_type() != part.part_type) {
+        problems.push(Problem { line: part.feature_line, message: format!("Only {} parts can have {}", feature.part_type(), feature) });
+    }
+    if let Some(feature) = part.feature.filter(|f| f.nose().is_some() && part.top_width != 0) {
+        problems.push(Problem { line: part.feature_line, message: format!("Only noses, bodies with a top_width of 0, can have {}", feature) });
+    }
+    let lines: Vec<&str> = part.shape.lines().collect();
+    if lines.len() != part.height {
+        problems.push(shape(format!("Shape has {} lines but \"height\" is {}", lines.len(), part.height)));
+    }
+    let widest = part.top_width.max(part.bottom_width);
+    // Width 0 ends are points, a needle or a nozzle no wider than a width 1 hull
+    let allowed = if widest == 0 { MIN_WIDTH } else { widest + 2 };
+    let ends = match lines.len() {
+        1 => vec![(0, widest, "top_width or bottom_width")],
+        _ => vec![(0, part.top_width, "top_width"), (lines.len() - 1, part.bottom_width, "bottom_width")],
+    };
+    for (i, width, key) in ends {
+        let hull = rocket::measure(lines[i].as_bytes(), 0, lines[i].len()).1;
+        if !lines[i].trim().is_empty() && !rocket::hull_fits(hull, width) {
+            let expected = if width == 0 { format!("at most {}", MIN_WIDTH) } else { (width + 2).to_string() };
+            problems.push(shape(format!("Shape line {} has a hull {} wide, but a {} of {} needs {}", i + 1, hull, key, width, expected)));
+        }
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let (width, hull) = rocket::measure(line.as_bytes(), 0, line.len());
+        if line.trim().is_empty() {
+            problems.push(shape(format!("Shape line {} is blank", i + 1)));
+            continue;
+        }
+        if width.is_multiple_of(2) {
+            problems.push(shape(format!("Shape line {} is {} wide, only odd widths can be centered", i + 1, width)));
+        }
+        if hull > allowed {
+            problems.push(shape(format!("Shape line {} has a hull {} wide, more than widths {} and {} allow",
+                                        i + 1, hull, part.top_width, part.bottom_width)));
+        }
+    }
+    problems
+}
+
+/// Every width reachable from `start` by repeatedly following `next`.
+fn spread(start: Vec<usize>, next: impl Fn(usize) -> Vec<usize>) -> Vec<usize> {
+    let mut widths = Vec::new();
+    let mut pending = start;
+    while let Some(width) = pending.pop() {
+        if !widths.contains(&width) {
+            widths.push(width);
+            pending.extend(next(width));
+        }
+    }
+    widths.sort_unstable();
+    widths
+}
+
+fn pack_problem(parts: &[PackPart], message: &str) -> Problem {
+    Problem { line: parts.first().map_or(1, |p| p.line), message: message.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pack that builds rockets one width wide, 20 lines long, for tests to add a part to at line 21.
+    const PACK: &str = "\
+[[part]]
+type = \"body\"
+top_width = 0
+bottom_width = 1
+height = 1
+weight = 1
+shape = \"/'\\\\\"
+[[part]]
+type = \"body\"
+top_width = 1
+bottom_width = 1
+height = 1
+weight = 1
+shape = \"│ │\"
+[[part]]
+type = \"engine\"
+top_width = 1
+bottom_width = 1
+height = 1
+weight = 1
+shape = \"/_\\\\\"
+[[part]]
+type = \"exhaust\"
+top_width = 1
+bottom_width = 1
+height = 1
+weight = 1
+shape = \" ' \"
+";
+
+    /// The problems with the pack and `part` after it, whether reading or validating finds them.
+    fn problems(part: &str) -> Vec<Problem> {
+        parse(&format!("{}{}", PACK, part)).map_or_else(|problems| problems, |parts| validate(&parts))
+    }
+
+    fn problem(line: usize, message: &str) -> Problem {
+        Problem { line, message: message.to_string() }
+    }
+
+    #[test]
+    fn the_pack_is_valid() {
+        assert_eq!(problems(""), []);
+    }
+
+    #[test]
+    fn missing_keys_point_at_the_table() {
+        let part = "[[part]]\ntype = \"body\"\ntop_width = 1\nbottom_width = 1\nheight = 1\nshape = \"│°│\"\n";
+        assert_eq!(problems(part), [problem(29, "Part is missing \"weight\"")]);
+    }
+
+    #[test]
+    fn wrong_types_point_at_the_key() {
+        let part = "[[part]]\ntype = \"body\"\ntop_width = 1\nbottom_width = 1\nheight = \"one\"\nweight = 1\nshape = \"│°│\"\n";
+        assert_eq!(problems(part), [problem(33, "\"height\" must be a whole number")]);
+    }
+
+    #[test]
+    fn duplicate_ids_point_at_the_second_id() {
+        let part = |shape| format!("[[part]]\nid = \"porthole\"\ntype = \"body\"\ntop_width = 1\nbottom_width = 1\nheight = 1\nweight = 1\nshape = \"{}\"\n", shape);
+        assert_eq!(problems(&(part("│°│") + &part("│o│"))), [problem(38, "Another part already has the id \"porthole\"")]);
+    }
+
+    #[test]
+    fn wide_end_lines_point_at_the_shape() {
+        let part = "[[part]]\ntype = \"body\"\ntop_width = 1\nbottom_width = 1\nheight = 2\nweight = 1\nshape = \"│ │\\n│   │\"\n";
+        assert_eq!(problems(part), [
+            problem(35, "Shape line 2 has a hull 5 wide, but a bottom_width of 1 needs 3"),
+            problem(35, "Shape line 2 has a hull 5 wide, more than widths 1 and 1 allow"),
+        ]);
+    }
+
+    #[test]
+    fn unreachable_widths_point_at_the_part() {
+        let part = "[[part]]\ntype = \"engine\"\ntop_width = 5\nbottom_width = 5\nheight = 1\nweight = 1\nshape = \"/_____\\\\\"\n";
+        assert_eq!(problems(part), [problem(29, "Nothing leads to a top_width of 5, so this engine is never used")]);
+    }
+}
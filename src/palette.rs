@@ -0,0 +1,397 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::rocket::PartType;
+use crate::toml;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    fn hex(hex: u32) -> Color {
+        Color::rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    /// The color of `hue` degrees round the color wheel, red at 0, with `saturation` and
+    /// `lightness` from 0 to 1.
+    pub fn hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as usize {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |value: f64| ((value + lightness - chroma / 2.0) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Color::rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// The color `t` of the way from this one to `other`, 0 giving this one and 1 the other.
+    pub fn blend(&self, other: Color, t: f64) -> Color {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t.clamp(0.0, 1.0)).round() as u8;
+        Color::rgb(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// The color a quarter of the way to black, for filling in behind lines drawn in this one.
+    fn darker(&self) -> Color {
+        let darken = |value: u8| (value as u16 * 3 / 4) as u8;
+        Color::rgb(darken(self.r), darken(self.g), darken(self.b))
+    }
+}
+
+/// Two colors to blend between, from the top of what's painted to the bottom, written
+/// `#ffffff..#003366` or with color names.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Gradient {
+    pub top: Color,
+    pub bottom: Color,
+}
+
+impl Gradient {
+    /// The color `t` of the way down, from 0 at the top to 1 at the bottom.
+    pub fn at(&self, t: f64) -> Color {
+        self.top.blend(self.bottom, t)
+    }
+}
+
+impl FromStr for Gradient {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (top, bottom) = s.split_once("..").ok_or_else(|| format!("Expected two colors like #ffffff..#003366, not \"{}\"", s))?;
+        Ok(Gradient { top: top.trim().parse()?, bottom: bottom.trim().parse()? })
+    }
+}
+
+/// Colors by name, for writing palettes by hand
+const NAMED_COLORS: [(&str, u32); 18] = [
+    ("black", 0x000000),
+    ("white", 0xffffff),
+    ("silver", 0xc0c0c0),
+    ("grey", 0x808080),
+    ("gray", 0x808080),
+    ("red", 0xe0283c),
+    ("orange", 0xff9f1c),
+    ("yellow", 0xffdd00),
+    ("gold", 0xd4af37),
+    ("green", 0x2eb82e),
+    ("teal", 0x008080),
+    ("cyan", 0x00d0e0),
+    ("blue", 0x2f6fdf),
+    ("navy", 0x14286e),
+    ("purple", 0x8a2be2),
+    ("magenta", 0xe040e0),
+    ("pink", 0xff7eb6),
+    ("brown", 0x8b5a2b),
+];
+
+impl FromStr for Color {
+    type Err = String;
+
+    /// Reads a color name like `silver`, or a hex code like `#c0c0c0` or `#ccc`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase();
+        if let Some(&(_, hex)) = NAMED_COLORS.iter().find(|(name, _)| *name == lower) {
+            return Ok(Color::hex(hex));
+        }
+        lower.strip_prefix('#')
+            .map(|digits| match digits.len() {
+                3 => digits.chars().flat_map(|digit| [digit, digit]).collect(),
+                _ => digits.to_string(),
+            })
+            .filter(|digits| digits.len() == 6 && digits.chars().all(|digit| digit.is_ascii_hexdigit()))
+            .and_then(|digits| u32::from_str_radix(&digits, 16).ok())
+            .map(Color::hex)
+            .ok_or_else(|| format!("Unknown color \"{}\", expected a name like silver or a code like #c0c0c0", s))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Palette {
+    America,
+    Nasa,
+    Soviet,
+    Esa,
+    Mono,
+    Rainbow,
+    Synthwave,
+    /// A rainbow washing diagonally across the whole picture, whatever drew each character, see
+    /// [`Palette::wave`]
+    RainbowWave,
+    /// Colors given by hand, see [`Palette::with_colors`]
+    Custom(Scheme),
+}
+
+/// Every color a palette picks, indexed by part type in the order tip, body, engine, exhaust.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Scheme {
+    color: [Color; 4],
+    fill: [Color; 4],
+    text: Color,
+    background: Color,
+    /// Colors to blend the body between when nothing else asks for a gradient
+    paint: Option<Gradient>,
+    /// Colors from the 256 of xterm's palette to draw each of `color`, `text` and `background`
+    /// in, for terminals without true color
+    ansi256: [Option<u8>; 6],
+}
+
+/// The keys a palette file gives colors for, in the order of a custom palette's list, and the
+/// slots of [`Scheme::ansi256`]
+const ROLES: [&str; 6] = ["tip", "body", "engine", "exhaust", "text", "background"];
+
+impl Palette {
+    /// Every built in palette, in the order `ship_gen palettes` lists them.
+    pub const ALL: [Palette; 8] = [
+        Palette::America,
+        Palette::Nasa,
+        Palette::Soviet,
+        Palette::Esa,
+        Palette::Mono,
+        Palette::Rainbow,
+        Palette::Synthwave,
+        Palette::RainbowWave,
+    ];
+
+    /// Color used for characters drawn by the given type of part.
+    pub fn color(&self, part_type: PartType) -> Color {
+        self.scheme().color[index(part_type)]
+    }
+
+    /// Color for the inside of a part's hull, for renderers that draw rockets filled in.
+    pub fn fill(&self, part_type: PartType) -> Color {
+        self.scheme().fill[index(part_type)]
+    }
+
+    /// Color for lettering drawn alongside the rocket.
+    pub fn text(&self) -> Color {
+        self.scheme().text
+    }
+
+    /// Color behind the rocket for formats that paint every pixel, like images.
+    pub fn background(&self) -> Color {
+        self.scheme().background
+    }
+
+    /// Colors to blend the body between, for palettes that come with a gradient.
+    pub fn paint(&self) -> Option<Gradient> {
+        self.scheme().paint
+    }
+
+    /// The color from xterm's 256 color palette the palette chose to stand in for `color`, if
+    /// `color` is one of its own and it chose one.
+    pub fn ansi256(&self, color: Color) -> Option<u8> {
+        let scheme = self.scheme();
+        let [tip, body, engine, exhaust] = scheme.color;
+        let colors = [tip, body, engine, exhaust, scheme.text, scheme.background];
+        colors.into_iter().zip(scheme.ansi256).find(|&(c, _)| c == color).and_then(|(_, code)| code)
+    }
+
+    /// Reads a palette file, `america` with whichever of its colors the file gives:
+    ///
+    /// ```toml
+    /// tip = "#e0283c"
+    /// body = "white"
+    /// engine = "#5b7bd5"
+    /// exhaust = "orange"
+    /// # Optional: the insides of hulls, a shade darker than their lines when left out
+    /// body_fill = "silver"
+    /// text = "#e0283c"
+    /// background = "#0b1026"
+    /// # Optional: a gradient down the body, like --paint
+    /// paint = "#ffffff..#003366"
+    ///
+    /// # Optional: stand ins from xterm's 256 colors, for terminals without true color
+    /// [ansi256]
+    /// tip = 161
+    /// body = 231
+    /// ```
+    pub fn from_file(text: &str) -> Result<Palette, String> {
+        let mut scheme = Palette::America.scheme();
+        for table in toml::parse(text).map_err(|e| e.to_string())? {
+            for entry in &table.entries {
+                let error = |message: String| format!("line {}: {}", entry.line, message);
+                let value = entry.value.to_string();
+                let role = ROLES.iter().position(|&role| role == entry.key);
+                match (table.name.as_str(), entry.key.as_str(), role) {
+                    ("", "paint", _) => scheme.paint = Some(value.parse().map_err(error)?),
+                    ("", key, _) if key.ends_with("_fill") => {
+                        let part_type: PartType = key.trim_end_matches("_fill").parse().map_err(error)?;
+                        scheme.fill[index(part_type)] = value.parse().map_err(error)?;
+                    }
+                    ("", _, Some(role)) => {
+                        let color: Color = value.parse().map_err(error)?;
+                        match role {
+                            4 => scheme.text = color,
+                            5 => scheme.background = color,
+                            _ => {
+                                // Fills follow their lines unless the file says otherwise
+                                let fill = format!("{}_fill", entry.key);
+                                if table.get(&fill).is_none() {
+                                    scheme.fill[role] = color.darker();
+                                }
+                                scheme.color[role] = color;
+                            }
+                        }
+                    }
+                    ("ansi256", _, Some(role)) => match &entry.value {
+                        toml::Value::Integer(code @ 0..=255) => scheme.ansi256[role] = Some(*code as u8),
+                        _ => return Err(error(format!("\"{}\" must be a color number from 0 to 255", entry.key))),
+                    },
+                    ("" | "ansi256", key, _) => return Err(error(format!("Unknown key \"{}\"", key))),
+                    (name, _, _) => return Err(error(format!("Unknown table \"{}\", only [ansi256] is allowed", name))),
+                }
+            }
+        }
+        Ok(Palette::Custom(scheme))
+    }
+
+    /// A few words on what the palette looks like.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Palette::America => "Red, white and blue",
+            Palette::Nasa => "A white hull with a red nose and a blue engine bell",
+            Palette::Soviet => "A red star nose on an off-white and olive hull",
+            Palette::Esa => "ESA's blues on a white hull",
+            Palette::Mono => "Shades of grey, for anything without color",
+            Palette::Rainbow => "A different color for every kind of part",
+            Palette::Synthwave => "Neon pink and cyan on a dusky purple",
+            Palette::RainbowWave => "A rainbow running diagonally across everything, like lolcat",
+            Palette::Custom(_) => "Colors given by hand",
+        }
+    }
+
+    /// A palette of shades of `hue`, in degrees round the color wheel: a pale body, a bold tip,
+    /// a deep engine and exhaust from the far side of the wheel, on a background nearly black.
+    pub fn from_hue(hue: u16) -> Palette {
+        let hue = hue as f64;
+        let color = [
+            Color::hsl(hue, 0.8, 0.55),
+            Color::hsl(hue, 0.5, 0.8),
+            Color::hsl(hue, 0.6, 0.4),
+            Color::hsl(hue + 150.0, 0.9, 0.6),
+        ];
+        Palette::Custom(Scheme {
+            color,
+            fill: color.map(|color| color.darker()),
+            text: color[0],
+            background: Color::hsl(hue, 0.4, 0.06),
+            paint: None,
+            ansi256: [None; 6],
+        })
+    }
+
+    /// This palette with some colors replaced, as given by `key=color` pairs like `tip=white
+    /// body=silver engine=grey exhaust=orange`. Keys are the part types, whose insides are filled
+    /// in a darker shade of their new color, or `text` and `background`. Colors are names or hex
+    /// codes, see [`Color`]'s `FromStr`.
+    pub fn with_colors(&self, spec: &str) -> Result<Palette, String> {
+        let mut scheme = self.scheme();
+        for pair in spec.split_whitespace() {
+            let (key, value) = pair.split_once('=').ok_or_else(|| format!("Expected key=color, not \"{}\"", pair))?;
+            let color: Color = value.parse()?;
+            match key {
+                "text" => scheme.text = color,
+                "background" => scheme.background = color,
+                part_type => {
+                    let part_type: PartType = part_type.parse()
+                        .map_err(|_| format!("Unknown color key \"{}\", expected a part type, text or background", key))?;
+                    scheme.color[index(part_type)] = color;
+                    scheme.fill[index(part_type)] = color.darker();
+                }
+            }
+        }
+        Ok(Palette::Custom(scheme))
+    }
+
+    /// The color a palette that paints by position gives the character at column `x` of line `y`,
+    /// or `None` for palettes that color by part. Only `rainbow-wave` does, cycling through the
+    /// hues about every 60 columns and a line down counting for two across.
+    pub fn wave(&self, x: usize, y: usize) -> Option<Color> {
+        if *self != Palette::RainbowWave {
+            return None;
+        }
+        let phase = 0.1 * (x + 2 * y) as f64;
+        let channel = |offset: f64| (127.5 + 127.5 * (phase + offset * std::f64::consts::TAU / 3.0).sin()) as u8;
+        Some(Color::rgb(channel(0.0), channel(1.0), channel(2.0)))
+    }
+
+    fn scheme(&self) -> Scheme {
+        let [tip, body, engine, exhaust, tip_fill, body_fill, engine_fill, exhaust_fill, text, background] = match self {
+            Palette::America => [0xe0283c, 0xffffff, 0x5b7bd5, 0xe0283c, 0xe0283c, 0xb4bccc, 0x2c3e7a, 0xff9f1c, 0xe0283c, 0x0b1026],
+            Palette::Nasa => [0xfc3d21, 0xffffff, 0x2f6fdf, 0xffb000, 0xfc3d21, 0xd9dde3, 0x0b3d91, 0xff8c1a, 0xfc3d21, 0x000814],
+            Palette::Soviet => [0xcc0000, 0xeae0c8, 0x8f9779, 0xffcc00, 0xcc0000, 0xb5ad94, 0x4b4f3c, 0xff8800, 0xffcc00, 0x1a0505],
+            Palette::Esa => [0x009bdb, 0xffffff, 0x6fb2d2, 0xffd100, 0x009bdb, 0xc8d3da, 0x003247, 0xff9e1b, 0x009bdb, 0x001821],
+            Palette::Mono => [0xffffff, 0xd0d0d0, 0xa0a0a0, 0x707070, 0xbbbbbb, 0x8c8c8c, 0x5a5a5a, 0x3c3c3c, 0xffffff, 0x000000],
+            Palette::Rainbow | Palette::RainbowWave => [0xe81416, 0xffa500, 0x79c314, 0x487de7, 0xe81416, 0xfaeb36, 0x4b369d, 0x70369d, 0xffa500, 0x14141e],
+            Palette::Synthwave => [0xff2a6d, 0x05d9e8, 0xd1f7ff, 0xff6c11, 0xff2a6d, 0x7700a6, 0x005678, 0xf9c80e, 0xff2a6d, 0x1a0933],
+            Palette::Custom(scheme) => return *scheme,
+        }.map(Color::hex);
+        Scheme {
+            color: [tip, body, engine, exhaust],
+            fill: [tip_fill, body_fill, engine_fill, exhaust_fill],
+            text,
+            background,
+            paint: None,
+            ansi256: [None; 6],
+        }
+    }
+}
+
+fn index(part_type: PartType) -> usize {
+    match part_type {
+        PartType::Tip => 0,
+        PartType::Body => 1,
+        PartType::Engine => 2,
+        PartType::Exhaust => 3,
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Palette::America => write!(f, "america"),
+            Palette::Nasa => write!(f, "nasa"),
+            Palette::Soviet => write!(f, "soviet"),
+            Palette::Esa => write!(f, "esa"),
+            Palette::Mono => write!(f, "mono"),
+            Palette::Rainbow => write!(f, "rainbow"),
+            Palette::Synthwave => write!(f, "synthwave"),
+            Palette::RainbowWave => write!(f, "rainbow-wave"),
+            Palette::Custom(_) => write!(f, "custom"),
+        }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    /// Reads a palette's name, or `custom:` and up to six comma separated colors for the tip, body,
+    /// engine, exhaust, text and background in that order, like `custom:#ff0000,#ffffff,#0000ff`.
+    /// Anything a custom palette leaves out comes from `america`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(colors) = s.strip_prefix("custom:") {
+            let colors: Vec<&str> = colors.split(',').map(str::trim).collect();
+            if colors.len() > ROLES.len() {
+                return Err(format!("A custom palette has at most {} colors, for the {}", ROLES.len(), ROLES.join(", ")));
+            }
+            let spec: Vec<String> = ROLES.iter().zip(colors).map(|(slot, color)| format!("{}={}", slot, color)).collect();
+            return Palette::America.with_colors(&spec.join(" "));
+        }
+        Palette::ALL.into_iter()
+            .find(|palette| palette.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown palette \"{}\"", s))
+    }
+}
@@ -0,0 +1,78 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::palette::Palette;
+use crate::render::Options;
+use crate::registry;
+use crate::rocket::{PartType, Rocket};
+
+/// A copy of one part from the parts bin.
+#[pyclass(name = "Part", frozen, get_all)]
+pub struct PyPart {
+    /// Stays the same from release to release, see `ship_gen parts list`
+    id: String,
+    name: String,
+    /// `tip`, `body`, `engine` or `exhaust`
+    kind: String,
+    shape: String,
+    height: usize,
+    top_width: usize,
+    bottom_width: usize,
+    selection_weight: usize,
+}
+
+#[pymethods]
+impl PyPart {
+    fn __repr__(&self) -> String {
+        format!("Part(id={:?}, kind={:?}, shape={:?}, top_width={}, bottom_width={})",
+                self.id, self.kind, self.shape, self.top_width, self.bottom_width)
+    }
+
+    fn __str__(&self) -> String {
+        self.shape.clone()
+    }
+}
+
+/// Generates a rocket `height` lines tall and returns its art. Leaving out `seed` picks a random
+/// one; the same seed and height always give the same rocket.
+#[pyfunction]
+#[pyo3(signature = (height, seed=None, palette="america"))]
+fn generate_rocket(height: usize, seed: Option<u64>, palette: &str) -> PyResult<String> {
+    let palette: Palette = palette.parse().map_err(PyValueError::new_err)?;
+    let seed = seed.unwrap_or_else(rand::random);
+    Rocket::generate_with(height, seed, &Options { palette, ..Options::default() })
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Lists the parts rockets are built from, optionally only those of one `kind`.
+#[pyfunction]
+#[pyo3(signature = (kind=None))]
+fn parts(kind: Option<&str>) -> PyResult<Vec<PyPart>> {
+    let kinds = [PartType::Tip, PartType::Body, PartType::Engine, PartType::Exhaust];
+    if let Some(kind) = kind {
+        if !kinds.iter().any(|k| k.to_string() == kind) {
+            return Err(PyValueError::new_err(format!("Unknown part kind \"{}\"", kind)));
+        }
+    }
+    Ok(registry::builtin().pack("builtin").unwrap_or_default().iter()
+        .filter(|p| kind.is_none_or(|k| p.part_type().to_string() == k))
+        .map(|p| PyPart {
+            id: p.id().to_string(),
+            name: p.name().to_string(),
+            kind: p.part_type().to_string(),
+            shape: p.shape().to_string(),
+            height: p.height(),
+            top_width: p.top_width(),
+            bottom_width: p.bottom_width(),
+            selection_weight: p.selection_weight(),
+        })
+        .collect())
+}
+
+#[pymodule]
+fn ship_gen(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPart>()?;
+    m.add_function(wrap_pyfunction!(generate_rocket, m)?)?;
+    m.add_function(wrap_pyfunction!(parts, m)?)?;
+    Ok(())
+}
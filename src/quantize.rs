@@ -0,0 +1,53 @@
+use crate::palette::Color;
+
+/// The 16 colors of an ANSI.SYS style viewer in SGR order, normal then bright. Terminals each
+/// have their own, but these are close to most of them.
+pub const ANSI_16: [Color; 16] = [
+    Color::rgb(0x00, 0x00, 0x00),
+    Color::rgb(0xaa, 0x00, 0x00),
+    Color::rgb(0x00, 0xaa, 0x00),
+    Color::rgb(0xaa, 0x55, 0x00),
+    Color::rgb(0x00, 0x00, 0xaa),
+    Color::rgb(0xaa, 0x00, 0xaa),
+    Color::rgb(0x00, 0xaa, 0xaa),
+    Color::rgb(0xaa, 0xaa, 0xaa),
+    Color::rgb(0x55, 0x55, 0x55),
+    Color::rgb(0xff, 0x55, 0x55),
+    Color::rgb(0x55, 0xff, 0x55),
+    Color::rgb(0xff, 0xff, 0x55),
+    Color::rgb(0x55, 0x55, 0xff),
+    Color::rgb(0xff, 0x55, 0xff),
+    Color::rgb(0x55, 0xff, 0xff),
+    Color::rgb(0xff, 0xff, 0xff),
+];
+
+/// Levels of each channel in xterm's 6x6x6 color cube, colors 16 to 231
+const CUBE_LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+fn distance(a: Color, b: Color) -> i32 {
+    let dr = a.r as i32 - b.r as i32;
+    let dg = a.g as i32 - b.g as i32;
+    let db = a.b as i32 - b.b as i32;
+    dr * dr + dg * dg + db * db
+}
+
+/// Index of the nearest of the [`ANSI_16`] colors.
+pub fn nearest_16(color: Color) -> u8 {
+    (0..ANSI_16.len()).min_by_key(|&i| distance(ANSI_16[i], color)).unwrap() as u8
+}
+
+/// The nearest of xterm's 256 colors past the first 16, from the color cube or the 24 greys.
+pub fn nearest_256(color: Color) -> u8 {
+    let level = |value: u8| (0..CUBE_LEVELS.len()).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs()).unwrap();
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = Color::rgb(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+    // Greys run from 8 to 238 in steps of 10
+    let average = (color.r as usize + color.g as usize + color.b as usize) / 3;
+    let grey_index = (average.saturating_sub(3) / 10).min(23);
+    let grey_level = (8 + grey_index * 10) as u8;
+    if distance(Color::rgb(grey_level, grey_level, grey_level), color) < distance(cube, color) {
+        232 + grey_index as u8
+    } else {
+        16 + (36 * r + 6 * g + b) as u8
+    }
+}
@@ -0,0 +1,71 @@
+use crate::font::{self, GLYPH_SIZE};
+use crate::grid::Grid;
+use crate::palette::{Color, Palette};
+
+/// Pixel width of one grid cell before scaling.
+pub const CELL_WIDTH: usize = GLYPH_SIZE;
+/// Pixel height of one grid cell before scaling. Glyph rows are doubled so cells keep the tall
+/// proportions of a terminal character and the rocket doesn't look squashed.
+pub const CELL_HEIGHT: usize = GLYPH_SIZE * 2;
+
+/// A grid rasterized through the embedded bitmap font. Pixels the font didn't set are `None`.
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Option<Color>>,
+}
+
+impl Raster {
+    /// Draws every cell of `grid` in its palette color, `scale` pixels per font pixel.
+    pub fn from_grid(grid: &Grid, palette: &Palette, scale: usize) -> Raster {
+        let cell_width = CELL_WIDTH * scale;
+        let cell_height = CELL_HEIGHT * scale;
+        let width = grid.width() * cell_width;
+        let height = grid.height() * cell_height;
+        let mut pixels = vec![None; width * height];
+
+        for (row, cells) in grid.rows().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let color = cell.foreground(palette).filter(|_| !cell.is_blank());
+                if color.is_none() && cell.background.is_none() {
+                    continue;
+                }
+                let glyph = font::glyph(cell.ch);
+                for y in 0..cell_height {
+                    for x in 0..cell_width {
+                        let lit = font::pixel(&glyph, x / scale, y * GLYPH_SIZE / cell_height);
+                        let pixel = if lit { color } else { None }.or(cell.background);
+                        if pixel.is_some() {
+                            let px = col * cell_width + x;
+                            let py = row * cell_height + y;
+                            pixels[py * width + px] = pixel;
+                        }
+                    }
+                }
+            }
+        }
+        Raster { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Flattens the raster to 8-bit RGB triples, filling unset pixels with `background`.
+    pub fn to_rgb(&self, background: Color) -> Vec<u8> {
+        self.pixels.iter()
+            .flat_map(|p| {
+                let c = p.unwrap_or(background);
+                [c.r, c.g, c.b]
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,112 @@
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
+
+use crate::pack::{self, Problem};
+use crate::rocket::{Part, PACKS};
+
+/// Parts rockets can be built from, grouped into named packs: the built in ones and any loaded or
+/// made at runtime. Parts are shared, so rockets built from them keep them alive on their own, and
+/// a part's index in [`PartRegistry::parts`] names it for as long as the registry lasts.
+#[derive(Clone, Debug, Default)]
+pub struct PartRegistry {
+    parts: Vec<Arc<Part>>,
+    packs: Vec<(String, Range<usize>)>,
+}
+
+static BUILTIN: LazyLock<PartRegistry> = LazyLock::new(|| {
+    let mut registry = PartRegistry::default();
+    for (name, parts) in PACKS {
+        registry.add_pack(name, parts.iter().cloned()).expect("built in pack names are unique");
+    }
+    registry
+});
+
+/// The built in packs, shared rather than built again for every rocket.
+pub fn builtin() -> &'static PartRegistry {
+    &BUILTIN
+}
+
+impl PartRegistry {
+    /// Adds a pack of parts under `name`, each asymmetric part followed by its mirror image at the
+    /// same weight, see [`Part::mirrored`]. The parts aren't checked, so a pack that can't finish
+    /// a rocket makes building one panic; [`PartRegistry::load_pack`] checks them first.
+    pub fn add_pack(&mut self, name: &str, parts: impl IntoIterator<Item = Part>) -> Result<(), String> {
+        if self.pack(name).is_some() {
+            return Err(format!("There's already a pack named \"{}\"", name));
+        }
+        let start = self.parts.len();
+        for part in parts {
+            let mirrored = part.mirrored();
+            self.parts.push(Arc::new(part));
+            self.parts.extend(mirrored.map(Arc::new));
+        }
+        self.packs.push((name.to_string(), start..self.parts.len()));
+        Ok(())
+    }
+
+    /// Reads a part pack and adds it under `name` if [`pack::validate`] finds nothing wrong with it.
+    pub fn load_pack(&mut self, name: &str, text: &str) -> Result<(), Vec<Problem>> {
+        let parts = pack::parse(text)?;
+        let problems = pack::validate(&parts);
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        let parts = parts.into_iter()
+            .map(|p| {
+                let part = Part::new(p.part_type, p.top_width, p.bottom_width, p.height, p.selection_weight, p.shape);
+                // Unnamed parts go by their id, made up or not
+                let id = p.id.unwrap_or_else(|| part.id().to_string());
+                let name = p.name.unwrap_or_else(|| id.clone());
+                let part = part.named(id, name).with_rarity(p.rarity);
+                match p.feature {
+                    Some(feature) => part.with_feature(feature),
+                    None => part,
+                }
+            });
+        self.add_pack(name, parts).map_err(|message| vec![Problem { line: 1, message }])
+    }
+
+    /// Every part in every pack, in the order they were added.
+    pub fn parts(&self) -> &[Arc<Part>] {
+        &self.parts
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Arc<Part>> {
+        self.parts.get(index)
+    }
+
+    /// Where `part` is in [`PartRegistry::parts`], if it's this registry's own.
+    pub fn index_of(&self, part: &Arc<Part>) -> Option<usize> {
+        self.parts.iter().position(|p| Arc::ptr_eq(p, part))
+    }
+
+    /// The pack `part` belongs to and its place in it, as `parts list` numbers them.
+    pub fn locate(&self, part: &Arc<Part>) -> Option<(&str, usize)> {
+        let index = self.index_of(part)?;
+        self.packs.iter()
+            .find(|(_, range)| range.contains(&index))
+            .map(|(name, range)| (name.as_str(), index - range.start))
+    }
+
+    /// The part `id` names: its [`Part::id`], the first part with that id if packs share one, or
+    /// the one in a pack after the pack's name, like `wide:body_hull_broad`. Parts are only found
+    /// by id, which stays the same from release to release, never by where they are in a pack.
+    pub fn find(&self, id: &str) -> Result<&Arc<Part>, String> {
+        let Some((pack, id)) = id.split_once(':') else {
+            return self.parts.iter().find(|p| p.id() == id).ok_or_else(|| format!("Unknown part \"{}\"", id));
+        };
+        let parts = self.pack(pack).ok_or_else(|| format!("Unknown pack \"{}\"", pack))?;
+        parts.iter().find(|p| p.id() == id).ok_or_else(|| format!("The {} pack has no part \"{}\"", pack, id))
+    }
+
+    pub fn pack(&self, name: &str) -> Option<&[Arc<Part>]> {
+        self.packs.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, range)| &self.parts[range.clone()])
+    }
+
+    /// The packs' names and parts, in the order they were added.
+    pub fn packs(&self) -> impl Iterator<Item = (&str, &[Arc<Part>])> {
+        self.packs.iter().map(|(name, range)| (name.as_str(), &self.parts[range.clone()]))
+    }
+}
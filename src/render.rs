@@ -0,0 +1,587 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::grid::{Cell, Grid};
+use crate::palette::{Gradient, Palette};
+use crate::rocket::{BuildOptions, PartType, Rocket};
+use crate::stats::Stats;
+
+mod ans;
+mod badge;
+mod base64;
+mod blocks;
+mod braille;
+mod cast;
+mod decal;
+mod exploded;
+mod flag;
+#[cfg(feature = "gif")]
+mod gif;
+mod html;
+mod iterm;
+mod json;
+mod kitty;
+mod markdown;
+mod orientation;
+#[cfg(feature = "png")]
+mod png;
+mod sixel;
+mod style;
+mod terminal;
+mod wind;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Text,
+    Png,
+    Html,
+    Ans,
+    /// The rocket's parts and art as a JSON document
+    Json,
+    /// An animated image of `--animate` frames, or a still of the rocket
+    Gif,
+    /// An asciinema recording of `--animate` frames, or of the rocket
+    Cast,
+    /// The art in a fenced code block, for pull requests and issues
+    Markdown,
+}
+
+impl Format {
+    /// File extension for files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Png => "png",
+            Format::Html => "html",
+            Format::Ans => "ans",
+            Format::Json => "json",
+            Format::Gif => "gif",
+            Format::Cast => "cast",
+            Format::Markdown => "md",
+        }
+    }
+
+    /// Whether output in this format is a file of its own, an image, an ANSI art file with its
+    /// SAUCE record at the end or a recording, which can't have another written after it.
+    pub fn whole_file(&self) -> bool {
+        matches!(self, Format::Png | Format::Gif | Format::Ans | Format::Cast)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Text => write!(f, "text"),
+            Format::Png => write!(f, "png"),
+            Format::Html => write!(f, "html"),
+            Format::Ans => write!(f, "ans"),
+            Format::Json => write!(f, "json"),
+            Format::Gif => write!(f, "gif"),
+            Format::Cast => write!(f, "cast"),
+            Format::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "png" => Ok(Format::Png),
+            "html" => Ok(Format::Html),
+            "ans" => Ok(Format::Ans),
+            "json" => Ok(Format::Json),
+            "gif" => Ok(Format::Gif),
+            "cast" => Ok(Format::Cast),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(format!("Unknown format \"{}\"", s)),
+        }
+    }
+}
+
+/// How the rocket's shape is drawn before it's written out in a [`Format`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Renderer {
+    /// The parts' own characters
+    Glyphs,
+    /// Rasterized and redrawn with Unicode Braille dots
+    Braille,
+    /// Rasterized and painted in with half block characters
+    Blocks,
+    /// Rasterized and streamed to the terminal as DEC sixel graphics, with text output only
+    Sixel,
+    /// Rasterized and sent with the kitty terminal graphics protocol, with text output only
+    Kitty,
+    /// Rasterized to a PNG and shown inline by iTerm2 and the terminals that copy it, with text
+    /// output only
+    Iterm,
+}
+
+impl Renderer {
+    /// Whether the renderer draws an image for the terminal to show rather than characters.
+    pub fn is_graphics(&self) -> bool {
+        matches!(self, Renderer::Sixel | Renderer::Kitty | Renderer::Iterm)
+    }
+}
+
+impl fmt::Display for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Renderer::Glyphs => write!(f, "glyphs"),
+            Renderer::Braille => write!(f, "braille"),
+            Renderer::Blocks => write!(f, "blocks"),
+            Renderer::Sixel => write!(f, "sixel"),
+            Renderer::Kitty => write!(f, "kitty"),
+            Renderer::Iterm => write!(f, "iterm"),
+        }
+    }
+}
+
+impl FromStr for Renderer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "glyphs" => Ok(Renderer::Glyphs),
+            "braille" => Ok(Renderer::Braille),
+            "blocks" => Ok(Renderer::Blocks),
+            "sixel" => Ok(Renderer::Sixel),
+            "kitty" => Ok(Renderer::Kitty),
+            "iterm" => Ok(Renderer::Iterm),
+            _ => Err(format!("Unknown renderer \"{}\"", s)),
+        }
+    }
+}
+
+/// How the rocket's parts are arranged.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum View {
+    /// Put together, the way it flies
+    Assembled,
+    /// Spaced out a line apart, each part labelled with its kind, widths and height
+    Exploded,
+    /// Framed, in a frame the same size for every rocket as tall, see [`badge::frame`]
+    Badge,
+}
+
+impl FromStr for View {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "assembled" => Ok(View::Assembled),
+            "exploded" => Ok(View::Exploded),
+            "badge" => Ok(View::Badge),
+            _ => Err(format!("Unknown view \"{}\"", s)),
+        }
+    }
+}
+
+/// A pattern painted onto the insides of a rocket's wide bodies, like the roll patterns test
+/// rockets were painted with so cameras could see them turn.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Decal {
+    /// Bands of checkerboard, two lines tall
+    Checker,
+    /// A racing stripe down the middle
+    Stripes,
+    /// A band of color round the top of the body
+    NoseBand,
+}
+
+impl FromStr for Decal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "checker" => Ok(Decal::Checker),
+            "stripes" => Ok(Decal::Stripes),
+            "nose-band" => Ok(Decal::NoseBand),
+            _ => Err(format!("Unknown decal \"{}\"", s)),
+        }
+    }
+}
+
+/// A national flag to stamp on a rocket's hull, by its two letter country code.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Flag(&'static str);
+
+impl Flag {
+    pub fn code(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl FromStr for Flag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        flag::FLAGS.iter()
+            .find(|(code, _)| *code == s.to_lowercase())
+            .map(|(code, _)| Flag(code))
+            .ok_or_else(|| {
+                let codes: Vec<&str> = flag::FLAGS.iter().map(|(code, _)| *code).collect();
+                format!("Unknown flag \"{}\", expected one of {}", s, codes.join(", "))
+            })
+    }
+}
+
+/// How many colors the terminal can show, for picking the escape codes colored text is written
+/// with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorDepth {
+    /// Any color, with 24-bit codes
+    TrueColor,
+    /// xterm's 256 colors, each color replaced by the nearest of them or the palette's own choice
+    Ansi256,
+    /// The 16 colors every color terminal has
+    Ansi16,
+}
+
+impl FromStr for ColorDepth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            "16" => Ok(ColorDepth::Ansi16),
+            _ => Err(format!("Unknown color depth \"{}\"", s)),
+        }
+    }
+}
+
+/// Which way the rocket flies across the screen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Orientation {
+    Up,
+    /// Turned a quarter turn anticlockwise, nose to the left
+    Left,
+    /// Turned a quarter turn clockwise, nose to the right
+    Right,
+    /// Flipped upside down, nosediving
+    Down,
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" => Ok(Orientation::Up),
+            "left" => Ok(Orientation::Left),
+            "right" => Ok(Orientation::Right),
+            "down" => Ok(Orientation::Down),
+            _ => Err(format!("Unknown orientation \"{}\"", s)),
+        }
+    }
+}
+
+/// Character encoding for formats that are written as raw bytes rather than UTF-8 text.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Encoding {
+    /// IBM code page 437, what DOS and BBS era viewers expect
+    Cp437,
+    Utf8,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cp437" => Ok(Encoding::Cp437),
+            "utf8" | "utf-8" => Ok(Encoding::Utf8),
+            _ => Err(format!("Unknown encoding \"{}\"", s)),
+        }
+    }
+}
+
+/// How a rocket should be written out.
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub format: Format,
+    pub renderer: Renderer,
+    pub view: View,
+    pub palette: Palette,
+    pub encoding: Encoding,
+    /// Whether text output should be colored with ANSI escape codes
+    pub color: bool,
+    /// Colors the terminal text output goes to can show
+    pub depth: ColorDepth,
+    /// Magnification for pixel based output, each renderer picks its own default when unset
+    pub scale: Option<usize>,
+    /// Whether to add the rocket's [`Stats`] under text output, or to JSON output
+    pub stats: bool,
+    /// Pattern to paint onto the body, in background colors
+    pub decal: Option<Decal>,
+    /// Flag to stamp on the body's first wide section with room for it
+    pub flag: Option<Flag>,
+    /// Colors to blend the body's lines from, top to bottom, instead of the palette's body color
+    pub paint: Option<Gradient>,
+    /// Columns to lean the rocket's nose over by, to the right if positive, see [`wind::shear`]
+    pub wind: f32,
+    /// Which way the nose points, see [`orientation::apply`]
+    pub orientation: Orientation,
+    /// Whether to name the rocket and its seed above markdown output
+    pub title: bool,
+    /// Whether plain text output keeps the spaces at the ends of its lines, so every line is as
+    /// wide as the others
+    pub pad: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            format: Format::Text,
+            renderer: Renderer::Glyphs,
+            view: View::Assembled,
+            palette: Palette::America,
+            encoding: Encoding::Cp437,
+            color: false,
+            depth: ColorDepth::TrueColor,
+            scale: None,
+            stats: false,
+            decal: None,
+            flag: None,
+            paint: None,
+            wind: 0.0,
+            orientation: Orientation::Up,
+            title: false,
+            pad: false,
+        }
+    }
+}
+
+impl Rocket {
+    /// Builds the rocket for `height` and `seed` and renders it as `options` ask, in one call for
+    /// tests and bindings.
+    ///
+    /// The result is stable in the same way as [`Rocket::with_seed`]: the same arguments give the
+    /// same string on every platform and across releases until the next major version, so it can
+    /// be kept as a golden snapshot. The one exception is `ans` output, whose SAUCE record holds
+    /// the date it was made. Fails if `height` is below 3 or the format isn't text, like `png`.
+    pub fn generate_with(height: usize, seed: u64, options: &Options) -> Result<String, Box<dyn Error>> {
+        let mut out = Vec::new();
+        write(&Rocket::build_with(height, seed, &BuildOptions::default())?, options, &mut out)?;
+        String::from_utf8(out).map_err(|_| format!("{} output isn't text", options.format).into())
+    }
+}
+
+/// Writes `rocket` to `out` in the requested format.
+pub fn write(rocket: &Rocket, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    write_grid(&draw(rocket, options)?, rocket, options, out)
+}
+
+/// Draws `rocket` onto a grid with the requested renderer.
+pub fn draw(rocket: &Rocket, options: &Options) -> Result<Grid, Box<dyn Error>> {
+    if options.scale == Some(0) {
+        return Err("Scale must be at least 1".into());
+    }
+    if options.view == View::Exploded {
+        if options.renderer != Renderer::Glyphs {
+            return Err("The exploded view is only drawn with glyphs".into());
+        }
+        return Ok(exploded::render(rocket));
+    }
+    let mut grid = rocket.to_grid();
+    if let Some(decal) = options.decal {
+        decal::apply(&mut grid, rocket, decal, &options.palette);
+    }
+    if let Some(flag) = options.flag {
+        flag::stamp(&mut grid, rocket, flag);
+    }
+    if options.wind != 0.0 {
+        grid = wind::shear(&grid, options.wind);
+    }
+    if options.orientation != Orientation::Up {
+        grid = orientation::apply(&grid, options.orientation);
+    }
+    if options.view == View::Badge {
+        grid = badge::frame(&grid, rocket, options);
+    }
+    Ok(match options.renderer {
+        // Rasterized as it's written, see `write_grid`
+        Renderer::Glyphs | Renderer::Sixel | Renderer::Kitty | Renderer::Iterm => grid,
+        Renderer::Braille => braille::render(&grid, &options.palette, options.scale.unwrap_or(1)),
+        Renderer::Blocks => blocks::render(&grid, &options.palette, options.scale.unwrap_or(1)),
+    })
+}
+
+/// The row of `grid`, drawn from `rocket` by [`draw`], that the rocket stands on, see
+/// [`Rocket::ground`]. Upside down rockets hang from theirs, and rockets turned on their side,
+/// exploded or framed as badges stand on their last row.
+pub fn ground(rocket: &Rocket, grid: &Grid, options: &Options) -> usize {
+    let bottom = grid.height().saturating_sub(1);
+    if matches!(options.view, View::Exploded | View::Badge) {
+        return bottom;
+    }
+    let lines: usize = rocket.sections().iter().map(|p| p.height()).sum();
+    // Braille and blocks draw each line as more or less than one row
+    let row = ((rocket.ground() + 1) * grid.height()).div_ceil(lines.max(1)).saturating_sub(1).min(bottom);
+    match options.orientation {
+        Orientation::Up => row,
+        Orientation::Down => bottom - row,
+        Orientation::Left | Orientation::Right => bottom,
+    }
+}
+
+/// Writes an already drawn grid to `out` in the requested format. `rocket` is the rocket it was
+/// drawn from, for formats that record how it was made.
+pub fn write_grid(grid: &Grid, rocket: &Rocket, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let grid = &*painted(grid, options);
+    if options.stats && !matches!(options.format, Format::Text | Format::Json | Format::Markdown) {
+        return Err("Stats are only written with text, json and markdown output".into());
+    }
+    if options.renderer.is_graphics() && options.format != Format::Text {
+        return Err(format!("The {} renderer only writes text output", options.renderer).into());
+    }
+    match options.format {
+        Format::Text => {
+            let scale = options.scale.unwrap_or(1);
+            match options.renderer {
+                Renderer::Sixel => writeln!(out, "{}", sixel::render(grid, &options.palette, scale))?,
+                Renderer::Kitty => writeln!(out, "{}", kitty::render(grid, &options.palette, scale))?,
+                Renderer::Iterm => {
+                    let mut image = Vec::new();
+                    write_png(grid, &Options { scale: Some(scale), ..options.clone() }, &mut image)?;
+                    writeln!(out, "{}", iterm::render(&image))?;
+                }
+                // Padded out to a rectangle, without the blank line after it
+                _ if options.pad => write!(out, "{}", text(grid, options))?,
+                _ => writeln!(out, "{}", text(grid, options))?,
+            }
+            for find in finds(rocket) {
+                writeln!(out, "{}", find)?;
+            }
+            if options.stats {
+                writeln!(out, "{}", Stats::of(rocket))?;
+            }
+        }
+        Format::Png => write_png(grid, options, out)?,
+        Format::Html => out.write_all(html::render(grid, &options.palette).as_bytes())?,
+        Format::Ans => out.write_all(&ans::render(rocket, grid, options))?,
+        Format::Json => writeln!(out, "{}", json::render(rocket, grid, options))?,
+        Format::Gif => write_gif(std::slice::from_ref(grid), 1, false, options, out)?,
+        Format::Cast => cast::write(std::slice::from_ref(grid), 1, options, out)?,
+        Format::Markdown => out.write_all(markdown::render(grid, rocket, options.title, &finds(rocket), options.stats.then(|| Stats::of(rocket))).as_bytes())?,
+    }
+    Ok(())
+}
+
+/// A line for each of the rocket's [`Rocket::finds`], like `✦ Legendary part: Golden Fairing`.
+fn finds(rocket: &Rocket) -> Vec<String> {
+    // Plain ASCII rockets, like chat ones, get a plain ASCII star
+    let star = if rocket.sections().iter().all(|p| p.chat_safe()) { '*' } else { '✦' };
+    rocket.finds().iter()
+        .map(|part| {
+            let rarity = part.rarity().to_string();
+            format!("{} {}{} part: {}", star, rarity[..1].to_uppercase(), &rarity[1..], part.name())
+        })
+        .collect()
+}
+
+/// Writes the frames of an animation to `out`, played at `fps` and, if it `loops`, over and over.
+/// Only gif and cast output hold more than one frame, and casts play once.
+pub fn write_animation(frames: &[Grid], fps: usize, loops: bool, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let frames: Vec<Grid> = frames.iter().map(|frame| painted(frame, options).into_owned()).collect();
+    let frames = &frames[..];
+    match options.format {
+        Format::Gif => write_gif(frames, fps, loops, options, out),
+        Format::Cast => cast::write(frames, fps, options, out),
+        format => Err(format!("Animations can't be written as {}", format).into()),
+    }
+}
+
+/// Renders a grid as lines of text, colored if the options ask for it, and padded out with spaces
+/// to the grid's width if they ask for that too.
+pub fn text(grid: &Grid, options: &Options) -> String {
+    let text = if options.color {
+        terminal::render(&painted(grid, options), &options.palette, options.depth)
+    } else {
+        grid.to_string()
+    };
+    if !options.pad {
+        return text;
+    }
+    // Either way lines stop at their last character, or colored ones at their last background
+    text.lines()
+        .zip(grid.rows())
+        .map(|(line, row)| {
+            let kept = if options.color {
+                row.iter().rposition(|c| !c.is_blank() || c.background.is_some()).map_or(0, |i| i + 1)
+            } else {
+                line.chars().count()
+            };
+            format!("{}{}\n", line, " ".repeat(grid.width() - kept))
+        })
+        .collect()
+}
+
+/// Paints over the colors the grid was drawn in: the body in the options' or palette's gradient, from
+/// its first line to its last, and then everything in the palette's colors for each place, for
+/// palettes that paint by position rather than by part. Without either the grid comes back as it
+/// was.
+fn painted<'a>(grid: &'a Grid, options: &Options) -> Cow<'a, Grid> {
+    let palette = &options.palette;
+    let paint = options.paint.or(palette.paint());
+    if paint.is_none() && palette.wave(0, 0).is_none() {
+        return Cow::Borrowed(grid);
+    }
+    let mut painted = grid.clone();
+    if let Some(gradient) = paint {
+        let body_line = |y: &usize| (0..grid.width()).any(|x| grid.get(x, *y).part_type == Some(PartType::Body));
+        let first = (0..grid.height()).find(body_line).unwrap_or(0);
+        let last = (0..grid.height()).rev().find(body_line).unwrap_or(0);
+        for y in first..=last {
+            let color = gradient.at((y - first) as f64 / (last - first).max(1) as f64);
+            for x in 0..grid.width() {
+                let cell = grid.get(x, y);
+                // Block characters are painted in their background too, but anything else's
+                // background is a decal's
+                let background = match options.renderer {
+                    Renderer::Blocks => cell.background.and(Some(color)),
+                    _ => cell.background,
+                };
+                if cell.part_type == Some(PartType::Body) {
+                    painted.set(x, y, Cell { color: Some(color), background, ..cell });
+                }
+            }
+        }
+    }
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let cell = painted.get(x, y);
+            let Some(color) = palette.wave(x, y) else { continue };
+            if !cell.is_blank() || cell.background.is_some() {
+                painted.set(x, y, Cell { color: Some(color), background: cell.background.and(Some(color)), ..cell });
+            }
+        }
+    }
+    Cow::Owned(painted)
+}
+
+#[cfg(feature = "png")]
+fn write_png(grid: &Grid, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    png::write(grid, &options.palette, options.scale.unwrap_or(2), out)
+}
+
+#[cfg(not(feature = "png"))]
+fn write_png(_grid: &Grid, _options: &Options, _out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    Err("PNG output is not available, rebuild ship_gen with `--features png`".into())
+}
+
+#[cfg(feature = "gif")]
+fn write_gif(frames: &[Grid], fps: usize, loops: bool, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    gif::write(frames, &options.palette, options.scale.unwrap_or(2), fps, loops, out)
+}
+
+#[cfg(not(feature = "gif"))]
+fn write_gif(_frames: &[Grid], _fps: usize, _loops: bool, _options: &Options, _out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    Err("GIF output is not available, rebuild ship_gen with `--features gif`".into())
+}
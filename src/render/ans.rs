@@ -0,0 +1,149 @@
+use crate::date::Date;
+use crate::grid::Grid;
+use crate::palette::Color;
+use crate::quantize;
+use crate::render::style::{self, Token};
+use crate::render::{Encoding, Options};
+use crate::rocket::Rocket;
+
+/// Code page 437 characters 0x80 to 0xFF, the lower half is plain ASCII.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Renders the grid as ANSI escape art followed by a SAUCE record describing how it was made,
+/// the layout BBS-era viewers like PabloDraw and ACiDView expect.
+pub fn render(rocket: &Rocket, grid: &Grid, options: &Options) -> Vec<u8> {
+    let mut art = Vec::new();
+    for row in grid.rows() {
+        for token in style::tokens(row, &options.palette) {
+            match token {
+                Token::Style { fg, bg } => art.extend_from_slice(sgr(fg, bg).as_bytes()),
+                Token::Char(ch) => encode(&mut art, ch, options.encoding),
+            }
+        }
+        art.extend_from_slice(b"\x1b[0m\r\n");
+    }
+
+    let comments: Vec<String> = [
+        Some(format!("Generated by ship_gen {}", env!("CARGO_PKG_VERSION"))),
+        rocket.seed.map(|seed| format!("seed: {}", seed)),
+        Some(format!("height: {}", rocket.max_height)),
+        Some(format!("palette: {}", options.palette)),
+    ].into_iter().flatten().collect();
+    let sauce = Sauce {
+        title: match rocket.seed {
+            Some(seed) => format!("Rocket {}", seed),
+            None => rocket.name(),
+        },
+        file_size: art.len() as u32,
+        width: grid.width() as u16,
+        lines: grid.height() as u16,
+        comments: &comments,
+        font: match options.encoding {
+            Encoding::Cp437 => "IBM VGA",
+            Encoding::Utf8 => "",
+        },
+    };
+    art.push(0x1a);
+    sauce.write(&mut art);
+    art
+}
+
+/// Select Graphic Rendition sequence for the nearest of the 16 colors. Bright foregrounds are
+/// reached with the bold attribute since that's all ANSI.SYS understood, and backgrounds are
+/// limited to the 8 normal colors.
+fn sgr(fg: Option<Color>, bg: Option<Color>) -> String {
+    let mut codes = String::from("\x1b[0");
+    if let Some(fg) = fg.map(quantize::nearest_16) {
+        if fg < 8 {
+            codes.push_str(&format!(";{}", 30 + fg));
+        } else {
+            codes.push_str(&format!(";1;{}", 30 + fg - 8));
+        }
+    }
+    if let Some(bg) = bg.map(quantize::nearest_16) {
+        codes.push_str(&format!(";{}", 40 + bg % 8));
+    }
+    codes.push('m');
+    codes
+}
+
+fn encode(out: &mut Vec<u8>, ch: char, encoding: Encoding) {
+    match encoding {
+        Encoding::Utf8 => {
+            let mut buf = [0; 4];
+            out.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+        }
+        Encoding::Cp437 => out.push(cp437(ch)),
+    }
+}
+
+fn cp437(ch: char) -> u8 {
+    if ch.is_ascii() && !ch.is_ascii_control() {
+        return ch as u8;
+    }
+    match CP437_HIGH.iter().position(|&c| c == ch) {
+        Some(i) => 0x80 + i as u8,
+        None => b'?',
+    }
+}
+
+/// Standard Architecture for Universal Comment Extensions metadata, see
+/// <https://www.acid.org/info/sauce/sauce.htm>.
+struct Sauce<'a> {
+    title: String,
+    file_size: u32,
+    width: u16,
+    lines: u16,
+    comments: &'a [String],
+    font: &'a str,
+}
+
+impl Sauce<'_> {
+    fn write(&self, out: &mut Vec<u8>) {
+        if !self.comments.is_empty() {
+            out.extend_from_slice(b"COMNT");
+            for comment in self.comments {
+                push_field(out, comment, 64);
+            }
+        }
+        out.extend_from_slice(b"SAUCE00");
+        push_field(out, &self.title, 35);
+        push_field(out, "ship_gen", 20);
+        push_field(out, "", 20);
+        push_field(out, &today(), 8);
+        out.extend_from_slice(&self.file_size.to_le_bytes());
+        // DataType Character, FileType ANSi
+        out.extend_from_slice(&[1, 1]);
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.lines.to_le_bytes());
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.push(self.comments.len() as u8);
+        // TFlags: no iCE colors, 8 pixel letter spacing, legacy aspect ratio
+        out.push(0b0000_1010);
+        let mut font = self.font.as_bytes().to_vec();
+        font.resize(22, 0);
+        out.extend_from_slice(&font);
+    }
+}
+
+/// Writes `value` as a fixed width, space padded SAUCE character field.
+fn push_field(out: &mut Vec<u8>, value: &str, width: usize) {
+    let mut field: Vec<u8> = value.chars().take(width).map(cp437).collect();
+    field.resize(width, b' ');
+    out.extend_from_slice(&field);
+}
+
+/// Current UTC date as SAUCE's CCYYMMDD.
+fn today() -> String {
+    let date = Date::today();
+    format!("{:04}{:02}{:02}", date.year, date.month, date.day)
+}
@@ -0,0 +1,42 @@
+use crate::grid::{Cell, Grid};
+use crate::rocket::{self, Rocket};
+
+use super::{wind, Options, Orientation};
+
+/// Puts the rocket in the middle of a frame that's the same size for every rocket as tall, drawn
+/// the same way, so a row of them lines up like icons. Rockets wider than the widest a rocket that
+/// tall can be, built from packs with wider parts, are cut off at the frame.
+pub fn frame(grid: &Grid, rocket: &Rocket, options: &Options) -> Grid {
+    // The widest hull a rocket that tall grows to, its walls and a fin either side, and room to
+    // lean over either way in the wind
+    let lean = wind::clamp(options.wind, rocket.max_height).abs().ceil() as usize;
+    let width = rocket::hull_width_for(rocket.max_height) + 6 + 2 * lean;
+    let (width, height) = match options.orientation {
+        Orientation::Up | Orientation::Down => (width, rocket.max_height),
+        Orientation::Left | Orientation::Right => (rocket.max_height, width),
+    };
+    // Plain ASCII rockets, like chat ones, get a plain ASCII frame
+    let [top_left, top_right, bottom_left, bottom_right, across, down] = if rocket.sections().iter().all(|p| p.chat_safe()) {
+        ['+', '+', '+', '+', '-', '|']
+    } else {
+        ['╭', '╮', '╰', '╯', '─', '│']
+    };
+    let mut badge = Grid::new(width + 4, height + 2);
+    let mut put = |x: usize, y: usize, ch: char| badge.set(x, y, Cell { ch, ..Cell::BLANK });
+    put(0, 0, top_left);
+    put(width + 3, 0, top_right);
+    put(0, height + 1, bottom_left);
+    put(width + 3, height + 1, bottom_right);
+    for x in 1..width + 3 {
+        put(x, 0, across);
+        put(x, height + 1, across);
+    }
+    for y in 1..height + 1 {
+        put(0, y, down);
+        put(width + 3, y, down);
+    }
+    let mut inside = Grid::new(width, height);
+    inside.overlay((width as isize - grid.width() as isize) / 2, (height as isize - grid.height() as isize) / 2, grid);
+    badge.draw(2, 1, &inside);
+    badge
+}
@@ -0,0 +1,41 @@
+use crate::grid::{Cell, Grid};
+use crate::palette::{Color, Palette};
+use crate::raster::{Raster, CELL_HEIGHT, CELL_WIDTH};
+
+/// Redraws the grid as solid pixels using half block characters, two pixels stacked in every
+/// character and `scale` by `scale` characters for every original cell. Inside a part's hull,
+/// pixels the part's glyphs pass through keep the part's color and the rest are filled with the
+/// palette's fill color, so the rocket comes out as a painted silhouette rather than line art.
+pub fn render(grid: &Grid, palette: &Palette, scale: usize) -> Grid {
+    let raster = Raster::from_grid(grid, palette, scale);
+    // Raster pixels covered by a single block pixel
+    let pixel_width = CELL_WIDTH;
+    let pixel_height = CELL_HEIGHT / 2;
+
+    let pixel = |x: usize, y: usize| -> Option<Color> {
+        let source = grid.get(x / scale, y / (scale * 2));
+        let fill = source.part_type.map(|t| palette.fill(t));
+        let (left, top) = (x * pixel_width, y * pixel_height);
+        (top..top + pixel_height)
+            .flat_map(|py| (left..left + pixel_width).map(move |px| (px, py)))
+            .find_map(|(px, py)| raster.get(px, py))
+            .or(fill)
+    };
+
+    let mut blocks = Grid::new(grid.width() * scale, grid.height() * scale);
+    for y in 0..blocks.height() {
+        for x in 0..blocks.width() {
+            let (top, bottom) = (pixel(x, y * 2), pixel(x, y * 2 + 1));
+            let (ch, color, background) = match (top, bottom) {
+                (None, None) => continue,
+                (Some(t), Some(b)) if t == b => ('█', t, None),
+                (Some(t), None) => ('▀', t, None),
+                (None, Some(b)) => ('▄', b, None),
+                (Some(t), Some(b)) => ('▀', t, Some(b)),
+            };
+            let source = grid.get(x / scale, y / scale);
+            blocks.set(x, y, Cell { ch, color: Some(color), background, ..source });
+        }
+    }
+    blocks
+}
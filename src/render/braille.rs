@@ -0,0 +1,47 @@
+use crate::grid::{Cell, Grid};
+use crate::palette::Palette;
+use crate::raster::{Raster, CELL_HEIGHT, CELL_WIDTH};
+
+/// Dot bit for each (column, row) of a Braille cell, see the Unicode Braille Patterns block.
+const DOTS: [[u8; 4]; 2] = [
+    [0x01, 0x02, 0x04, 0x40],
+    [0x08, 0x10, 0x20, 0x80],
+];
+const DOT_COLUMNS: usize = 2;
+const DOT_ROWS: usize = 4;
+
+/// Rasterizes the grid and samples it back down into Braille characters, `scale` by `scale`
+/// of them for every original cell. At higher scales diagonals and curves come out much smoother
+/// than the box drawing glyphs they started as.
+pub fn render(grid: &Grid, palette: &Palette, scale: usize) -> Grid {
+    let raster = Raster::from_grid(grid, palette, scale);
+    // Raster pixels covered by a single dot
+    let dot_width = CELL_WIDTH / DOT_COLUMNS;
+    let dot_height = CELL_HEIGHT / DOT_ROWS;
+
+    let mut braille = Grid::new(grid.width() * scale, grid.height() * scale);
+    for y in 0..braille.height() {
+        for x in 0..braille.width() {
+            let mut bits = 0;
+            for (column, column_bits) in DOTS.iter().enumerate() {
+                for (row, bit) in column_bits.iter().enumerate() {
+                    let left = (x * DOT_COLUMNS + column) * dot_width;
+                    let top = (y * DOT_ROWS + row) * dot_height;
+                    let lit = (top..top + dot_height)
+                        .any(|py| (left..left + dot_width).any(|px| raster.get(px, py).is_some()));
+                    if lit {
+                        bits |= bit;
+                    }
+                }
+            }
+            if bits != 0 {
+                let source = grid.get(x / scale, y / scale);
+                braille.set(x, y, Cell {
+                    ch: char::from_u32(0x2800 + bits as u32).unwrap(),
+                    ..source
+                });
+            }
+        }
+    }
+    braille
+}
@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::io::Write;
+
+use super::{text, Options};
+use crate::grid::Grid;
+use crate::json::Value;
+
+/// Writes the frames as an asciinema v2 recording, one output event for each frame `fps` of them
+/// a second, drawn over the last like `--animate` draws them in the terminal.
+pub fn write(frames: &[Grid], fps: usize, options: &Options, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let width = frames.iter().map(|frame| frame.width()).max().unwrap_or(0);
+    let height = frames.iter().map(|frame| frame.height()).max().unwrap_or(0);
+    let header = Value::object([
+        ("version", 2.into()),
+        ("width", width.into()),
+        ("height", height.into()),
+        ("env", Value::object([("TERM", "xterm-256color".into())])),
+    ]);
+    writeln!(out, "{}", header)?;
+
+    let time = |i: usize| Value::Number((i as f64 / fps as f64 * 1e6).round() / 1e6);
+    for (i, frame) in frames.iter().enumerate() {
+        // The first frame hides the cursor, and every one homes it and clears what the last left.
+        // Nothing turns newlines into carriage returns as well on playback, so they're written out,
+        // all but the last, which would scroll a frame as tall as the recording up a line
+        let hide = if i == 0 { "\x1b[?25l" } else { "" };
+        let lines = text(frame, options);
+        let art = format!("{}\x1b[H{}\x1b[K\x1b[J", hide, lines.strip_suffix('\n').unwrap_or(&lines).replace('\n', "\x1b[K\r\n"));
+        writeln!(out, "{}", Value::Array(vec![time(i), "o".into(), art.into()]))?;
+    }
+    // An empty event keeps the last frame up for as long as the others
+    writeln!(out, "{}", Value::Array(vec![time(frames.len()), "o".into(), "".into()]))?;
+    Ok(())
+}
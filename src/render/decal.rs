@@ -0,0 +1,69 @@
+use std::ops::Range;
+
+use crate::grid::{Cell, Grid};
+use crate::palette::{Color, Palette};
+use crate::render::Decal;
+use crate::rocket::{PartType, Rocket};
+
+/// Bodies narrower than this inside have no room for a pattern
+const MIN_WIDTH: usize = 3;
+
+/// One line of a hull's insides: the line, and the columns between its walls.
+pub(super) type Inside = (usize, Range<usize>);
+
+/// The insides of the rocket's wide, straight bodies as they're laid out on its grid, in runs of
+/// lines one under the other of the same width.
+pub(super) fn insides(rocket: &Rocket) -> Vec<Vec<Inside>> {
+    let sections = rocket.sections();
+    let rocket_width = sections.iter().flat_map(|p| p.shape().lines()).map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut runs: Vec<Vec<Inside>> = Vec::new();
+    let mut y = 0;
+    let mut previous = None;
+    for part in sections {
+        let width = part.top_width();
+        let straight = part.part_type() == PartType::Body && width >= MIN_WIDTH && width == part.bottom_width();
+        for line in part.shape().lines() {
+            if straight {
+                // Lines are centered the same way as `Rocket::to_grid` centers them
+                let length = line.chars().count();
+                let start = (rocket_width - length).div_ceil(2) + (length - width) / 2;
+                if previous != Some(width) {
+                    runs.push(Vec::new());
+                }
+                runs.last_mut().expect("a run was just started").push((y, start..start + width));
+            }
+            previous = straight.then_some(width);
+            y += 1;
+        }
+    }
+    runs
+}
+
+/// Paints `decal` onto the insides of the rocket's wide bodies, as background colors so the
+/// portholes and markings drawn there still show.
+pub(super) fn apply(grid: &mut Grid, rocket: &Rocket, decal: Decal, palette: &Palette) {
+    let accent = palette.text();
+    let (dark, light) = (palette.background(), palette.fill(PartType::Body));
+    for (run, lines) in insides(rocket).iter().enumerate() {
+        for (line, (y, columns)) in lines.iter().enumerate() {
+            for x in columns.clone() {
+                let column = x - columns.start;
+                let color = match decal {
+                    // Bands two lines tall with two plain lines between them
+                    Decal::Checker if line % 4 < 2 => Some(if (column + line) % 2 == 0 { dark } else { light }),
+                    Decal::Checker => None,
+                    // One column down the middle, or two if there's no middle column
+                    Decal::Stripes => Some(accent).filter(|_| (2 * column + 1).abs_diff(columns.len()) <= 1),
+                    Decal::NoseBand => Some(accent).filter(|_| run == 0 && line < 2),
+                };
+                paint(grid, x, *y, color);
+            }
+        }
+    }
+}
+
+fn paint(grid: &mut Grid, x: usize, y: usize, color: Option<Color>) {
+    if color.is_some() {
+        grid.set(x, y, Cell { background: color, ..grid.get(x, y) });
+    }
+}
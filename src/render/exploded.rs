@@ -0,0 +1,43 @@
+use std::cmp::max;
+
+use crate::grid::{Cell, Grid};
+use crate::rocket::Rocket;
+
+/// Columns between the widest part and the labels
+const GUTTER: usize = 3;
+
+/// Lays the sections out a line apart, each labelled with its id and name, its kind, widths and
+/// height, to show how a rocket was put together.
+pub fn render(rocket: &Rocket) -> Grid {
+    let sections = rocket.sections();
+    let id_width = sections.iter().map(|part| part.id().chars().count()).max().unwrap_or(0);
+    let labels: Vec<String> = sections.iter()
+        .map(|part| {
+            let lines = if part.height() == 1 { "line" } else { "lines" };
+            format!("{:<width$}  {}, {} {}→{}, {} {}", part.id(), part.name(), part.part_type(), part.top_width(),
+                    part.bottom_width(), part.height(), lines, width = id_width)
+        })
+        .collect();
+    let rocket_width = sections.iter()
+        .flat_map(|part| part.shape().lines())
+        .fold(0, |a, line| max(a, line.chars().count()));
+    let label_width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+    let height = sections.iter().map(|part| part.height()).sum::<usize>() + sections.len().saturating_sub(1);
+
+    let mut grid = Grid::new(rocket_width + GUTTER + label_width, height);
+    let mut y = 0;
+    for (part, label) in sections.iter().zip(&labels) {
+        for (x, ch) in label.chars().enumerate() {
+            grid.set(rocket_width + GUTTER + x, y, Cell { ch, ..Cell::BLANK });
+        }
+        for line in part.shape().lines() {
+            let spacing = (rocket_width - line.chars().count()).div_ceil(2);
+            for (x, ch) in line.chars().enumerate() {
+                grid.set(spacing + x, y, Cell { ch, part_type: Some(part.part_type()), ..Cell::BLANK });
+            }
+            y += 1;
+        }
+        y += 1;
+    }
+    grid
+}
@@ -0,0 +1,57 @@
+use crate::grid::{Cell, Grid};
+use crate::palette::Color;
+use crate::render::decal;
+use crate::render::Flag;
+use crate::rocket::Rocket;
+
+/// Each flag as rows of pixels, two to a line of half blocks, in these colors:
+/// black, white, red, orange, yellow, green, blue and navy
+pub(super) const FLAGS: [(&str, &[&str]); 13] = [
+    ("us", &["NNR", "NNW", "RRR", "WWW"]),
+    ("ca", &["RWR", "RRR", "RRR", "RWR"]),
+    ("fr", &["BWR", "BWR", "BWR", "BWR"]),
+    ("it", &["GWR", "GWR", "GWR", "GWR"]),
+    ("ie", &["GWO", "GWO", "GWO", "GWO"]),
+    ("be", &["KYR", "KYR", "KYR", "KYR"]),
+    ("de", &["KKK", "KKK", "RRR", "RRR", "YYY", "YYY"]),
+    ("nl", &["RRR", "RRR", "WWW", "WWW", "NNN", "NNN"]),
+    ("ru", &["WWW", "WWW", "BBB", "BBB", "RRR", "RRR"]),
+    ("in", &["OOO", "OOO", "WNW", "WWW", "GGG", "GGG"]),
+    ("ua", &["BBB", "BBB", "YYY", "YYY"]),
+    ("jp", &["WWW", "WRW", "WRW", "WWW"]),
+    ("cn", &["YRR", "RRR", "RRR", "RRR"]),
+];
+
+fn color(code: char) -> Color {
+    match code {
+        'K' => Color::rgb(0x00, 0x00, 0x00),
+        'W' => Color::rgb(0xff, 0xff, 0xff),
+        'R' => Color::rgb(0xd8, 0x1e, 0x2c),
+        'O' => Color::rgb(0xff, 0x99, 0x33),
+        'Y' => Color::rgb(0xff, 0xcc, 0x00),
+        'G' => Color::rgb(0x00, 0x8c, 0x45),
+        'B' => Color::rgb(0x00, 0x55, 0xa4),
+        _ => Color::rgb(0x0a, 0x31, 0x61),
+    }
+}
+
+/// Stamps the flag onto the first of the rocket's wide, straight bodies with room for it, centered
+/// and starting at its top line. Rockets without one are left as they are.
+pub(super) fn stamp(grid: &mut Grid, rocket: &Rocket, flag: Flag) {
+    let rows = FLAGS.iter().find(|(code, _)| *code == flag.code()).map_or(&[][..], |(_, rows)| rows);
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let lines = rows.len().div_ceil(2);
+    let Some(run) = decal::insides(rocket).into_iter().find(|run| run.len() >= lines && run[0].1.len() >= width) else {
+        return;
+    };
+    for (line, (y, columns)) in run.iter().take(lines).enumerate() {
+        let left = columns.start + (columns.len() - width) / 2;
+        for x in 0..width {
+            let pixel = |row: usize| rows.get(row).and_then(|row| row.chars().nth(x)).map(color);
+            let (top, bottom) = (pixel(2 * line), pixel(2 * line + 1));
+            let (ch, background) = if top == bottom { ('█', None) } else { ('▀', bottom) };
+            // Not part of the body any more, so nothing paints over it
+            grid.set(left + x, *y, Cell { ch, part_type: None, color: top, background });
+        }
+    }
+}
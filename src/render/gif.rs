@@ -0,0 +1,57 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::io::Write;
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::grid::Grid;
+use crate::palette::{Color, Palette};
+use crate::raster::Raster;
+
+/// Encodes the frames as an animated GIF, `scale` image pixels per font pixel, showing `fps` of
+/// them a second and playing them forever if `loops`, or once otherwise.
+pub fn write(frames: &[Grid], palette: &Palette, scale: usize, fps: usize, loops: bool, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let rasters: Vec<Raster> = frames.iter().map(|frame| Raster::from_grid(frame, palette, scale)).collect();
+    let width = rasters.iter().map(|r| r.width()).max().unwrap_or(0);
+    let height = rasters.iter().map(|r| r.height()).max().unwrap_or(0);
+    if width > u16::MAX as usize || height > u16::MAX as usize {
+        return Err(format!("The animation is {}x{} pixels, too big for a GIF", width, height).into());
+    }
+
+    // Rockets use a handful of colors, so every frame can share one palette
+    let background = palette.background();
+    let mut colors = vec![background];
+    let mut indexed = Vec::with_capacity(rasters.len());
+    for raster in &rasters {
+        let mut pixels = vec![0; width * height];
+        for y in 0..raster.height() {
+            for x in 0..raster.width() {
+                let color = raster.get(x, y).unwrap_or(background);
+                let index = colors.iter().position(|&c| c == color).unwrap_or_else(|| {
+                    colors.push(color);
+                    colors.len() - 1
+                });
+                pixels[y * width + x] = u8::try_from(index).map_err(|_| "The animation uses more colors than a GIF can hold")?;
+            }
+        }
+        indexed.push(pixels);
+    }
+    let global: Vec<u8> = colors.iter().flat_map(|c: &Color| [c.r, c.g, c.b]).collect();
+
+    let mut encoder = Encoder::new(out, width as u16, height as u16, &global)?;
+    if loops {
+        encoder.set_repeat(Repeat::Infinite)?;
+    }
+    for (i, pixels) in indexed.into_iter().enumerate() {
+        // Delays are in hundredths of a second, rounded so they add up to the right length
+        let delay = ((i + 1) * 100 / fps) - (i * 100 / fps);
+        encoder.write_frame(&Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: delay as u16,
+            buffer: Cow::Owned(pixels),
+            ..Frame::default()
+        })?;
+    }
+    Ok(())
+}
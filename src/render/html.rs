@@ -0,0 +1,56 @@
+use std::fmt::Write as _;
+
+use crate::grid::Grid;
+use crate::palette::{Color, Palette};
+use crate::render::style::{self, Token};
+
+/// Renders the grid as a `<pre>` block, wrapping each run of same-colored characters in a
+/// `<span>` with an inline color so the markup can be pasted into any page without a stylesheet.
+pub fn render(grid: &Grid, palette: &Palette) -> String {
+    let mut html = format!("<pre style=\"background-color: {}\">", hex(palette.background()));
+    for row in grid.rows() {
+        let mut open = false;
+        for token in style::tokens(row, palette) {
+            match token {
+                Token::Style { fg, bg } => {
+                    if open {
+                        html.push_str("</span>");
+                    }
+                    open = fg.is_some() || bg.is_some();
+                    if open {
+                        html.push_str("<span style=\"");
+                        if let Some(c) = fg {
+                            write!(html, "color: {};", hex(c)).unwrap();
+                        }
+                        if let Some(c) = bg {
+                            write!(html, "background-color: {};", hex(c)).unwrap();
+                        }
+                        html.push_str("\">");
+                    }
+                }
+                Token::Char(ch) => push_escaped(&mut html, ch),
+            }
+        }
+        if open {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+    }
+    html.push_str("</pre>\n");
+    html
+}
+
+fn hex(c: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+fn push_escaped(html: &mut String, ch: char) {
+    match ch {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '"' => html.push_str("&quot;"),
+        '\'' => html.push_str("&#39;"),
+        _ => html.push(ch),
+    }
+}
@@ -0,0 +1,7 @@
+use crate::render::base64;
+
+/// Wraps an image file, a PNG here, in iTerm2's inline image escape sequence, to be shown at its
+/// own size where the cursor is.
+pub fn render(image: &[u8]) -> String {
+    format!("\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{}\x07", image.len(), base64::encode(image))
+}
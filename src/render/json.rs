@@ -0,0 +1,37 @@
+use crate::grid::Grid;
+use crate::json::Value;
+use crate::render::Options;
+use crate::rocket::Rocket;
+use crate::stats::Stats;
+
+/// Describes the rocket for programs rather than people: how it was made, the parts it's built
+/// from, and the drawn art line by line, with its stats if the options ask for them.
+pub fn render(rocket: &Rocket, grid: &Grid, options: &Options) -> Value {
+    let sections: Vec<Value> = rocket.sections().iter()
+        .map(|part| Value::object([
+            ("id", part.id().into()),
+            ("name", part.name().into()),
+            ("type", part.part_type().to_string().into()),
+            ("shape", part.shape().into()),
+            ("height", part.height().into()),
+            ("top_width", part.top_width().into()),
+            ("bottom_width", part.bottom_width().into()),
+            ("rarity", part.rarity().to_string().into()),
+            ("feature", part.feature().map_or(Value::Null, |feature| feature.to_string().into())),
+        ]))
+        .collect();
+    let lines: Vec<String> = grid.to_string().lines().map(String::from).collect();
+    let mut entries = vec![
+        // Seeds use the full u64 range, more than a JSON number can be trusted to hold
+        ("seed", rocket.seed.map_or(Value::Null, |seed| Value::String(seed.to_string()))),
+        ("height", rocket.max_height.into()),
+        ("palette", options.palette.to_string().into()),
+        ("width", grid.width().into()),
+        ("sections", Value::Array(sections)),
+        ("lines", lines.into()),
+    ];
+    if options.stats {
+        entries.push(("stats", Stats::of(rocket).to_json()));
+    }
+    Value::object(entries)
+}
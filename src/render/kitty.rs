@@ -0,0 +1,36 @@
+use crate::grid::Grid;
+use crate::palette::Palette;
+use crate::raster::Raster;
+use crate::render::base64;
+
+/// Base64 characters sent in each escape sequence, the most the protocol allows
+const CHUNK: usize = 4096;
+
+/// Rasterizes the grid, `scale` pixels per font pixel, and encodes it for the kitty terminal
+/// graphics protocol as RGBA, so pixels the rocket doesn't cover are transparent.
+pub fn render(grid: &Grid, palette: &Palette, scale: usize) -> String {
+    let raster = Raster::from_grid(grid, palette, scale);
+    let mut rgba = Vec::with_capacity(raster.width() * raster.height() * 4);
+    for y in 0..raster.height() {
+        for x in 0..raster.width() {
+            rgba.extend(match raster.get(x, y) {
+                Some(c) => [c.r, c.g, c.b, 255],
+                None => [0; 4],
+            });
+        }
+    }
+
+    let data = base64::encode(&rgba);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK).collect();
+    let mut out = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        // Only the first chunk says what the image is, the rest just whether more follow
+        let more = (i + 1 < chunks.len()) as u8;
+        let keys = match i {
+            0 => format!("a=T,f=32,s={},v={},q=2,m={}", raster.width(), raster.height(), more),
+            _ => format!("m={}", more),
+        };
+        out.push_str(&format!("\x1b_G{};{}\x1b\\", keys, String::from_utf8_lossy(chunk)));
+    }
+    out
+}
@@ -0,0 +1,38 @@
+use crate::grid::Grid;
+use crate::rocket::Rocket;
+use crate::stats::Stats;
+
+/// Renders the grid as a fenced code block, ready to paste into a pull request or an issue, with
+/// a line naming the rocket and its seed above it if `title` is set, and a list of its `finds`
+/// and a table of its `stats` under it. Nothing inside the fence needs escaping, but a fence can't
+/// hold a run of backticks as long as its own, so it's made longer than any in the art.
+pub fn render(grid: &Grid, rocket: &Rocket, title: bool, finds: &[String], stats: Option<Stats>) -> String {
+    let art = grid.to_string();
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in art.chars() {
+        run = if ch == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat((longest + 1).max(3));
+    let mut markdown = String::new();
+    if title {
+        markdown.push_str(&format!("**{}**", rocket.name()));
+        if let Some(seed) = rocket.seed {
+            markdown.push_str(&format!(" (seed {})", seed));
+        }
+        markdown.push_str("\n\n");
+    }
+    markdown.push_str(&format!("{}text\n{}\n{}\n", fence, art.trim_end_matches('\n'), fence));
+    if !finds.is_empty() {
+        markdown.push('\n');
+        for find in finds {
+            markdown.push_str(&format!("- {}\n", find));
+        }
+    }
+    if let Some(stats) = stats {
+        markdown.push('\n');
+        markdown.push_str(&stats.to_markdown());
+    }
+    markdown
+}
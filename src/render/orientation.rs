@@ -0,0 +1,73 @@
+use crate::grid::{Cell, Grid};
+
+use super::Orientation;
+
+/// Glyphs and what they look like turned a quarter turn clockwise. Anything not listed, like `O`
+/// or `°`, looks the same either way. Going round a quarter turn anticlockwise is the same as
+/// three clockwise.
+const CLOCKWISE: [(char, char); 39] = [
+    ('│', '─'), ('─', '│'), ('║', '═'), ('═', '║'), ('|', '-'), ('-', '|'), ('_', '|'), ('=', '║'),
+    ('/', '\\'), ('\\', '/'), ('╱', '╲'), ('╲', '╱'),
+    ('┌', '┐'), ('┐', '┘'), ('┘', '└'), ('└', '┌'),
+    ('┴', '├'), ('├', '┬'), ('┬', '┤'), ('┤', '┴'),
+    ('╩', '╠'), ('╠', '╦'), ('╦', '╣'), ('╣', '╩'),
+    ('╔', '╗'), ('╗', '╝'), ('╝', '╚'), ('╚', '╔'),
+    ('(', '^'), ('^', '>'), ('>', 'v'), ('v', '<'), ('<', '^'), (')', 'v'),
+    ('▀', '▐'), ('▐', '▄'), ('▄', '▌'), ('▌', '▀'),
+    ('\'', '-'),
+];
+
+/// Glyphs that turn into each other when flipped upside down.
+const UPSIDE_DOWN: [(char, char); 12] = [
+    ('/', '\\'), ('╱', '╲'), ('┌', '└'), ('┐', '┘'), ('┬', '┴'), ('╦', '╩'), ('╔', '╚'), ('╗', '╝'),
+    ('▀', '▄'), ('^', 'v'), ('\'', '.'), ('_', '¯'),
+];
+
+fn clockwise(ch: char) -> char {
+    CLOCKWISE.iter().find(|&&(from, _)| from == ch).map_or(ch, |&(_, to)| to)
+}
+
+fn upside_down(ch: char) -> char {
+    UPSIDE_DOWN.iter()
+        .find_map(|&(a, b)| if ch == a { Some(b) } else if ch == b { Some(a) } else { None })
+        .unwrap_or(ch)
+}
+
+/// Turns the rocket to fly `orientation`, swapping glyphs for ones that point the new way.
+/// Sideways rockets are as wide as they were tall, and only as tall as they were wide, and
+/// upside down ones trail their exhaust up above them.
+pub fn apply(grid: &Grid, orientation: Orientation) -> Grid {
+    let turn = |cell: Cell, turns: usize| Cell { ch: (0..turns).fold(cell.ch, |ch, _| clockwise(ch)), ..cell };
+    let (width, height) = (grid.width(), grid.height());
+    match orientation {
+        Orientation::Up => grid.clone(),
+        Orientation::Right => {
+            let mut turned = Grid::new(height, width);
+            for y in 0..height {
+                for x in 0..width {
+                    turned.set(height - 1 - y, x, turn(grid.get(x, y), 1));
+                }
+            }
+            turned
+        }
+        Orientation::Left => {
+            let mut turned = Grid::new(height, width);
+            for y in 0..height {
+                for x in 0..width {
+                    turned.set(y, width - 1 - x, turn(grid.get(x, y), 3));
+                }
+            }
+            turned
+        }
+        Orientation::Down => {
+            let mut flipped = Grid::new(width, height);
+            for y in 0..height {
+                for x in 0..width {
+                    let cell = grid.get(x, y);
+                    flipped.set(x, height - 1 - y, Cell { ch: upside_down(cell.ch), ..cell });
+                }
+            }
+            flipped
+        }
+    }
+}
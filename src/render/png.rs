@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::grid::Grid;
+use crate::palette::Palette;
+use crate::raster::Raster;
+
+/// Encodes the grid as an RGB image, `scale` image pixels per font pixel.
+pub fn write(grid: &Grid, palette: &Palette, scale: usize, out: &mut dyn Write) -> Result<(), Box<dyn Error>> {
+    let raster = Raster::from_grid(grid, palette, scale);
+    let mut encoder = png::Encoder::new(out, raster.width() as u32, raster.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&raster.to_rgb(palette.background()))?;
+    writer.finish()?;
+    Ok(())
+}
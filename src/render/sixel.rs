@@ -0,0 +1,53 @@
+use std::fmt::Write;
+
+use crate::grid::Grid;
+use crate::palette::{Color, Palette};
+use crate::raster::Raster;
+
+/// Rasterizes the grid, `scale` pixels per font pixel, and encodes it as a DEC sixel image for
+/// terminals that can show one. Pixels the rocket doesn't cover are left transparent, so the
+/// terminal's own background shows through.
+pub fn render(grid: &Grid, palette: &Palette, scale: usize) -> String {
+    let raster = Raster::from_grid(grid, palette, scale);
+    let mut colors: Vec<Color> = Vec::new();
+    for y in 0..raster.height() {
+        for x in 0..raster.width() {
+            if let Some(color) = raster.get(x, y).filter(|c| !colors.contains(c)) {
+                colors.push(color);
+            }
+        }
+    }
+
+    // Transparent background, square pixels and the image's size, then a register per color
+    let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", raster.width(), raster.height());
+    let percent = |value: u8| (value as usize * 100 + 127) / 255;
+    for (i, color) in colors.iter().enumerate() {
+        write!(out, "#{};2;{};{};{}", i, percent(color.r), percent(color.g), percent(color.b)).unwrap();
+    }
+
+    // Each band is six pixel rows, painted once per color that appears in it
+    for band in (0..raster.height()).step_by(6) {
+        let rows = band..(band + 6).min(raster.height());
+        for (i, &color) in colors.iter().enumerate() {
+            let sixels: Vec<u8> = (0..raster.width())
+                .map(|x| rows.clone().fold(0, |bits, y| bits | ((raster.get(x, y) == Some(color)) as u8) << (y - band)))
+                .collect();
+            if sixels.iter().all(|&bits| bits == 0) {
+                continue;
+            }
+            write!(out, "#{}", i).unwrap();
+            for run in sixels.chunk_by(|a, b| a == b) {
+                let ch = (63 + run[0]) as char;
+                match run.len() {
+                    1..=3 => out.extend(std::iter::repeat_n(ch, run.len())),
+                    n => write!(out, "!{}{}", n, ch).unwrap(),
+                }
+            }
+            // Back to the start of the band for the next color
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
@@ -0,0 +1,31 @@
+use crate::grid::Cell;
+use crate::palette::{Color, Palette};
+
+/// A row of cells flattened into characters and the color changes between them, which is all a
+/// markup or escape code based format needs to know.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Token {
+    Style { fg: Option<Color>, bg: Option<Color> },
+    Char(char),
+}
+
+/// Tokenizes one row, dropping trailing blanks. A blank cell's foreground can't be seen, so it
+/// never starts a new style on its own; that keeps runs like `│ │` in a single style.
+pub fn tokens(row: &[Cell], palette: &Palette) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let len = row.iter()
+        .rposition(|c| !c.is_blank() || c.background.is_some())
+        .map_or(0, |i| i + 1);
+    let (mut current_fg, mut current_bg) = (None, None);
+    for cell in &row[..len] {
+        let fg = if cell.is_blank() { current_fg } else { cell.foreground(palette) };
+        let bg = cell.background;
+        if fg != current_fg || bg != current_bg {
+            tokens.push(Token::Style { fg, bg });
+            current_fg = fg;
+            current_bg = bg;
+        }
+        tokens.push(Token::Char(cell.ch));
+    }
+    tokens
+}
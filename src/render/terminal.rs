@@ -0,0 +1,48 @@
+use std::fmt::Write as _;
+
+use crate::grid::Grid;
+use crate::palette::{Color, Palette};
+use crate::quantize;
+use crate::render::style::{self, Token};
+use crate::render::ColorDepth;
+
+/// Renders the grid as text colored with ANSI escape codes, 24-bit ones or the nearest colors
+/// there are at a lower `depth`.
+pub fn render(grid: &Grid, palette: &Palette, depth: ColorDepth) -> String {
+    let mut text = String::new();
+    for row in grid.rows() {
+        let tokens = style::tokens(row, palette);
+        for token in &tokens {
+            match *token {
+                Token::Style { fg, bg } => text.push_str(&sgr(fg, bg, palette, depth)),
+                Token::Char(ch) => text.push(ch),
+            }
+        }
+        if tokens.iter().any(|t| matches!(t, Token::Style { .. })) {
+            text.push_str("\x1b[0m");
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn sgr(fg: Option<Color>, bg: Option<Color>, palette: &Palette, depth: ColorDepth) -> String {
+    let mut codes = String::from("\x1b[0");
+    for (color, base) in [(fg, 30), (bg, 40)] {
+        let Some(c) = color else { continue };
+        match depth {
+            ColorDepth::TrueColor => write!(codes, ";{};2;{};{};{}", base + 8, c.r, c.g, c.b).unwrap(),
+            ColorDepth::Ansi256 => {
+                let code = palette.ansi256(c).unwrap_or_else(|| quantize::nearest_256(c));
+                write!(codes, ";{};5;{}", base + 8, code).unwrap();
+            }
+            // Bright colors have codes of their own, 60 past the normal ones
+            ColorDepth::Ansi16 => match quantize::nearest_16(c) {
+                code @ 0..=7 => write!(codes, ";{}", base + code as usize).unwrap(),
+                code => write!(codes, ";{}", base + 60 + code as usize - 8).unwrap(),
+            },
+        }
+    }
+    codes.push('m');
+    codes
+}
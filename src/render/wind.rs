@@ -0,0 +1,40 @@
+use crate::grid::Grid;
+use crate::rocket::PartType;
+
+/// Shears the rocket sideways, as if leaning into a wind of `strength`: its nose is moved that
+/// many columns, to the right for a positive strength, and each line below it less, down to none
+/// at the bottom of the body. Exhaust streams off the other way, a column for every two lines. No
+/// wind leans the nose further than the rocket is tall, however strong, see [`clamp`].
+pub fn shear(grid: &Grid, strength: f32) -> Grid {
+    let strength = clamp(strength, grid.height());
+    let exhaust = |y: usize| {
+        let mut cells = (0..grid.width()).map(|x| grid.get(x, y)).filter(|cell| !cell.is_blank()).peekable();
+        cells.peek().is_some() && cells.all(|cell| cell.part_type == Some(PartType::Exhaust))
+    };
+    let body = (0..grid.height()).rposition(|y| !exhaust(y)).map_or(0, |y| y + 1);
+    let offsets: Vec<isize> = (0..grid.height())
+        .map(|y| {
+            if y < body {
+                (strength * (body - 1 - y) as f32 / body.saturating_sub(1).max(1) as f32).round() as isize
+            } else {
+                -(strength.signum() * (y + 1 - body) as f32 / 2.0).round() as isize
+            }
+        })
+        .collect();
+    let left = -offsets.iter().copied().min().unwrap_or(0).min(0);
+    let right = offsets.iter().copied().max().unwrap_or(0).max(0);
+
+    let mut sheared = Grid::new(grid.width() + (left + right) as usize, grid.height());
+    for (y, row) in grid.rows().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            sheared.set((x as isize + left + offsets[y]) as usize, y, *cell);
+        }
+    }
+    sheared
+}
+
+/// `strength` brought within `height` columns either way, as far as a wind leans a rocket that
+/// tall.
+pub fn clamp(strength: f32, height: usize) -> f32 {
+    strength.clamp(-(height as f32), height as f32)
+}
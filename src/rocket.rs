@@ -1,181 +1,1856 @@
-pub mod rocket {
-    use std::cmp::max;
-    use std::fmt;
-    use rand;
-    use rand::distributions::WeightedIndex;
-    use rand::prelude::*;
-
-    #[derive(PartialEq, Debug)]
-    pub enum PartType {
-        TIP,
-        BODY,
-        ENGINE,
-        EXHAUST,
-    }
-
-    #[derive(Debug)]
-    pub struct Part {
-        height: usize,
-        top_width: usize,
-        bottom_width: usize,
-        shape: &'static str,
-        type_: PartType,
-        selection_weight: usize,
-    }
-
-    impl fmt::Display for Part {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.shape)
-        }
-    }
-
-    pub const PARTS_BIN: [Part; 23] = [
-        // Tips
-        Part { top_width: 0, bottom_width: 0, height: 1, shape: "│", type_: PartType::TIP, selection_weight: 1 },
-        Part { top_width: 0, bottom_width: 0, height: 2, shape: "│\n║", type_: PartType::TIP, selection_weight: 1 },
-
-        // Transitions
-        Part { top_width: 0, bottom_width: 1, height: 1, shape: "/'\\", type_: PartType::BODY, selection_weight: 2 },
-        Part { top_width: 0, bottom_width: 1, height: 1, shape: "┌┴┐", type_: PartType::BODY, selection_weight: 2 },
-        Part { top_width: 0, bottom_width: 1, height: 1, shape: "┌╩┐", type_: PartType::BODY, selection_weight: 1 },
-        Part { top_width: 1, bottom_width: 3, height: 1, shape: "/   \\", type_: PartType::BODY, selection_weight: 2 },
-        Part { top_width: 0, bottom_width: 3, height: 2, shape: "/'\\\n/   \\", type_: PartType::BODY, selection_weight: 1 },
-        Part { top_width: 1, bottom_width: 3, height: 1, shape: "┌┘ └┐", type_: PartType::BODY, selection_weight: 1 },
-        Part { top_width: 3, bottom_width: 1, height: 1, shape: "\\   /", type_: PartType::BODY, selection_weight: 1 },
-        Part { top_width: 3, bottom_width: 1, height: 1, shape: "└┐ ┌┘", type_: PartType::BODY, selection_weight: 1 },
-
-        // Body
-        Part { top_width: 1, bottom_width: 1, height: 1, shape: "│ │", type_: PartType::BODY, selection_weight: 10 },
-        Part { top_width: 1, bottom_width: 1, height: 1, shape: "│°│", type_: PartType::BODY, selection_weight: 5 },
-        Part { top_width: 1, bottom_width: 1, height: 1, shape: "/│ │\\", type_: PartType::BODY, selection_weight: 1 },
-        Part { top_width: 3, bottom_width: 3, height: 1, shape: "│   │", type_: PartType::BODY, selection_weight: 10 },
-        Part { top_width: 3, bottom_width: 3, height: 1, shape: "│° °│", type_: PartType::BODY, selection_weight: 5 },
-        Part { top_width: 3, bottom_width: 3, height: 1, shape: "│ O │", type_: PartType::BODY, selection_weight: 5 },
-        Part { top_width: 3, bottom_width: 3, height: 2, shape: "/│ ^ │\\\n/_│ | │_\\", type_: PartType::BODY, selection_weight: 1 },
-
-        // Engines
-        Part { top_width: 1, bottom_width: 0, height: 1, shape: "'─'", type_: PartType::ENGINE, selection_weight: 1 },
-        Part { top_width: 3, bottom_width: 1, height: 1, shape: "\\_/", type_: PartType::ENGINE, selection_weight: 1 },
-        Part { top_width: 1, bottom_width: 0, height: 1, shape: "( )", type_: PartType::EXHAUST, selection_weight: 1 },
-        Part { top_width: 0, bottom_width: 0, height: 1, shape: "·", type_: PartType::EXHAUST, selection_weight: 1 },
-        Part { top_width: 0, bottom_width: 0, height: 1, shape: ".", type_: PartType::EXHAUST, selection_weight: 1 },
-        Part { top_width: 0, bottom_width: 0, height: 1, shape: "'", type_: PartType::EXHAUST, selection_weight: 1 },
+use std::borrow::Cow;
+use std::cmp::{max, Reverse};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::date::Date;
+use crate::grammar::Grammar;
+use crate::learn::Model;
+use crate::grid::{Cell, Grid};
+use crate::registry;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PartType {
+    Tip,
+    Body,
+    Engine,
+    Exhaust,
+}
+
+impl fmt::Display for PartType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PartType::Tip => write!(f, "tip"),
+            PartType::Body => write!(f, "body"),
+            PartType::Engine => write!(f, "engine"),
+            PartType::Exhaust => write!(f, "exhaust"),
+        }
+    }
+}
+
+impl FromStr for PartType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tip" => Ok(PartType::Tip),
+            "body" => Ok(PartType::Body),
+            "engine" => Ok(PartType::Engine),
+            "exhaust" => Ok(PartType::Exhaust),
+            _ => Err(format!("Unknown part type \"{}\"", s)),
+        }
+    }
+}
+
+/// The look of the bodies that change a rocket's width, which clash when mixed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Look {
+    /// Sloping sides, like `/   \`
+    Rounded,
+    /// Box drawing corners, like `┌┘ └┐`
+    Angular,
+}
+
+impl Look {
+    pub const ALL: [Look; 2] = [Look::Rounded, Look::Angular];
+}
+
+impl fmt::Display for Look {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Look::Rounded => write!(f, "rounded"),
+            Look::Angular => write!(f, "angular"),
+        }
+    }
+}
+
+/// What kind of nose a rocket has, for [`BuildOptions::nose`]. Noses say which they are with a
+/// [`Feature`], see [`Part::nose`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Nose {
+    /// Blunt, with a window, like `/°\`
+    Capsule,
+    /// A bulbous payload fairing, like `(   )`
+    Fairing,
+    /// A box with an antenna or a dish on top, like `┌┴┐`
+    Probe,
+    /// Sloping to a point, like `/'\`
+    Spike,
+    /// One of the others, picked from each rocket's seed among the ones there are noses for
+    Random,
+}
+
+impl Nose {
+    /// Every kind of nose but [`Nose::Random`]
+    pub const ALL: [Nose; 4] = [Nose::Capsule, Nose::Fairing, Nose::Probe, Nose::Spike];
+}
+
+impl Nose {
+    /// The kind of nose to build from `parts`, the random one being picked from `seed` out of the
+    /// kinds there are noses for, hashed like a [`Look`] for [`BuildOptions::coherent`].
+    fn resolve(self, parts: &[Arc<Part>], seed: u64) -> Nose {
+        if self != Nose::Random {
+            return self;
+        }
+        let noses: Vec<Nose> = Nose::ALL.into_iter()
+            .filter(|&nose| parts.iter().any(|p| p.type_ == PartType::Body && p.nose() == Some(nose)))
+            .collect();
+        if noses.is_empty() {
+            return Nose::Random;
+        }
+        noses[(fnv1a(seed.to_le_bytes().into_iter()) % noses.len() as u64) as usize]
+    }
+}
+
+impl fmt::Display for Nose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Nose::Capsule => write!(f, "capsule"),
+            Nose::Fairing => write!(f, "fairing"),
+            Nose::Probe => write!(f, "probe"),
+            Nose::Spike => write!(f, "spike"),
+            Nose::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl FromStr for Nose {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Nose::ALL.into_iter().chain([Nose::Random])
+            .find(|nose| nose.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown nose \"{}\", expected capsule, fairing, probe, spike or random", s))
+    }
+}
+
+/// What a part looks like, for steering which parts rockets are built from. Most tags are worked
+/// out from the part's shape, so parts from packs have them too, but [`Tag::GridFin`] and
+/// [`Tag::Docking`] come from the part's [`Feature`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tag {
+    /// A [`Look::Rounded`] body
+    Rounded,
+    /// A [`Look::Angular`] body
+    Angular,
+    /// With portholes, like `│°│`
+    Porthole,
+    /// With fins or markings, like `/│ ^ │\`
+    Greeble,
+    /// Plain ASCII, like a rocket drawn on an old terminal
+    Retro,
+    /// An engine with its nozzles angled to one side, like `/_/`
+    Gimbaled,
+    /// With grid fins for steering a booster back down, like `▦│ │▦`
+    GridFin,
+    /// A docking port or the collar ringing one, for an upper stage to dock with, like `╞═╡`
+    Docking,
+}
+
+impl Tag {
+    pub const ALL: [Tag; 8] = [
+        Tag::Rounded, Tag::Angular, Tag::Porthole, Tag::Greeble, Tag::Retro, Tag::Gimbaled, Tag::GridFin, Tag::Docking,
     ];
+}
 
-    pub struct Rocket {
-        pub max_height: usize,
-        pub max_width: usize,
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tag::Rounded => write!(f, "rounded"),
+            Tag::Angular => write!(f, "angular"),
+            Tag::Porthole => write!(f, "porthole"),
+            Tag::Greeble => write!(f, "greeble"),
+            Tag::Retro => write!(f, "retro"),
+            Tag::Gimbaled => write!(f, "gimbaled"),
+            Tag::GridFin => write!(f, "gridfin"),
+            Tag::Docking => write!(f, "docking"),
+        }
+    }
+}
+
+impl FromStr for Tag {
+    type Err = String;
 
-        sections: Vec<&'static Part>,
-        height: usize,
-        bottom_width: usize,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tag::ALL.into_iter()
+            .find(|tag| tag.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown tag \"{}\", expected rounded, angular, porthole, greeble, retro, gimbaled, gridfin or docking", s))
     }
+}
 
-    impl Default for Rocket {
-        fn default() -> Self {
-            Rocket { max_height: 3, max_width: 3, sections: Vec::new(), height: 0, bottom_width: 0 }
+/// How seldom a part turns up. Common parts are picked by their selection weights; the others are
+/// only ever rolled for, each pick having a small chance of being one of them whatever the
+/// weights say. Built in parts are all common but for the `loot` pack's, which `--loot` adds.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    pub const ALL: [Rarity; 4] = [Rarity::Common, Rarity::Uncommon, Rarity::Rare, Rarity::Legendary];
+
+    /// Chance in a thousand of a pick rolling this rarity, when a part of it fits.
+    fn odds(self) -> u64 {
+        match self {
+            Rarity::Common => 0,
+            Rarity::Uncommon => 40,
+            Rarity::Rare => 10,
+            Rarity::Legendary => 2,
         }
     }
+}
 
-    impl Rocket {
-        pub fn new(max_height: usize) -> Rocket {
-            let mut rocket = Rocket {
-                max_height,
-                ..Rocket::default()
-            };
-            rocket.build();
-            return rocket;
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rarity::Common => write!(f, "common"),
+            Rarity::Uncommon => write!(f, "uncommon"),
+            Rarity::Rare => write!(f, "rare"),
+            Rarity::Legendary => write!(f, "legendary"),
+        }
+    }
+}
+
+impl FromStr for Rarity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rarity::ALL.into_iter()
+            .find(|rarity| rarity.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown rarity \"{}\", expected common, uncommon, rare or legendary", s))
+    }
+}
+
+/// What a part is for, beyond its type, which decides where on a rocket the builder puts it. Built
+/// in parts are given theirs by hand, and parts from packs by their `feature` key, rather than
+/// having it guessed from their glyphs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Feature {
+    /// Grid fins for steering a booster back down, which only go at the top of one
+    GridFins,
+    /// Landing legs folded up against the hull, which swing out to land on
+    LandingLegs,
+    /// A launch escape tower, a tip that only goes above the nose of rockets built with
+    /// [`BuildOptions::escape_tower`]
+    EscapeTower,
+    /// Solar panel wings spread either side of the hull, which only go on upper stages
+    SolarPanels,
+    /// The collar ringing a docking port, which only goes on upper stages
+    DockingCollar,
+    /// A blunt nose with a window, see [`Nose::Capsule`]
+    CapsuleNose,
+    /// A bulbous nose, see [`Nose::Fairing`]
+    FairingNose,
+    /// A box with an antenna or a dish on top, see [`Nose::Probe`]
+    ProbeNose,
+    /// A docking port, a tip that only goes straight onto a capsule nose as wide as it
+    DockingPort,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 9] = [Feature::GridFins, Feature::LandingLegs, Feature::EscapeTower, Feature::SolarPanels, Feature::DockingCollar, Feature::CapsuleNose, Feature::FairingNose, Feature::ProbeNose, Feature::DockingPort];
+
+    /// The kind of nose the feature makes a nose, if it's one of those.
+    pub fn nose(self) -> Option<Nose> {
+        match self {
+            Feature::CapsuleNose => Some(Nose::Capsule),
+            Feature::FairingNose => Some(Nose::Fairing),
+            Feature::ProbeNose => Some(Nose::Probe),
+            _ => None,
+        }
+    }
+
+    /// The type of part that can have the feature.
+    pub fn part_type(self) -> PartType {
+        match self {
+            Feature::GridFins => PartType::Body,
+            Feature::LandingLegs => PartType::Body,
+            Feature::EscapeTower => PartType::Tip,
+            Feature::SolarPanels => PartType::Body,
+            Feature::DockingCollar => PartType::Body,
+            Feature::CapsuleNose => PartType::Body,
+            Feature::FairingNose => PartType::Body,
+            Feature::ProbeNose => PartType::Body,
+            Feature::DockingPort => PartType::Tip,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Feature::GridFins => write!(f, "grid_fins"),
+            Feature::LandingLegs => write!(f, "landing_legs"),
+            Feature::EscapeTower => write!(f, "escape_tower"),
+            Feature::SolarPanels => write!(f, "solar_panels"),
+            Feature::DockingCollar => write!(f, "docking_collar"),
+            Feature::CapsuleNose => write!(f, "capsule_nose"),
+            Feature::FairingNose => write!(f, "fairing_nose"),
+            Feature::ProbeNose => write!(f, "probe_nose"),
+            Feature::DockingPort => write!(f, "docking_port"),
+        }
+    }
+}
+
+impl FromStr for Feature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Feature::ALL.into_iter()
+            .find(|feature| feature.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown feature \"{}\", expected grid_fins, landing_legs, escape_tower, solar_panels, docking_collar, capsule_nose, fairing_nose, probe_nose or docking_port", s))
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Part {
+    /// Names the part in filters, blueprints and bug reports, and stays the same from release to
+    /// release, wherever the part ends up in its pack
+    id: Cow<'static, str>,
+    /// What to call the part in front of people
+    name: Cow<'static, str>,
+    height: usize,
+    top_width: usize,
+    bottom_width: usize,
+    shape: Cow<'static, str>,
+    type_: PartType,
+    selection_weight: usize,
+    rarity: Rarity,
+    feature: Option<Feature>,
+}
+
+impl Part {
+    /// A part made at runtime, read from a part pack or generated. Its shape isn't checked against
+    /// the widths and height, see [`crate::pack::validate`] for that. Its id and name are made up
+    /// from its kind and a hash of its shape and widths, so the same part gets the same one every
+    /// time; [`Part::named`] gives it better ones.
+    pub fn new(part_type: PartType, top_width: usize, bottom_width: usize, height: usize, selection_weight: usize,
+               shape: impl Into<Cow<'static, str>>) -> Part {
+        let shape = shape.into();
+        let hash = fnv1a(shape.bytes().chain([top_width as u8, bottom_width as u8]));
+        let id = format!("{}_{:08x}", part_type, hash as u32);
+        Part { name: Cow::Owned(id.clone()), id: Cow::Owned(id), height, top_width, bottom_width, shape, type_: part_type,
+               selection_weight, rarity: Rarity::Common, feature: None }
+    }
+
+    /// The part at `rarity` instead of common.
+    pub fn with_rarity(self, rarity: Rarity) -> Part {
+        Part { rarity, ..self }
+    }
+
+    /// The part with `feature`, see [`Feature`].
+    pub fn with_feature(self, feature: Feature) -> Part {
+        Part { feature: Some(feature), ..self }
+    }
+
+    /// The part with `id` and `name` instead of the ones it had.
+    pub fn named(self, id: impl Into<Cow<'static, str>>, name: impl Into<Cow<'static, str>>) -> Part {
+        Part { id: id.into(), name: name.into(), ..self }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn top_width(&self) -> usize {
+        self.top_width
+    }
+
+    pub fn bottom_width(&self) -> usize {
+        self.bottom_width
+    }
+
+    pub fn shape(&self) -> &str {
+        &self.shape
+    }
+
+    pub fn part_type(&self) -> PartType {
+        self.type_
+    }
+
+    pub fn selection_weight(&self) -> usize {
+        self.selection_weight
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        self.rarity
+    }
+
+    pub fn feature(&self) -> Option<Feature> {
+        self.feature
+    }
+
+    /// Widest line of the shape in characters.
+    pub fn width(&self) -> usize {
+        self.shape.lines().map(|line| line.chars().count()).max().unwrap_or(0)
+    }
+
+    /// The part flipped left to right, or `None` if that would look the same or it has characters
+    /// without a mirror image, like most letters. Its id and name are the part's own, marked as
+    /// mirrored.
+    pub fn mirrored(&self) -> Option<Part> {
+        let lines: Option<Vec<String>> = self.shape.lines().map(mirror_line).collect();
+        let shape = lines?.join("\n");
+        if shape == self.shape {
+            return None;
+        }
+        Some(Part {
+            id: Cow::Owned(format!("{}_mirrored", self.id)),
+            name: Cow::Owned(format!("{} (mirrored)", self.name)),
+            shape: Cow::Owned(shape),
+            ..self.clone()
+        })
+    }
+
+    /// Whether `other` is the same part, whatever their weights, which styles change.
+    pub fn same_as(&self, other: &Part) -> bool {
+        self.type_ == other.type_ && self.shape == other.shape && self.top_width == other.top_width
+            && self.bottom_width == other.bottom_width && self.height == other.height
+    }
+
+    /// Whether the part has portholes, markings or fins sticking out past its hull.
+    fn decorated(&self) -> bool {
+        self.finned() || self.shape.contains(['°', 'O', 'o', '^'])
+    }
+
+    /// Whether the part has fins or a skirt sticking out past its hull.
+    pub(crate) fn finned(&self) -> bool {
+        self.shape.lines().any(|line| {
+            let (width, hull) = measure(line.as_bytes(), 0, line.len());
+            hull < width
+        })
+    }
+
+    /// Whether the part is a launch escape tower, which only goes above the nose of rockets built
+    /// with [`BuildOptions::escape_tower`].
+    pub fn escape_tower(&self) -> bool {
+        self.feature == Some(Feature::EscapeTower)
+    }
+
+    /// Whether the part has grid fins, like `▦│ │▦`.
+    pub fn grid_finned(&self) -> bool {
+        self.feature == Some(Feature::GridFins)
+    }
+
+    /// Whether the part has landing legs folded up against its hull, like `▐│ │▌`.
+    pub fn stowed_legs(&self) -> bool {
+        self.feature == Some(Feature::LandingLegs)
+    }
+
+    /// Whether the part is a docking port, like `╒═╕`, or the collar ringing one, like `╞═╡`.
+    pub fn docking(&self) -> bool {
+        matches!(self.feature, Some(Feature::DockingPort | Feature::DockingCollar))
+    }
+
+    /// Whether the part spreads solar panel wings either side of its hull, like `▤▤│ │▤▤`.
+    pub fn solar_panels(&self) -> bool {
+        self.feature == Some(Feature::SolarPanels)
+    }
+
+    /// Which kind of nose the part is, for noses, the bodies a rocket starts from, or goes on, for
+    /// tips: docking ports only top capsules, and antennas and beacons probes. Noses are the kind their feature says, and
+    /// spikes without one. Escape towers go on any.
+    pub fn nose(&self) -> Option<Nose> {
+        match self.type_ {
+            PartType::Tip if self.feature == Some(Feature::DockingPort) => Some(Nose::Capsule),
+            PartType::Tip if !self.escape_tower() => Some(Nose::Probe),
+            PartType::Body if self.top_width == 0 => Some(self.feature.and_then(Feature::nose).unwrap_or(Nose::Spike)),
+            _ => None,
+        }
+    }
+
+    /// How many nozzles an engine has, counted along its bottom line: 2 or 3 for a cluster, 1 for
+    /// anything else.
+    pub fn nozzles(&self) -> usize {
+        if self.type_ != PartType::Engine {
+            return 1;
+        }
+        self.nozzle_columns().len()
+    }
+
+    /// Columns along the bottom line that each nozzle's exhaust comes out of, midway between each
+    /// pair of slashes drawing a nozzle's sides, or the middle of the line for a nozzle drawn some
+    /// other way.
+    pub fn nozzle_columns(&self) -> Vec<usize> {
+        let line = self.shape.lines().last().unwrap_or("");
+        let sides: Vec<usize> = line.chars().enumerate().filter(|&(_, ch)| matches!(ch, '/' | '\\')).map(|(x, _)| x).collect();
+        let columns: Vec<usize> = sides.chunks_exact(2).map(|pair| (pair[0] + pair[1]) / 2).collect();
+        if columns.is_empty() {
+            return vec![line.chars().count() / 2];
+        }
+        columns
+    }
+
+    /// Which way a gimbaled engine points its exhaust, a column a line: -1 to the left for sides
+    /// both drawn `/`, 1 to the right for both drawn `\`, like its mirror image, and 0 for
+    /// anything else.
+    pub fn lean(&self) -> isize {
+        if self.type_ != PartType::Engine {
+            return 0;
+        }
+        let line = self.shape.lines().last().unwrap_or("");
+        let mut sides = line.chars().filter(|ch| matches!(ch, '/' | '\\'));
+        match (sides.next(), sides.next_back()) {
+            (Some('/'), Some('/')) => -1,
+            (Some('\\'), Some('\\')) => 1,
+            _ => 0,
+        }
+    }
+
+    /// Whether the shape is plain ASCII, which every chat client draws one column per character.
+    /// Box drawing characters, `°` and `·` are ambiguous width and come out misaligned in some.
+    pub fn chat_safe(&self) -> bool {
+        self.shape.is_ascii()
+    }
+
+    /// The part's tags, in the order of [`Tag::ALL`].
+    pub fn tags(&self) -> Vec<Tag> {
+        Tag::ALL.into_iter().filter(|&tag| self.has_tag(tag)).collect()
+    }
+
+    pub fn has_tag(&self, tag: Tag) -> bool {
+        match tag {
+            Tag::Rounded => self.look() == Some(Look::Rounded),
+            Tag::Angular => self.look() == Some(Look::Angular),
+            Tag::Porthole => self.shape.contains(['°', 'O', 'o']),
+            Tag::Greeble => self.finned() || self.shape.contains('^'),
+            Tag::Retro => self.chat_safe(),
+            Tag::Gimbaled => self.lean() != 0,
+            Tag::GridFin => self.grid_finned(),
+            Tag::Docking => self.docking(),
         }
+    }
+
+    /// Which look the part has, for bodies that change width, like noses. Parts that go with
+    /// either, like straight bodies and engines, have none.
+    pub fn look(&self) -> Option<Look> {
+        if self.type_ != PartType::Body || self.top_width == self.bottom_width {
+            None
+        } else if self.shape.contains(['┌', '┐', '└', '┘']) {
+            Some(Look::Angular)
+        } else if self.shape.contains(['/', '\\']) {
+            Some(Look::Rounded)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.shape)
+    }
+}
+
+/// Glyphs that turn into each other when flipped left to right.
+const MIRRORED_GLYPHS: [(char, char); 18] = [
+    ('/', '\\'), ('(', ')'), ('[', ']'), ('{', '}'), ('<', '>'), ('b', 'd'), ('p', 'q'),
+    ('┌', '┐'), ('└', '┘'), ('├', '┤'), ('╔', '╗'), ('╚', '╝'), ('╠', '╣'), ('╓', '╖'),
+    ('╙', '╜'), ('▌', '▐'), ('◄', '►'), ('╱', '╲'),
+];
+
+/// Glyphs that look the same flipped left to right.
+const SYMMETRIC_GLYPHS: &str = " !\"'*+-.:=^_|#°·│║─═┴┬╩╦┼╬╨╥▀▄█░▒▓AHIMOTUVWXYilovwx";
 
-        fn append_section(&mut self, part: &'static Part) {
-            if part.height + self.height > self.max_height {
-                panic!("Cannot add part because it would make the rocket too tall")
+pub const PARTS_BIN: [Part; 48] = [
+    // Tips
+    Part { id: Cow::Borrowed("tip_single"), name: Cow::Borrowed("Antenna"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("│"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("tip_double"), name: Cow::Borrowed("Double antenna"), top_width: 0, bottom_width: 0, height: 2, shape: Cow::Borrowed("│\n║"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("tip_whip"), name: Cow::Borrowed("Whip antenna"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("'\n│\n│"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("tip_whip_forked"), name: Cow::Borrowed("Forked antenna"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("\\ /\n│\n│"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("tip_boom_dish"), name: Cow::Borrowed("Dish on a boom"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("\\_/\n│\n║"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("tip_pitot"), name: Cow::Borrowed("Pitot boom"), top_width: 0, bottom_width: 0, height: 4, shape: Cow::Borrowed("*\n│\n│\n║"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("docking_port"), name: Cow::Borrowed("Docking port"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("╒═╕"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingPort) },
+    Part { id: Cow::Borrowed("tower_escape"), name: Cow::Borrowed("Escape tower"), top_width: 0, bottom_width: 0, height: 4, shape: Cow::Borrowed("^\n/_\\\n╳\n╳"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::EscapeTower) },
+    Part { id: Cow::Borrowed("tower_escape_short"), name: Cow::Borrowed("Short escape tower"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("^\n/_\\\n╳"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::EscapeTower) },
+
+    // Transitions
+    Part { id: Cow::Borrowed("nose_point"), name: Cow::Borrowed("Pointed nose"), top_width: 0, bottom_width: 1, height: 1, shape: Cow::Borrowed("/'\\"), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("nose_box"), name: Cow::Borrowed("Box nose"), top_width: 0, bottom_width: 1, height: 1, shape: Cow::Borrowed("┌┴┐"), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: Some(Feature::ProbeNose) },
+    Part { id: Cow::Borrowed("nose_box_double"), name: Cow::Borrowed("Double box nose"), top_width: 0, bottom_width: 1, height: 1, shape: Cow::Borrowed("┌╩┐"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::ProbeNose) },
+    Part { id: Cow::Borrowed("nose_needle"), name: Cow::Borrowed("Needle nose"), top_width: 0, bottom_width: 1, height: 2, shape: Cow::Borrowed("'\n/ \\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("nose_capsule_slim"), name: Cow::Borrowed("Slim capsule"), top_width: 0, bottom_width: 1, height: 2, shape: Cow::Borrowed("_\n/°\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::CapsuleNose) },
+    Part { id: Cow::Borrowed("nose_fairing_slim"), name: Cow::Borrowed("Slim fairing"), top_width: 0, bottom_width: 1, height: 2, shape: Cow::Borrowed(".\n( )"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::FairingNose) },
+    Part { id: Cow::Borrowed("shoulder_slope"), name: Cow::Borrowed("Sloped shoulder"), top_width: 1, bottom_width: 3, height: 1, shape: Cow::Borrowed("/   \\"), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("nose_cone"), name: Cow::Borrowed("Nose cone"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("/'\\\n/   \\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("nose_capsule"), name: Cow::Borrowed("Capsule"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("___\n/ ° \\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::CapsuleNose) },
+    Part { id: Cow::Borrowed("nose_fairing"), name: Cow::Borrowed("Payload fairing"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed(".-.\n(   )"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::FairingNose) },
+    Part { id: Cow::Borrowed("nose_probe_dish"), name: Cow::Borrowed("Dish probe"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("\\_/\n┌─┴─┐"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::ProbeNose) },
+    Part { id: Cow::Borrowed("shoulder_step"), name: Cow::Borrowed("Stepped shoulder"), top_width: 1, bottom_width: 3, height: 1, shape: Cow::Borrowed("┌┘ └┐"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_slope"), name: Cow::Borrowed("Sloped waist"), top_width: 3, bottom_width: 1, height: 1, shape: Cow::Borrowed("\\   /"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_step"), name: Cow::Borrowed("Stepped waist"), top_width: 3, bottom_width: 1, height: 1, shape: Cow::Borrowed("└┐ ┌┘"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+
+    // Body
+    Part { id: Cow::Borrowed("body_hull_slim"), name: Cow::Borrowed("Slim hull"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("│ │"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_porthole_slim"), name: Cow::Borrowed("Slim porthole"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("│°│"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_fins_slim"), name: Cow::Borrowed("Slim fins"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("/│ │\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_hull"), name: Cow::Borrowed("Hull"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│   │"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_eyes"), name: Cow::Borrowed("Twin portholes"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│° °│"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_window"), name: Cow::Borrowed("Window"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│ O │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_stage"), name: Cow::Borrowed("Finned stage"), top_width: 3, bottom_width: 3, height: 2, shape: Cow::Borrowed("/│ ^ │\\\n/_│ | │_\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_grid_fins_slim"), name: Cow::Borrowed("Slim grid fins"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("▦│ │▦"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("body_grid_fins"), name: Cow::Borrowed("Grid fins"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("▦│   │▦"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("legs_stowed_slim"), name: Cow::Borrowed("Stowed slim landing legs"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("▐│ │▌"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("legs_stowed"), name: Cow::Borrowed("Stowed landing legs"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("▐│   │▌"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("solar_panels_slim"), name: Cow::Borrowed("Slim solar panels"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("▤▤│ │▤▤"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("solar_panels"), name: Cow::Borrowed("Solar panels"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("▤▤│   │▤▤"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("docking_collar_slim"), name: Cow::Borrowed("Slim docking collar"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("╞═╡"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+    Part { id: Cow::Borrowed("docking_collar"), name: Cow::Borrowed("Docking collar"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("╞═══╡"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+
+    // Engines
+    Part { id: Cow::Borrowed("engine_nozzle"), name: Cow::Borrowed("Nozzle"), top_width: 1, bottom_width: 0, height: 1, shape: Cow::Borrowed("'─'"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_bell"), name: Cow::Borrowed("Engine bell"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("\\_/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt_slim"), name: Cow::Borrowed("Slim engine skirt"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("/_\\"), type_: PartType::Engine, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt_slim_ribbed"), name: Cow::Borrowed("Ribbed slim skirt"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("/=\\"), type_: PartType::Engine, selection_weight: 3, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt_slim_gimbaled"), name: Cow::Borrowed("Gimbaled slim skirt"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("/_/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume"), name: Cow::Borrowed("Plume"), top_width: 1, bottom_width: 0, height: 1, shape: Cow::Borrowed("( )"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume_flicker"), name: Cow::Borrowed("Flickering plume"), top_width: 1, bottom_width: 0, height: 1, shape: Cow::Borrowed("(')"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_spark"), name: Cow::Borrowed("Spark"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("·"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_smoke"), name: Cow::Borrowed("Smoke"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("."), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_flicker"), name: Cow::Borrowed("Flicker"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("'"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+];
+
+/// ASCII stand-ins for the parts that aren't [`Part::chat_safe`], so chat rockets have every kind
+/// of part to choose from.
+pub const CHAT_PARTS: [Part; 44] = [
+    Part { id: Cow::Borrowed("chat_tip"), name: Cow::Borrowed("ASCII antenna"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("|"), type_: PartType::Tip, selection_weight: 2, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_tip_whip"), name: Cow::Borrowed("ASCII whip antenna"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("'\n|\n|"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_tip_whip_forked"), name: Cow::Borrowed("ASCII forked antenna"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("\\ /\n|\n|"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_tip_boom_dish"), name: Cow::Borrowed("ASCII dish on a boom"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("\\_/\n|\n|"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_tip_pitot"), name: Cow::Borrowed("ASCII pitot boom"), top_width: 0, bottom_width: 0, height: 4, shape: Cow::Borrowed("*\n|\n|\n|"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_docking_port"), name: Cow::Borrowed("ASCII docking port"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("{_}"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingPort) },
+    Part { id: Cow::Borrowed("chat_tower_escape"), name: Cow::Borrowed("ASCII escape tower"), top_width: 0, bottom_width: 0, height: 4, shape: Cow::Borrowed("^\n/_\\\nX\nX"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::EscapeTower) },
+    Part { id: Cow::Borrowed("chat_tower_escape_short"), name: Cow::Borrowed("ASCII short escape tower"), top_width: 0, bottom_width: 0, height: 3, shape: Cow::Borrowed("^\n/_\\\nX"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::EscapeTower) },
+    Part { id: Cow::Borrowed("chat_nose_probe"), name: Cow::Borrowed("ASCII probe nose"), top_width: 0, bottom_width: 1, height: 1, shape: Cow::Borrowed(".T."), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: Some(Feature::ProbeNose) },
+    Part { id: Cow::Borrowed("chat_nose_capsule_slim"), name: Cow::Borrowed("ASCII slim capsule"), top_width: 0, bottom_width: 1, height: 2, shape: Cow::Borrowed("_\n/o\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::CapsuleNose) },
+    Part { id: Cow::Borrowed("chat_nose_capsule"), name: Cow::Borrowed("ASCII capsule"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("___\n/ o \\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::CapsuleNose) },
+    Part { id: Cow::Borrowed("chat_nose_probe_dish"), name: Cow::Borrowed("ASCII dish probe"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("\\_/\n.-T-."), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::ProbeNose) },
+    Part { id: Cow::Borrowed("chat_hull_slim"), name: Cow::Borrowed("ASCII slim hull"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("| |"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_porthole_slim"), name: Cow::Borrowed("ASCII slim porthole"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("|o|"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_fins_slim"), name: Cow::Borrowed("ASCII slim fins"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("/| |\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_hull"), name: Cow::Borrowed("ASCII hull"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("|   |"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_eyes"), name: Cow::Borrowed("ASCII twin portholes"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("|o o|"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_window"), name: Cow::Borrowed("ASCII window"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("| O |"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_stage"), name: Cow::Borrowed("ASCII finned stage"), top_width: 3, bottom_width: 3, height: 2, shape: Cow::Borrowed("/| ^ |\\\n/_| | |_\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_grid_fins_slim"), name: Cow::Borrowed("ASCII slim grid fins"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("#| |#"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("chat_grid_fins"), name: Cow::Borrowed("ASCII grid fins"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("#|   |#"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("chat_legs_stowed_slim"), name: Cow::Borrowed("ASCII stowed slim landing legs"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("!| |!"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("chat_legs_stowed"), name: Cow::Borrowed("ASCII stowed landing legs"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("!|   |!"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("chat_solar_panels_slim"), name: Cow::Borrowed("ASCII slim solar panels"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("[]| |[]"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("chat_solar_panels"), name: Cow::Borrowed("ASCII solar panels"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("[]|   |[]"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("chat_docking_collar_slim"), name: Cow::Borrowed("ASCII slim docking collar"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("{=}"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+    Part { id: Cow::Borrowed("chat_docking_collar"), name: Cow::Borrowed("ASCII docking collar"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("{===}"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+    Part { id: Cow::Borrowed("chat_nozzle"), name: Cow::Borrowed("ASCII nozzle"), top_width: 1, bottom_width: 0, height: 1, shape: Cow::Borrowed("'-'"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_hull_wide"), name: Cow::Borrowed("ASCII wide hull"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("|     |"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_eyes_wide"), name: Cow::Borrowed("ASCII wide twin portholes"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("| o o |"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_window_wide"), name: Cow::Borrowed("ASCII wide window"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("|  O  |"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_fins_wide"), name: Cow::Borrowed("ASCII wide fins"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("/|     |\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_grid_fins_wide"), name: Cow::Borrowed("ASCII wide grid fins"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("#|     |#"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("chat_legs_stowed_wide"), name: Cow::Borrowed("ASCII stowed wide landing legs"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("!|     |!"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("chat_solar_panels_wide"), name: Cow::Borrowed("ASCII wide solar panels"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("[]|     |[]"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("chat_docking_collar_wide"), name: Cow::Borrowed("ASCII wide docking collar"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("{=====}"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+    Part { id: Cow::Borrowed("chat_hull_broad"), name: Cow::Borrowed("ASCII broad hull"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("|       |"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_eyes_broad"), name: Cow::Borrowed("ASCII broad twin portholes"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("| o   o |"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_window_broad"), name: Cow::Borrowed("ASCII broad window"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("|   O   |"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_fins_broad"), name: Cow::Borrowed("ASCII broad fins"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("/|       |\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("chat_grid_fins_broad"), name: Cow::Borrowed("ASCII broad grid fins"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("#|       |#"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("chat_legs_stowed_broad"), name: Cow::Borrowed("ASCII stowed broad landing legs"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("!|       |!"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("chat_solar_panels_broad"), name: Cow::Borrowed("ASCII broad solar panels"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("[]|       |[]"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("chat_docking_collar_broad"), name: Cow::Borrowed("ASCII broad docking collar"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("{=======}"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+];
+
+/// Parts for hulls 5 and 7 wide, which only rockets tall enough to carry them are built from, and
+/// the wide exhaust that hulls 3 wide can end in too. See [`Profile::parts`].
+pub const WIDE_PARTS: [Part; 42] = [
+    // Transitions
+    Part { id: Cow::Borrowed("shoulder_slope_wide"), name: Cow::Borrowed("Wide sloped shoulder"), top_width: 3, bottom_width: 5, height: 1, shape: Cow::Borrowed("/     \\"), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("shoulder_step_wide"), name: Cow::Borrowed("Wide stepped shoulder"), top_width: 3, bottom_width: 5, height: 1, shape: Cow::Borrowed("┌┘   └┐"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_slope_wide"), name: Cow::Borrowed("Wide sloped waist"), top_width: 5, bottom_width: 3, height: 1, shape: Cow::Borrowed("\\     /"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_step_wide"), name: Cow::Borrowed("Wide stepped waist"), top_width: 5, bottom_width: 3, height: 1, shape: Cow::Borrowed("└┐   ┌┘"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("shoulder_slope_broad"), name: Cow::Borrowed("Broad sloped shoulder"), top_width: 5, bottom_width: 7, height: 1, shape: Cow::Borrowed("/       \\"), type_: PartType::Body, selection_weight: 2, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("shoulder_step_broad"), name: Cow::Borrowed("Broad stepped shoulder"), top_width: 5, bottom_width: 7, height: 1, shape: Cow::Borrowed("┌┘     └┐"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_slope_broad"), name: Cow::Borrowed("Broad sloped waist"), top_width: 7, bottom_width: 5, height: 1, shape: Cow::Borrowed("\\       /"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("waist_step_broad"), name: Cow::Borrowed("Broad stepped waist"), top_width: 7, bottom_width: 5, height: 1, shape: Cow::Borrowed("└┐     ┌┘"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+
+    // Body
+    Part { id: Cow::Borrowed("body_hull_wide"), name: Cow::Borrowed("Wide hull"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("│     │"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_eyes_wide"), name: Cow::Borrowed("Wide twin portholes"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("│ ° ° │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_window_wide"), name: Cow::Borrowed("Wide window"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("│  O  │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_stage_wide"), name: Cow::Borrowed("Wide finned stage"), top_width: 5, bottom_width: 5, height: 2, shape: Cow::Borrowed("/│  ^  │\\\n/_│  |  │_\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_grid_fins_wide"), name: Cow::Borrowed("Wide grid fins"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("▦│     │▦"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("legs_stowed_wide"), name: Cow::Borrowed("Stowed wide landing legs"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("▐│     │▌"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("solar_panels_wide"), name: Cow::Borrowed("Wide solar panels"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("▤▤│     │▤▤"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("docking_collar_wide"), name: Cow::Borrowed("Wide docking collar"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("╞═════╡"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+    Part { id: Cow::Borrowed("body_hull_broad"), name: Cow::Borrowed("Broad hull"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("│       │"), type_: PartType::Body, selection_weight: 10, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_eyes_broad"), name: Cow::Borrowed("Broad twin portholes"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("│ °   ° │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_porthole_row"), name: Cow::Borrowed("Porthole row"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("│ ° ° ° │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_window_broad"), name: Cow::Borrowed("Broad window"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("│   O   │"), type_: PartType::Body, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_fins_broad"), name: Cow::Borrowed("Broad fins"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("/│       │\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("body_grid_fins_broad"), name: Cow::Borrowed("Broad grid fins"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("▦│       │▦"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::GridFins) },
+    Part { id: Cow::Borrowed("legs_stowed_broad"), name: Cow::Borrowed("Stowed broad landing legs"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("▐│       │▌"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::LandingLegs) },
+    Part { id: Cow::Borrowed("solar_panels_broad"), name: Cow::Borrowed("Broad solar panels"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("▤▤│       │▤▤"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::SolarPanels) },
+    Part { id: Cow::Borrowed("docking_collar_broad"), name: Cow::Borrowed("Broad docking collar"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("╞═══════╡"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Common, feature: Some(Feature::DockingCollar) },
+
+    // Engines
+    Part { id: Cow::Borrowed("engine_bell_wide"), name: Cow::Borrowed("Wide engine bell"), top_width: 3, bottom_width: 1, height: 1, shape: Cow::Borrowed("\\___/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_bell_broad"), name: Cow::Borrowed("Broad engine bell"), top_width: 5, bottom_width: 3, height: 1, shape: Cow::Borrowed("\\_____/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt"), name: Cow::Borrowed("Engine skirt"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("/___\\"), type_: PartType::Engine, selection_weight: 5, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt_ribbed"), name: Cow::Borrowed("Ribbed engine skirt"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("/===\\"), type_: PartType::Engine, selection_weight: 3, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_skirt_gimbaled"), name: Cow::Borrowed("Gimbaled engine skirt"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("/___/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume_wide"), name: Cow::Borrowed("Wide plume"), top_width: 3, bottom_width: 1, height: 1, shape: Cow::Borrowed("(   )"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume_wide_flicker"), name: Cow::Borrowed("Wide flickering plume"), top_width: 3, bottom_width: 1, height: 1, shape: Cow::Borrowed("( ' )"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+
+    // Engine clusters, a nozzle under every few columns of a wide body, and their exhaust
+    Part { id: Cow::Borrowed("engine_cluster_twin"), name: Cow::Borrowed("Twin engine cluster"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("\\_/ \\_/"), type_: PartType::Engine, selection_weight: 8, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_cluster_twin_gimbaled"), name: Cow::Borrowed("Gimbaled twin cluster"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("/_/ /_/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume_twin"), name: Cow::Borrowed("Twin plumes"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed("( ) ( )"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_flicker_twin"), name: Cow::Borrowed("Twin flickers"), top_width: 5, bottom_width: 5, height: 1, shape: Cow::Borrowed(" '   ' "), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_smoke_twin"), name: Cow::Borrowed("Twin smoke"), top_width: 5, bottom_width: 0, height: 1, shape: Cow::Borrowed(" .   . "), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_cluster_triple"), name: Cow::Borrowed("Triple engine cluster"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("\\_/\\_/\\_/"), type_: PartType::Engine, selection_weight: 8, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("engine_cluster_triple_gimbaled"), name: Cow::Borrowed("Gimbaled triple cluster"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("/_//_//_/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_plume_triple"), name: Cow::Borrowed("Triple plumes"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed("( )( )( )"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_flicker_triple"), name: Cow::Borrowed("Triple flickers"), top_width: 7, bottom_width: 7, height: 1, shape: Cow::Borrowed(" '  '  ' "), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+    Part { id: Cow::Borrowed("exhaust_smoke_triple"), name: Cow::Borrowed("Triple smoke"), top_width: 7, bottom_width: 0, height: 1, shape: Cow::Borrowed(" .  .  . "), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Common, feature: None },
+];
+
+/// Parts that are never picked by weight, only rolled for with `--loot`, see [`Rarity`].
+pub const LOOT_PARTS: [Part; 8] = [
+    Part { id: Cow::Borrowed("tip_beacon"), name: Cow::Borrowed("Beacon"), top_width: 0, bottom_width: 0, height: 1, shape: Cow::Borrowed("*"), type_: PartType::Tip, selection_weight: 1, rarity: Rarity::Uncommon, feature: None },
+    Part { id: Cow::Borrowed("body_stripes"), name: Cow::Borrowed("Racing stripes"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│═══│"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Uncommon, feature: None },
+    Part { id: Cow::Borrowed("body_hatch"), name: Cow::Borrowed("Cargo hatch"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│[ ]│"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Uncommon, feature: None },
+    Part { id: Cow::Borrowed("body_viewport"), name: Cow::Borrowed("Observation deck"), top_width: 3, bottom_width: 3, height: 1, shape: Cow::Borrowed("│(O)│"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Rare, feature: None },
+    Part { id: Cow::Borrowed("engine_twin"), name: Cow::Borrowed("Twin nozzles"), top_width: 1, bottom_width: 1, height: 1, shape: Cow::Borrowed("\\v/"), type_: PartType::Engine, selection_weight: 1, rarity: Rarity::Rare, feature: None },
+    Part { id: Cow::Borrowed("exhaust_flame"), name: Cow::Borrowed("Blue flame"), top_width: 1, bottom_width: 0, height: 1, shape: Cow::Borrowed("{ }"), type_: PartType::Exhaust, selection_weight: 1, rarity: Rarity::Rare, feature: None },
+    Part { id: Cow::Borrowed("nose_golden_fairing"), name: Cow::Borrowed("Golden Fairing"), top_width: 0, bottom_width: 3, height: 2, shape: Cow::Borrowed("/$\\\n/$$$\\"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Legendary, feature: Some(Feature::FairingNose) },
+    Part { id: Cow::Borrowed("body_warp_core"), name: Cow::Borrowed("Warp Core"), top_width: 3, bottom_width: 3, height: 2, shape: Cow::Borrowed("│▓█▓│\n│▓█▓│"), type_: PartType::Body, selection_weight: 1, rarity: Rarity::Legendary, feature: None },
+];
+
+// Built in parts are held to the same geometry rules as part packs, see `pack::validate`, and the
+// build fails if one of them breaks them
+const _: () = check_parts(&PARTS_BIN);
+const _: () = check_parts(&CHAT_PARTS);
+const _: () = check_parts(&WIDE_PARTS);
+const _: () = check_parts(&LOOT_PARTS);
+
+const fn check_parts(parts: &[Part]) {
+    let mut i = 0;
+    while i < parts.len() {
+        check_part(&parts[i]);
+        i += 1;
+    }
+}
+
+const fn check_part(part: &Part) {
+    let bytes = match &part.shape {
+        Cow::Borrowed(shape) => shape.as_bytes(),
+        Cow::Owned(shape) => shape.as_bytes(),
+    };
+    let widest = if part.top_width > part.bottom_width { part.top_width } else { part.bottom_width };
+    let allowed = if widest == 0 { MIN_WIDTH } else { widest + 2 };
+    assert!(part.selection_weight > 0, "a built in part has a selection_weight of 0");
+    let mut lines = 0;
+    let (mut first, mut last) = (0, 0);
+    let mut i = 0;
+    while i <= bytes.len() {
+        let start = i;
+        while i < bytes.len() && bytes[i] != b'\n' {
+            i += 1;
+        }
+        let (width, hull) = measure(bytes, start, i);
+        assert!(width > 0, "a built in part has a blank line");
+        assert!(!width.is_multiple_of(2), "a built in part has a line of even width, which can't be centered");
+        assert!(hull <= allowed, "a built in part has a line wider than its top_width and bottom_width allow");
+        if lines == 0 {
+            first = hull;
+        }
+        last = hull;
+        lines += 1;
+        i += 1;
+    }
+    assert!(lines == part.height, "a built in part's shape doesn't have as many lines as its height");
+    if lines == 1 {
+        assert!(hull_fits(first, widest), "a built in part's only line doesn't match its wider end's width");
+    } else {
+        assert!(hull_fits(first, part.top_width), "a built in part's first line doesn't match its top_width");
+        assert!(hull_fits(last, part.bottom_width), "a built in part's last line doesn't match its bottom_width");
+    }
+}
+
+/// Whether a hull `hull` wide is what an end `width` wide is drawn as: two wider for its walls, or
+/// at most [`MIN_WIDTH`] for a width 0 end, a point, like a needle or a nozzle no wider than a width
+/// 1 hull. The first line of a part is its top end, the last its bottom, and a one line part goes by
+/// the wider of the two.
+pub(crate) const fn hull_fits(hull: usize, width: usize) -> bool {
+    if width == 0 { hull <= MIN_WIDTH } else { hull == width + 2 }
+}
+
+/// `line` flipped left to right, or `None` if it has characters without a mirror image.
+fn mirror_line(line: &str) -> Option<String> {
+    line.chars().rev()
+        .map(|ch| {
+            MIRRORED_GLYPHS.iter()
+                .find_map(|&(a, b)| if ch == a { Some(b) } else if ch == b { Some(a) } else { None })
+                .or_else(|| SYMMETRIC_GLYPHS.contains(ch).then_some(ch))
+        })
+        .collect()
+}
+
+/// Width in characters of the line `bytes[start..end]`, and the width of its hull. Fins and skirts
+/// can stick out past a hull's side walls, `│`, `║` or `|`, so the hull is measured wall to wall,
+/// or is the whole line if it hasn't got two walls.
+pub(crate) const fn measure(bytes: &[u8], start: usize, end: usize) -> (usize, usize) {
+    let mut width = 0;
+    let mut first_wall = None;
+    let mut last_wall = 0;
+    let mut i = start;
+    while i < end {
+        // Continuation bytes of UTF-8 don't start a new character
+        if bytes[i] & 0xC0 != 0x80 {
+            let wall = bytes[i] == b'|'
+                || (i + 2 < end && bytes[i] == 0xE2 && bytes[i + 1] == 0x94 && bytes[i + 2] == 0x82)
+                || (i + 2 < end && bytes[i] == 0xE2 && bytes[i + 1] == 0x95 && bytes[i + 2] == 0x91);
+            if wall {
+                if first_wall.is_none() {
+                    first_wall = Some(width);
+                }
+                last_wall = width;
             }
-            self.sections.push(part);
-            self.height += part.height;
-            self.bottom_width = part.bottom_width;
+            width += 1;
         }
+        i += 1;
+    }
+    match first_wall {
+        Some(first) if first < last_wall => (width, last_wall - first + 1),
+        _ => (width, width),
+    }
+}
+
+/// Every built in part, grouped by the pack it comes in.
+pub const PACKS: [(&str, &[Part]); 4] = [
+    ("builtin", &PARTS_BIN), ("chat", &CHAT_PARTS), ("wide", &WIDE_PARTS), ("loot", &LOOT_PARTS),
+];
+
+/// Narrowest rocket that can always be built: a width one body needs three columns.
+pub const MIN_WIDTH: usize = 3;
+
+/// Widest hull a rocket `max_height` lines tall grows to, so tall rockets come out proportioned
+/// instead of as pencils.
+pub fn hull_width_for(max_height: usize) -> usize {
+    match max_height {
+        0..=29 => 3,
+        30..=44 => 5,
+        _ => 7,
+    }
+}
 
-        fn prepend_section(&mut self, part: &'static Part) {
-            if part.height + self.height > self.max_height {
-                panic!("Cannot add part because it would make the rocket too tall")
+/// Restrictions on which parts a rocket can use.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Profile {
+    /// Every part in the parts bin
+    #[default]
+    Standard,
+    /// Only [`Part::chat_safe`] parts, for pasting into Discord, Slack or Matrix
+    Chat,
+}
+
+impl Profile {
+    /// The parts this profile allows for a rocket `max_height` lines tall, leaving out any wider
+    /// than `max_width`. Wide parts are only included for rockets tall enough to carry them, see
+    /// [`hull_width_for`].
+    pub fn parts(&self, max_height: usize, max_width: Option<usize>) -> Result<Vec<Arc<Part>>, String> {
+        let builtin = registry::builtin();
+        let parts = match self {
+            Profile::Standard => ["builtin", "wide"].iter().flat_map(|&pack| builtin.pack(pack).unwrap_or_default()).collect::<Vec<_>>(),
+            Profile::Chat => ["builtin", "chat", "wide"].iter().flat_map(|&pack| builtin.pack(pack).unwrap_or_default()).collect(),
+        };
+        let hull_width = hull_width_for(max_height);
+        self.select(parts.into_iter().filter(|p| max(p.top_width, p.bottom_width) <= hull_width), max_width)
+    }
+
+    /// The ones of `parts` this profile allows, leaving out any wider than `max_width`.
+    pub fn select<'a>(&self, parts: impl IntoIterator<Item = &'a Arc<Part>>, max_width: Option<usize>) -> Result<Vec<Arc<Part>>, String> {
+        if let Some(max_width) = max_width.filter(|&w| w < MIN_WIDTH) {
+            return Err(format!("Rockets need at least {} columns, not {}", MIN_WIDTH, max_width));
+        }
+        Ok(parts.into_iter()
+            .filter(|p| *self != Profile::Chat || p.chat_safe())
+            .filter(|p| max_width.is_none_or(|w| p.width() <= w))
+            .cloned()
+            .collect())
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Profile::Standard => write!(f, "standard"),
+            Profile::Chat => write!(f, "chat"),
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "standard" => Ok(Profile::Standard),
+            "chat" => Ok(Profile::Chat),
+            _ => Err(format!("Unknown profile \"{}\"", s)),
+        }
+    }
+}
+
+/// A look to aim for, favoring some parts over others by multiplying their selection weights.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Style {
+    /// Every part at its usual weight
+    #[default]
+    Plain,
+    /// Plain narrow bodies and pointy tips
+    Sleek,
+    /// Sections 3 wide or more
+    Chunky,
+    /// Portholes, markings and fins
+    Busy,
+}
+
+impl Style {
+    /// `parts` reweighted for this style. Parts whose weight doesn't change are shared, not copied.
+    pub fn apply(&self, parts: &[Arc<Part>]) -> Vec<Arc<Part>> {
+        reweighted(parts, |part| self.multiplier(part))
+    }
+
+    fn multiplier(&self, part: &Part) -> usize {
+        let favored = match self {
+            Style::Plain => false,
+            Style::Sleek => {
+                part.type_ == PartType::Tip
+                    || (part.type_ == PartType::Body && max(part.top_width, part.bottom_width) <= 1 && !part.decorated())
             }
-            self.sections.insert(0, part);
-            self.height += part.height;
+            Style::Chunky => max(part.top_width, part.bottom_width) >= 3,
+            Style::Busy => part.decorated(),
+        };
+        if favored { 4 } else { 1 }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Style::Plain => write!(f, "plain"),
+            Style::Sleek => write!(f, "sleek"),
+            Style::Chunky => write!(f, "chunky"),
+            Style::Busy => write!(f, "busy"),
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "plain" => Ok(Style::Plain),
+            "sleek" => Ok(Style::Sleek),
+            "chunky" => Ok(Style::Chunky),
+            "busy" => Ok(Style::Busy),
+            _ => Err(format!("Unknown style \"{}\"", s)),
+        }
+    }
+}
+
+/// What a rocket flies for, which steers what it's built from: the kind of nose it gets,
+/// the tags its parts keep away from, and the parts it favors, like a [`Style`] does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mission {
+    /// A capsule with windows and, wherever one fits, an escape tower
+    Crew,
+    /// A big payload fairing and no windows
+    Cargo,
+    /// Small bodies with antennas and solar panels, and no windows
+    Probe,
+}
+
+impl Mission {
+    /// The kind of nose the mission flies under, unless [`BuildOptions::nose`] asks for another.
+    pub fn nose(self) -> Nose {
+        match self {
+            Mission::Crew => Nose::Capsule,
+            Mission::Cargo => Nose::Fairing,
+            Mission::Probe => Nose::Probe,
         }
+    }
 
-        fn part_height_remaining(&self) -> usize {
-            self.max_height - self.height
+    /// Tags the mission leaves out, wherever a part without them can go in their place.
+    pub fn forbid_tags(self) -> &'static [Tag] {
+        match self {
+            Mission::Crew => &[],
+            Mission::Cargo | Mission::Probe => &[Tag::Porthole],
         }
+    }
 
-        fn build(&mut self) {
-            if self.max_height < 3 {
-                panic!("Cannot build a rocket shorter than 3 sections")
+    /// `parts` reweighted for this mission, as [`Style::apply`] does.
+    pub fn apply(self, parts: &[Arc<Part>]) -> Vec<Arc<Part>> {
+        reweighted(parts, |part| self.multiplier(part))
+    }
+
+    fn multiplier(self, part: &Part) -> usize {
+        let favored = match self {
+            Mission::Crew => part.has_tag(Tag::Porthole) || part.docking(),
+            Mission::Cargo => part.nose() == Some(Nose::Fairing) && part.bottom_width >= 3,
+            Mission::Probe => {
+                part.type_ == PartType::Tip || (part.type_ == PartType::Body && max(part.top_width, part.bottom_width) <= 1)
             }
-            let nose_cone = self.choose_next_part(&PARTS_BIN, &[PartType::BODY]);
-            self.append_section(nose_cone);
+        };
+        if favored { 4 } else { 1 }
+    }
+}
+
+impl fmt::Display for Mission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mission::Crew => write!(f, "crew"),
+            Mission::Cargo => write!(f, "cargo"),
+            Mission::Probe => write!(f, "probe"),
+        }
+    }
+}
+
+impl FromStr for Mission {
+    type Err = String;
 
-            let mut rng = rand::thread_rng();
-            let body_decor_ratio = rng.gen_range(0.2..0.4);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "crew" => Ok(Mission::Crew),
+            "cargo" => Ok(Mission::Cargo),
+            "probe" => Ok(Mission::Probe),
+            _ => Err(format!("Unknown mission \"{}\", expected crew, cargo or probe", s)),
+        }
+    }
+}
+
+/// Design choices shared by a family of rockets of different sizes, like Falcon 1, 9 and Heavy:
+/// the parts one of them was built from, favored when building the others. Bigger rockets use
+/// the same nose, engine and exhaust wherever they fit, and fall back on others where they don't.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Design {
+    parts: Vec<Arc<Part>>,
+}
 
-            // Add body or transition
-            while (self.part_height_remaining() as f32 / self.height as f32) > body_decor_ratio && self.part_height_remaining() > 3 {
-                let next_part = self.choose_next_part_buffer(&PARTS_BIN, &[PartType::BODY], 2);
-                self.append_section(next_part);
+impl Design {
+    /// How much more often the design's own parts are picked
+    const FAVOR: usize = 8;
+
+    /// The design of `rocket`.
+    pub fn of(rocket: &Rocket) -> Design {
+        let mut parts: Vec<Arc<Part>> = Vec::new();
+        for part in rocket.sections() {
+            if !parts.iter().any(|p| p.type_ == part.type_ && p.shape == part.shape) {
+                parts.push(Arc::clone(part));
             }
-            // Finish up and add engine
-            let engine_part = self.choose_next_part(&PARTS_BIN, &[PartType::ENGINE]);
-            self.append_section(engine_part);
+        }
+        Design { parts }
+    }
 
-            // Add decoration (exhaust or nose)
-            while self.part_height_remaining() > 0 {
-                let decoration_part = self.choose_next_part(&PARTS_BIN, &[PartType::TIP, PartType::EXHAUST]);
-                if decoration_part.type_ == PartType::TIP {
-                    self.prepend_section(decoration_part);
-                } else {
-                    self.append_section(decoration_part);
-                }
+    /// `parts` reweighted to favor the design's own. Parts whose weight doesn't change are
+    /// shared, not copied.
+    pub fn apply(&self, parts: &[Arc<Part>]) -> Vec<Arc<Part>> {
+        parts.iter()
+            .map(|part| match self.parts.iter().any(|p| p.type_ == part.type_ && p.shape == part.shape) {
+                false => Arc::clone(part),
+                true => Arc::new(Part { selection_weight: part.selection_weight * Design::FAVOR, ..Part::clone(part) }),
+            })
+            .collect()
+    }
+}
+
+/// How a rocket's parts are put in order.
+#[derive(Clone, Debug, Default)]
+pub enum Generator {
+    /// Part by part from the nose down, each fitting under the last, until the rocket is tall enough
+    #[default]
+    Greedy,
+    /// Following a grammar's rules, which take the shape of the whole rocket into account
+    Grammar(Arc<Grammar>),
+    /// Following a model of how often each part followed another in example rockets
+    Learned(Arc<Model>),
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Generator::Greedy => write!(f, "greedy"),
+            Generator::Grammar(_) => write!(f, "grammar"),
+            Generator::Learned(_) => write!(f, "learned"),
+        }
+    }
+}
+
+impl FromStr for Generator {
+    type Err = String;
+
+    /// `grammar` stands for the built in grammar.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "greedy" => Ok(Generator::Greedy),
+            "grammar" => Ok(Generator::Grammar(Arc::new(Grammar::builtin()))),
+            _ => Err(format!("Unknown engine \"{}\"", s)),
+        }
+    }
+}
+
+/// Which parts a rocket is built from, how they're weighted and how they're put in order.
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    pub profile: Profile,
+    pub style: Style,
+    pub generator: Generator,
+    /// Leave out parts wider than this many columns
+    pub max_width: Option<usize>,
+    /// How much height to leave for tips and exhaust, as a share of the body's: 0 for as much
+    /// body as fits. Each rocket picks its own between 0.2 and 0.4 when unset. Grammars and learned
+    /// models ignore it and say how much decoration they want themselves.
+    pub decor_ratio: Option<f32>,
+    /// Parts to favor, for building the rest of a family of rockets like one already built
+    pub design: Option<Design>,
+    /// Whether each rocket keeps to one [`Look`], picked from its seed among the ones the parts
+    /// have noses for
+    pub coherent: bool,
+    /// Tags to keep to, leaving out the parts without them wherever a part with them can go in
+    /// their place
+    pub require_tags: Vec<Tag>,
+    /// Tags to leave out, wherever a part without them can go in their place
+    pub forbid_tags: Vec<Tag>,
+    /// Parts never to use. Each needs another part left of the same kind and widths, and no
+    /// taller, to go in its place.
+    pub exclude_parts: Vec<Arc<Part>>,
+    /// Parts every rocket has to use at least once
+    pub force_parts: Vec<Arc<Part>>,
+    /// Whether to add the `loot` pack's parts, which only the greedy engine rolls for
+    pub loot: bool,
+    /// Whether to put a launch escape tower above the nose, which only the greedy engine builds
+    pub escape_tower: bool,
+    /// Whether to spread solar panel wings from every rocket's upper stage, which only the greedy
+    /// engine builds. Rockets without it get them now and then
+    pub solar_panels: bool,
+    /// What kind of nose to build, which only the greedy engine picks by kind
+    pub nose: Option<Nose>,
+    /// What the rockets fly for, which only the greedy engine builds for. It picks the nose when
+    /// [`BuildOptions::nose`] doesn't, and adds its own tags to leave out and parts to favor
+    pub mission: Option<Mission>,
+}
+
+impl BuildOptions {
+    /// The parts these options allow for a rocket `max_height` lines tall, weighted for the style
+    /// and the design.
+    pub fn parts(&self, max_height: usize) -> Result<Vec<Arc<Part>>, String> {
+        if let Some(ratio) = self.decor_ratio.filter(|r| !(r.is_finite() && *r >= 0.0)) {
+            return Err(format!("The decor ratio must be a number 0 or more, not {}", ratio));
+        }
+        let mut parts = self.profile.parts(max_height, self.max_width)?;
+        if self.loot {
+            if !matches!(self.generator, Generator::Greedy) {
+                return Err("Loot is only rolled for by the greedy engine".to_string());
+            }
+            let hull_width = hull_width_for(max_height);
+            let loot = registry::builtin().pack("loot").unwrap_or_default().iter()
+                .filter(|p| max(p.top_width, p.bottom_width) <= hull_width);
+            parts.extend(self.profile.select(loot, self.max_width)?);
+        }
+        if self.mission.is_some() && !matches!(self.generator, Generator::Greedy) {
+            return Err("Missions are only flown by the greedy engine".to_string());
+        }
+        if self.escape_tower {
+            if !matches!(self.generator, Generator::Greedy) {
+                return Err("Escape towers are only built by the greedy engine".to_string());
+            }
+            // Leaving room under it for a nose, a body and an engine
+            if !parts.iter().any(|p| p.escape_tower() && p.height + 3 <= max_height) {
+                return Err(format!("No escape tower fits on a rocket {} lines tall", max_height));
+            }
+        } else if self.mission != Some(Mission::Crew) {
+            parts.retain(|p| !p.escape_tower());
+        }
+        if self.solar_panels {
+            if !matches!(self.generator, Generator::Greedy) {
+                return Err("Solar panels are only built by the greedy engine".to_string());
+            }
+            if !parts.iter().any(|p| p.solar_panels()) {
+                return Err(format!("No solar panels fit on a rocket {} lines tall", max_height));
+            }
+        }
+        if let Some(nose) = self.nose {
+            if !matches!(self.generator, Generator::Greedy) {
+                return Err("Noses are only picked by kind by the greedy engine".to_string());
+            }
+            // Leaving room under it for an engine
+            let fits = |p: &Arc<Part>| p.type_ == PartType::Body && p.height < max_height && (nose == Nose::Random || p.nose() == Some(nose));
+            if !parts.iter().any(fits) {
+                return Err(format!("No {} nose fits on a rocket {} lines tall", nose, max_height));
+            }
+        }
+        for excluded in &self.exclude_parts {
+            parts.retain(|p| !p.same_as(excluded));
+            let replaceable = parts.iter().any(|p| {
+                p.type_ == excluded.type_ && p.top_width == excluded.top_width && p.bottom_width == excluded.bottom_width
+                    && p.height <= excluded.height
+            });
+            if !replaceable {
+                return Err(format!("Nothing else can go in the place of {}, so it can't be left out", excluded.id));
+            }
+        }
+        for forced in &self.force_parts {
+            if !parts.iter().any(|p| p.same_as(forced)) {
+                return Err(format!("{} isn't one of the parts for rockets {} lines tall", forced.id, max_height));
             }
         }
+        for &tag in &self.require_tags {
+            parts = prefer(parts, |p| p.has_tag(tag));
+        }
+        let mission_tags = self.mission.map_or(&[][..], Mission::forbid_tags);
+        for &tag in self.forbid_tags.iter().chain(mission_tags) {
+            parts = prefer(parts, |p| !p.has_tag(tag));
+        }
+        let mut parts = self.style.apply(&parts);
+        if let Some(mission) = self.mission {
+            parts = mission.apply(&parts);
+        }
+        Ok(match &self.design {
+            Some(design) => design.apply(&parts),
+            None => parts,
+        })
+    }
+}
+
+/// How many rockets to try building before giving up on fitting in every forced part
+const FORCE_ATTEMPTS: u64 = 1000;
+
+/// Lines of rocket a tip taller than 2 lines needs for each of its own, so booms stay in proportion
+const TIP_SCALE: usize = 4;
+
+/// One in this many rockets has landing legs stowed at the bottom of its hull
+const LEG_ODDS: u64 = 4;
+
+/// One in this many rockets with a plain hull on its upper stage spreads solar panels from it
+const PANEL_ODDS: u64 = 6;
+
+const NAME_ADJECTIVES: [&str; 16] = [
+    "Bold", "Bright", "Crimson", "Distant", "Golden", "Silent", "Swift", "Lucky",
+    "Iron", "Silver", "Wandering", "Brave", "Restless", "Quiet", "Burning", "Patient",
+];
+const NAME_NOUNS: [&str; 16] = [
+    "Falcon", "Comet", "Voyager", "Pioneer", "Sparrow", "Horizon", "Meteor", "Pilgrim",
+    "Nomad", "Orbit", "Phoenix", "Lantern", "Albatross", "Beacon", "Zephyr", "Mariner",
+];
 
-        fn choose_next_part_buffer(&self, parts_list: &'static[Part], part_types: &'static[PartType], height_buffer: usize) -> &'static Part {
-            let mut rng = rand::thread_rng();
-            let possible_parts = parts_list.iter().filter(|p| {
-                part_types.contains(&p.type_)
-                    && p.top_width == self.bottom_width
-                    && p.height <= (self.part_height_remaining() - height_buffer)
-            }).collect::<Vec<&'static Part>>();
-            let dist = WeightedIndex::new(possible_parts.iter()
-                .map(|x| x.selection_weight)).unwrap();
+pub struct Rocket {
+    pub max_height: usize,
+    /// Seed every random choice was drawn from, building again with it gives the same rocket.
+    /// `None` for rockets built from a caller's own random number generator.
+    pub seed: Option<u64>,
 
-            possible_parts[dist.sample(&mut rng)]
+    /// Parts to choose from while building
+    parts: Vec<Arc<Part>>,
+    decor_ratio: Option<f32>,
+    escape_tower: bool,
+    nose: Option<Nose>,
+    solar_panels: bool,
+    sections: Vec<Arc<Part>>,
+    height: usize,
+    bottom_width: usize,
+}
+
+impl Default for Rocket {
+    fn default() -> Self {
+        Rocket { max_height: 3, seed: None, parts: registry::builtin().pack("builtin").unwrap_or_default().to_vec(), decor_ratio: None, escape_tower: false, nose: None, solar_panels: false, sections: Vec::new(), height: 0, bottom_width: 0 }
+    }
+}
+
+impl Rocket {
+    /// Builds a rocket from a random seed.
+    #[cfg(feature = "entropy")]
+    pub fn new(max_height: usize) -> Rocket {
+        Rocket::with_seed(max_height, rand::thread_rng().gen())
+    }
+
+    /// Builds the rocket for `seed`.
+    ///
+    /// Seeds are stable: a seed and height build the same rocket on every platform, and keep
+    /// building it across releases of ship_gen and of rand. Choices are drawn from ChaCha8, whose
+    /// output is fixed by its specification, without going through rand's distributions. Changes
+    /// to the parts bin or to how parts are picked that would give a seed a different rocket only
+    /// come with a new major version.
+    pub fn with_seed(max_height: usize, seed: u64) -> Rocket {
+        let parts = Profile::Standard.parts(max_height, None).expect("there's no max_width to be too narrow");
+        Rocket::with_parts(max_height, seed, parts)
+    }
+
+    /// Builds a rocket from `seed` using only `parts`, such as the ones a [`Profile`] allows.
+    pub fn with_parts(max_height: usize, seed: u64, parts: Vec<Arc<Part>>) -> Rocket {
+        Rocket::seeded(max_height, seed, parts, &BuildOptions::default())
+    }
+
+    /// Builds the rocket for `seed` from the parts `options` allow. Unlike the other constructors
+    /// it reports a height below 3 or options no rocket can be built with instead of panicking.
+    pub fn build_with(max_height: usize, seed: u64, options: &BuildOptions) -> Result<Rocket, String> {
+        check_height(max_height)?;
+        Rocket::build_from(max_height, seed, options.parts(max_height)?, options)
+    }
+
+    /// Builds the rocket for `seed` from `parts`, in an order `grammar` allows.
+    pub fn with_grammar(max_height: usize, seed: u64, parts: Vec<Arc<Part>>, grammar: &Grammar) -> Result<Rocket, String> {
+        let sections = grammar.build(max_height, &parts, &mut ChaCha8Rng::seed_from_u64(seed))?;
+        let bottom_width = sections.last().map_or(0, |p| p.bottom_width);
+        Ok(Rocket { max_height, seed: Some(seed), parts, decor_ratio: None, escape_tower: false, nose: None, solar_panels: false, sections, height: max_height, bottom_width })
+    }
+
+    /// Builds the rocket for `seed` from `parts`, in the style of the examples `model` learned from.
+    pub fn with_model(max_height: usize, seed: u64, parts: Vec<Arc<Part>>, model: &Model) -> Result<Rocket, String> {
+        let sections = model.build(max_height, &parts, &mut ChaCha8Rng::seed_from_u64(seed))?;
+        let bottom_width = sections.last().map_or(0, |p| p.bottom_width);
+        Ok(Rocket { max_height, seed: Some(seed), parts, decor_ratio: None, escape_tower: false, nose: None, solar_panels: false, sections, height: max_height, bottom_width })
+    }
+
+    /// A rocket of `sections` as they are, for putting rockets together by hand. It's as tall as
+    /// they are, but may not pass [`Rocket::validate`].
+    pub fn assembled(sections: Vec<Arc<Part>>) -> Rocket {
+        let height = sections.iter().map(|p| p.height).sum();
+        let bottom_width = sections.last().map_or(0, |p| p.bottom_width);
+        Rocket { max_height: height, seed: None, parts: sections.clone(), decor_ratio: None, escape_tower: false, nose: None, solar_panels: false, sections, height, bottom_width }
+    }
+
+    /// Builds a rocket from `parts`, drawing every choice from `rng`. Handy for tests and for
+    /// sources of randomness other than a seed.
+    pub fn with_rng<R: Rng + ?Sized>(max_height: usize, parts: Vec<Arc<Part>>, rng: &mut R) -> Rocket {
+        let mut rocket = Rocket { max_height, seed: None, parts, decor_ratio: None, escape_tower: false, nose: None, solar_panels: false, sections: Vec::new(), height: 0, bottom_width: 0 };
+        rocket.build(rng);
+        rocket
+    }
+
+    /// Builds a rocket for each of `seeds`, in order. They're the same rockets
+    /// [`Rocket::build_with`] builds one at a time, but with the `parallel` feature they're built
+    /// across every core, which pays off for fleets of thousands.
+    pub fn fleet(max_height: usize, seeds: &[u64], options: &BuildOptions) -> Result<Vec<Rocket>, String> {
+        check_height(max_height)?;
+        let parts = options.parts(max_height)?;
+        let build = |&seed: &u64| Rocket::build_from(max_height, seed, parts.clone(), options);
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            seeds.par_iter().map(build).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            seeds.iter().map(build).collect()
         }
+    }
 
-        fn choose_next_part(&self, parts_list: &'static[Part], part_types: &'static[PartType])-> &'static Part {
-            self.choose_next_part_buffer(parts_list, part_types, 0)
+    /// Builds the rocket for `seed`, or with --force-part like parts or --solar-panels, the first
+    /// rocket with all of them from seeds `seed` leads on to. Its seed is the one it was built
+    /// from, so it can be built again without them.
+    fn build_from(max_height: usize, seed: u64, parts: Vec<Arc<Part>>, options: &BuildOptions) -> Result<Rocket, String> {
+        if options.force_parts.is_empty() && !options.solar_panels {
+            return Rocket::build_once(max_height, seed, parts, options);
+        }
+        for attempt in 0..FORCE_ATTEMPTS {
+            let seed = seed.wrapping_add(attempt.wrapping_mul(0x9e3779b97f4a7c15));
+            let rocket = Rocket::build_once(max_height, seed, parts.clone(), options)?;
+            let panels = !options.solar_panels || rocket.sections.iter().any(|p| p.solar_panels());
+            if panels && options.force_parts.iter().all(|forced| rocket.sections.iter().any(|p| p.same_as(forced))) {
+                return Ok(rocket);
+            }
         }
+        if options.force_parts.is_empty() {
+            return Err(format!("No rocket {} lines tall had room for solar panels in {} tries", max_height, FORCE_ATTEMPTS));
+        }
+        Err(format!("No rocket {} lines tall with every forced part turned up in {} tries", max_height, FORCE_ATTEMPTS))
+    }
+
+    fn build_once(max_height: usize, seed: u64, parts: Vec<Arc<Part>>, options: &BuildOptions) -> Result<Rocket, String> {
+        let parts = if options.coherent { coherent(parts, seed) } else { parts };
+        match &options.generator {
+            Generator::Greedy => Ok(Rocket::seeded(max_height, seed, parts, options)),
+            Generator::Grammar(grammar) => Rocket::with_grammar(max_height, seed, parts, grammar),
+            Generator::Learned(model) => Rocket::with_model(max_height, seed, parts, model),
+        }
+    }
+
+    fn seeded(max_height: usize, seed: u64, parts: Vec<Arc<Part>>, options: &BuildOptions) -> Rocket {
+        let nose = options.nose.or(options.mission.map(Mission::nose)).map(|nose| nose.resolve(&parts, seed));
+        // Crews get an escape tower wherever one fits, leaving room under it for a nose, a body
+        // and an engine, and probes solar panels wherever there's a hull to spread them from
+        let crew_tower = options.mission == Some(Mission::Crew) && parts.iter().any(|p| p.escape_tower() && p.height + 3 <= max_height);
+        let mut rocket = Rocket {
+            max_height, seed: Some(seed), parts, decor_ratio: options.decor_ratio, escape_tower: options.escape_tower || crew_tower,
+            nose, solar_panels: options.solar_panels || options.mission == Some(Mission::Probe), sections: Vec::new(), height: 0, bottom_width: 0,
+        };
+        rocket.build(&mut ChaCha8Rng::seed_from_u64(seed));
+        rocket
+    }
+
+    /// A name for the rocket picked from its seed, so the same seed always has the same name.
+    /// Rockets without a seed are named after their parts.
+    pub fn name(&self) -> String {
+        let seed = self.seed.unwrap_or_else(|| fnv1a(self.sections.iter().flat_map(|part| part.shape.bytes())));
+        let adjectives = NAME_ADJECTIVES.len() as u64;
+        let adjective = NAME_ADJECTIVES[(seed % adjectives) as usize];
+        let noun = NAME_NOUNS[(seed / adjectives % NAME_NOUNS.len() as u64) as usize];
+        format!("{} {}", adjective, noun)
+    }
+
+    /// The rocket's parts rarer than common, rarest first, each only once.
+    pub fn finds(&self) -> Vec<&Arc<Part>> {
+        let mut finds: Vec<&Arc<Part>> = Vec::new();
+        for part in self.sections.iter().filter(|p| p.rarity != Rarity::Common) {
+            if !finds.iter().any(|found| found.same_as(part)) {
+                finds.push(part);
+            }
+        }
+        finds.sort_by_key(|p| Reverse(p.rarity));
+        finds
+    }
+
+    /// The parts making up the rocket, nose first.
+    pub fn sections(&self) -> &[Arc<Part>] {
+        &self.sections
+    }
+
+    /// The line the bottom of the engine is on, counting down from the nose, where the rocket
+    /// would stand on a launch pad with its exhaust hanging below. Rockets without an engine
+    /// stand on their last line.
+    pub fn ground(&self) -> usize {
+        let lines = |sections: &[Arc<Part>]| sections.iter().map(|p| p.shape.lines().count()).sum::<usize>();
+        let engine = self.sections.iter().rposition(|p| p.type_ == PartType::Engine).map_or(self.sections.len(), |i| i + 1);
+        lines(&self.sections[..engine]).saturating_sub(1)
+    }
+
+    /// How good the rocket looks, from 0 to 1: the average of how many of its lines are
+    /// symmetric, how smoothly its body tapers (widening again after narrowing counts against
+    /// it), how close to a quarter of its body is decorated, and how close its exhaust is to a
+    /// tenth to a fifth of its height.
+    pub fn score(&self) -> f64 {
+        let lines: Vec<&str> = self.sections.iter().flat_map(|p| p.shape.lines()).collect();
+        let symmetry = lines.iter().filter(|&&line| mirror_line(line).is_some_and(|m| m == line)).count() as f64 / lines.len().max(1) as f64;
+
+        let mut bulges = 0;
+        let mut narrowed = false;
+        for part in self.sections.iter().filter(|p| p.type_ == PartType::Body && p.top_width > 0) {
+            if part.bottom_width < part.top_width {
+                narrowed = true;
+            } else if part.bottom_width > part.top_width && narrowed {
+                bulges += 1;
+                narrowed = false;
+            }
+        }
+        let taper = 1.0 / (1 + bulges) as f64;
+
+        let body_lines = |decorated: bool| -> usize {
+            self.sections.iter()
+                .filter(|p| p.type_ == PartType::Body && (!decorated || p.decorated()))
+                .map(|p| p.height)
+                .sum()
+        };
+        let decorated = body_lines(true) as f64 / body_lines(false).max(1) as f64;
+        let decoration = 1.0 - (decorated - 0.25).abs() / 0.75;
+
+        let exhaust = self.sections.iter().filter(|p| p.type_ == PartType::Exhaust).map(|p| p.height).sum::<usize>() as f64
+            / self.max_height.max(1) as f64;
+        let off = if exhaust < 0.1 { 0.1 - exhaust } else { (exhaust - 0.2).max(0.0) };
+        let proportion = (1.0 - off / 0.2).max(0.0);
+
+        (symmetry + taper + decoration + proportion) / 4.0
+    }
+
+    /// Checks that the rocket is put together properly: each part's top as wide as the bottom of
+    /// the one above, tips above the body, exactly one engine with only exhaust below it, and as
+    /// tall as asked. Every rocket the generator builds passes, so this is for tests and for
+    /// trying out new parts.
+    pub fn validate(&self) -> Result<(), String> {
+        let height: usize = self.sections.iter().map(|p| p.height).sum();
+        if height != self.max_height {
+            return Err(format!("Rocket is {} lines tall but was built to {}", height, self.max_height));
+        }
+        let engines = self.sections.iter().filter(|p| p.type_ == PartType::Engine).count();
+        if engines != 1 {
+            return Err(format!("Rocket has {} engines instead of one", engines));
+        }
+        let engine = self.sections.iter().position(|p| p.type_ == PartType::Engine).unwrap_or(0);
+        let tips = self.sections.iter().take_while(|p| p.type_ == PartType::Tip).count();
+        for (i, part) in self.sections.iter().enumerate() {
+            let expected = if i < tips {
+                PartType::Tip
+            } else if i < engine {
+                PartType::Body
+            } else if i == engine {
+                PartType::Engine
+            } else {
+                PartType::Exhaust
+            };
+            if part.type_ != expected {
+                return Err(format!("Section {} is a {} where a {} belongs", i, part.type_, expected));
+            }
+        }
+        for (i, pair) in self.sections.windows(2).enumerate() {
+            if pair[0].bottom_width != pair[1].top_width {
+                return Err(format!("Section {} is {} wide at the bottom but section {} is {} wide at the top",
+                                   i, pair[0].bottom_width, i + 1, pair[1].top_width));
+            }
+        }
+        Ok(())
+    }
+
+    fn append_section(&mut self, part: Arc<Part>) {
+        if part.height + self.height > self.max_height {
+            panic!("Cannot add part because it would make the rocket too tall")
+        }
+        self.height += part.height;
+        self.bottom_width = part.bottom_width;
+        self.sections.push(part);
+    }
+
+    fn prepend_section(&mut self, part: Arc<Part>) {
+        if part.height + self.height > self.max_height {
+            panic!("Cannot add part because it would make the rocket too tall")
+        }
+        self.height += part.height;
+        self.sections.insert(0, part);
+    }
+
+    fn part_height_remaining(&self) -> usize {
+        self.max_height - self.height
+    }
+
+    fn build<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.max_height < 3 {
+            panic!("Cannot build a rocket shorter than 3 sections")
+        }
+        // An escape tower gets the very top to itself, above the nose and any tip
+        if self.escape_tower {
+            let tower = self.choose_next_part_buffer(rng, &[PartType::Tip], 3);
+            self.append_section(tower);
+        }
+        // The nose, of the kind asked for if there's one that fits
+        let nose_cone = self.choose_next_part(rng, &[PartType::Body]);
+        self.append_section(nose_cone);
+
+        // Between 0.2 and 0.4, from the top 24 bits so every one of them fits in an f32 exactly.
+        // Drawn even when the ratio is fixed, so the choices after it don't change
+        let drawn_ratio = 0.2 + 0.2 * (rng.next_u32() >> 8) as f32 / (1 << 24) as f32;
+        let body_decor_ratio = self.decor_ratio.unwrap_or(drawn_ratio);
+
+        // Add body or transition
+        while (self.part_height_remaining() as f32 / self.height as f32) > body_decor_ratio && self.part_height_remaining() > 3 {
+            let next_part = self.choose_next_part_buffer(rng, &[PartType::Body], 2);
+            self.append_section(next_part);
+        }
+        // Now and then the last of the hull carries landing legs stowed along it, drawn for only
+        // when there are legs that fit in its place
+        if let Some(last) = self.sections.last().filter(|p| !p.stowed_legs()).cloned() {
+            let legs: Vec<&Arc<Part>> = self.parts.iter()
+                .filter(|p| {
+                    p.stowed_legs() && p.top_width == last.top_width && p.bottom_width == last.bottom_width
+                        && p.height == last.height
+                })
+                .collect();
+            if !legs.is_empty() && rng.next_u64().is_multiple_of(LEG_ODDS) {
+                let legs = Arc::clone(legs[(rng.next_u64() % legs.len() as u64) as usize]);
+                *self.sections.last_mut().expect("the nose is always there") = legs;
+            }
+        }
+        // Finish up and add engine
+        let engine_part = self.choose_next_part(rng, &[PartType::Engine]);
+        self.append_section(engine_part);
+
+        // Add decoration (exhaust or nose), only ever exhaust under an escape tower
+        let decorations: &'static [PartType] = if self.escape_tower { &[PartType::Exhaust] } else { &[PartType::Tip, PartType::Exhaust] };
+        while self.part_height_remaining() > 0 {
+            let decoration_part = self.choose_next_part(rng, decorations);
+            if decoration_part.type_ == PartType::Tip {
+                self.prepend_section(decoration_part);
+            } else {
+                self.append_section(decoration_part);
+            }
+        }
+        // Now and then, or always when asked for, a plain hull on the upper stage, above where it
+        // first widens out to a booster, spreads solar panels. Drawn for last, so the rest of the
+        // rocket is the same either way
+        let upper_stage = self.sections.iter()
+            .position(|p| p.top_width != 0 && p.bottom_width > p.top_width)
+            .unwrap_or(self.sections.len());
+        let slots: Vec<(usize, Vec<Arc<Part>>)> = self.sections[..upper_stage].iter().enumerate()
+            .filter(|(_, p)| p.type_ == PartType::Body && p.top_width == p.bottom_width && !p.decorated())
+            .map(|(i, hull)| {
+                let panels = self.parts.iter()
+                    .filter(|p| p.solar_panels() && p.top_width == hull.top_width && p.height == hull.height)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (i, panels)
+            })
+            .filter(|(_, panels)| !panels.is_empty())
+            .collect();
+        if !slots.is_empty() && (self.solar_panels || rng.next_u64().is_multiple_of(PANEL_ODDS)) {
+            let (i, panels) = &slots[(rng.next_u64() % slots.len() as u64) as usize];
+            self.sections[*i] = Arc::clone(&panels[(rng.next_u64() % panels.len() as u64) as usize]);
+        }
+    }
+
+    fn choose_next_part_buffer<R: Rng + ?Sized>(&self, rng: &mut R, part_types: &'static[PartType], height_buffer: usize) -> Arc<Part> {
+        let mut possible_parts = self.parts.iter().filter(|p| {
+            part_types.contains(&p.type_)
+                && p.top_width == self.bottom_width
+                && p.height <= (self.part_height_remaining() - height_buffer)
+        }).collect::<Vec<&Arc<Part>>>();
+        // Bodies short of the hull width the rocket is built to don't narrow, unless nothing else fits
+        let widening = |p: &&Arc<Part>| p.bottom_width >= p.top_width;
+        if part_types == [PartType::Body] && self.bottom_width < hull_width_for(self.max_height) && possible_parts.iter().any(widening) {
+            possible_parts.retain(widening);
+        }
+        // Escape towers are the first pick of rockets built with one, and never a tip
+        if part_types.contains(&PartType::Tip) {
+            let tower_slot = self.escape_tower && self.sections.is_empty();
+            possible_parts.retain(|p| p.escape_tower() == tower_slot);
+        }
+        // Docking ports only sit straight on a capsule nose as wide as they are at its top, with
+        // nothing above them
+        if part_types.contains(&PartType::Tip) {
+            let top = self.sections.first();
+            let width = |p: &Part| p.shape.lines().next().map_or(0, |line| line.chars().count());
+            if top.is_some_and(|p| p.feature == Some(Feature::DockingPort)) && possible_parts.iter().any(|p| p.type_ != PartType::Tip) {
+                possible_parts.retain(|p| p.type_ != PartType::Tip);
+            }
+            let docks = |port: &Part| {
+                top.is_some_and(|nose| nose.type_ == PartType::Body && nose.nose() == Some(Nose::Capsule) && width(nose) == width(port))
+            };
+            possible_parts.retain(|p| p.feature != Some(Feature::DockingPort) || docks(p));
+        }
+        // Tall tips, like booms and whip antennas, only top rockets tall enough to carry them
+        let in_proportion = |p: &&Arc<Part>| {
+            p.type_ != PartType::Tip || p.escape_tower() || p.height <= 2 || p.height * TIP_SCALE <= self.max_height
+        };
+        if possible_parts.iter().any(in_proportion) {
+            possible_parts.retain(in_proportion);
+        }
+        // Noses, and the tips above them, keep to the kind of nose asked for wherever one fits
+        if let Some(nose) = self.nose {
+            let themed = |p: &&Arc<Part>| p.type_ == PartType::Exhaust || p.escape_tower() || p.nose() == Some(nose);
+            if self.bottom_width == 0 && possible_parts.iter().any(themed) {
+                possible_parts.retain(themed);
+            }
+        }
+        // Landing legs and solar panels take the place of a hull once it's built, see `build`
+        let swapped_in = |p: &&Arc<Part>| p.stowed_legs() || p.solar_panels();
+        if part_types == [PartType::Body] && possible_parts.iter().any(|p| !swapped_in(p)) {
+            possible_parts.retain(|p| !swapped_in(p));
+        }
+        // Docking collars only go on the upper stage, above where it first widens out to a booster
+        let upper_stage = !self.sections.iter().any(|p| p.top_width != 0 && p.bottom_width > p.top_width);
+        if part_types == [PartType::Body] && !upper_stage && possible_parts.iter().any(|p| !p.docking()) {
+            possible_parts.retain(|p| !p.docking());
+        }
+        // Grid fins only go at the top of a booster, under the nose or a shoulder widening to it
+        let booster_top = self.sections.last().is_some_and(|p| p.bottom_width > p.top_width);
+        if !booster_top && possible_parts.iter().any(|p| !p.grid_finned()) {
+            possible_parts.retain(|p| !p.grid_finned());
+        }
+        // Parts rarer than common are rolled for first, whatever their weights, and only when one
+        // fits, so rockets without any to choose from draw the same numbers they always have
+        if possible_parts.iter().any(|p| p.rarity != Rarity::Common) {
+            let roll = rng.next_u64() % 1000;
+            let mut odds = 0;
+            if let Some(rarity) = Rarity::ALL.into_iter().rev().find(|rarity| { odds += rarity.odds(); roll < odds }) {
+                let rolled: Vec<&&Arc<Part>> = possible_parts.iter().filter(|p| p.rarity == rarity).collect();
+                if !rolled.is_empty() {
+                    return Arc::clone(rolled[(rng.next_u64() % rolled.len() as u64) as usize]);
+                }
+            }
+            if possible_parts.iter().any(|p| p.rarity == Rarity::Common) {
+                possible_parts.retain(|p| p.rarity == Rarity::Common);
+            }
+        }
+        // Engines finish the body as wide as it is, a skirt or a cluster of them, rather than
+        // narrowing to a stub, when there's exhaust to carry on from one
+        let preserving = |p: &&Arc<Part>| {
+            p.bottom_width == p.top_width
+                && self.parts.iter().any(|e| e.type_ == PartType::Exhaust && e.top_width == p.bottom_width)
+        };
+        if part_types == [PartType::Engine] && possible_parts.iter().any(preserving) {
+            possible_parts.retain(preserving);
+        }
+        // Picked by hand rather than with rand's distributions, whose results can change between
+        // releases of rand, so seeds keep building the same rockets
+        let total: usize = possible_parts.iter().map(|p| p.selection_weight).sum();
+        if total == 0 {
+            panic!("No part fits below a section {} wide", self.bottom_width)
+        }
+        let mut pick = (rng.next_u64() % total as u64) as usize;
+        for part in possible_parts {
+            if pick < part.selection_weight {
+                return Arc::clone(part);
+            }
+            pick -= part.selection_weight;
+        }
+        unreachable!("the pick is less than the total weight")
+    }
+
+    fn choose_next_part<R: Rng + ?Sized>(&self, rng: &mut R, part_types: &'static[PartType])-> Arc<Part> {
+        self.choose_next_part_buffer(rng, part_types, 0)
     }
 
-    impl fmt::Display for Rocket {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let mut output = String::new();
-            let rocket_width = self.sections.iter()
-                .fold(0, |a, x| {
-                    let mut max_width = a;
-                    for line in x.shape.lines() {
-                        max_width = max(max_width, line.chars().count());
+    /// Lays the sections out on a character grid, each line centered on the widest one.
+    pub fn to_grid(&self) -> Grid {
+        let rocket_width = self.sections.iter()
+            .flat_map(|x| x.shape.lines())
+            .fold(0, |a, line| max(a, line.chars().count()));
+        let rocket_height = self.sections.iter()
+            .map(|x| x.shape.lines().count())
+            .sum();
+
+        let mut grid = Grid::new(rocket_width, rocket_height);
+        let mut y = 0;
+        // The columns of the grid the engine's nozzles are over and how wide it is at the bottom,
+        // once it's drawn, so exhaust lines up under the nozzles rather than the middle
+        let mut nozzles: Option<(Vec<usize>, usize)> = None;
+        // Exhaust from a gimbaled engine drifts a column further its way each line down
+        let mut lean = 0;
+        let mut drift = 0;
+        for section in &self.sections {
+            for line in section.shape.lines() {
+                let width = line.chars().count();
+                let spacing: usize = ((rocket_width - width) as f32 / 2.0).ceil() as usize;
+                let starts = match &nozzles {
+                    Some((columns, engine_width)) if section.type_ == PartType::Exhaust => {
+                        // Exhaust as wide as the engine puts out goes under all its nozzles, and
+                        // anything narrower trails from each one, all kept on the grid
+                        let centers = if section.top_width < *engine_width {
+                            columns.clone()
+                        } else {
+                            vec![(columns[0] + columns[columns.len() - 1]) / 2]
+                        };
+                        // The drift stops where the exhaust would run off the grid, so the
+                        // trails under a cluster don't bunch up against its edge
+                        let first = centers[0] as isize - (width / 2) as isize;
+                        let last = centers[centers.len() - 1] as isize - (width / 2) as isize;
+                        if lean != 0 {
+                            drift = (drift + lean).min((rocket_width - width) as isize - last).max(-first);
+                        }
+                        let room = (rocket_width - width) as isize;
+                        centers.iter().map(|&center| (center as isize - (width / 2) as isize + drift).clamp(0, room) as usize).collect()
+                    }
+                    _ => vec![spacing],
+                };
+                for start in starts {
+                    for (x, ch) in line.chars().enumerate() {
+                        grid.set(start + x, y, Cell { ch, part_type: Some(section.type_), ..Cell::BLANK });
                     }
-                    return max_width;
-                });
-            for section in &self.sections {
-                for line in section.shape.lines() {
-                    let spacing: usize = ((rocket_width - line.chars().count()) as f32 / 2.0).ceil() as usize;
-                    output.push_str(&" ".repeat(spacing));
-                    output.push_str(&line);
-                    output.push_str("\n");
+                }
+                y += 1;
+                if section.type_ == PartType::Engine {
+                    let columns = section.nozzle_columns().iter().map(|column| spacing + column).collect();
+                    nozzles = Some((columns, section.bottom_width));
+                    lean = section.lean();
                 }
             }
-            write!(f, "{}", output)
         }
+        grid
+    }
+}
+
+/// Whether `part` can go under `previous`, or at the top of a rocket: tips, then bodies, one
+/// engine, then exhaust, each as wide at the top as the one above is at the bottom.
+pub(crate) fn fits_under(previous: Option<&Part>, part: &Part) -> bool {
+    let Some(previous) = previous else {
+        return part.top_width() == 0 && matches!(part.part_type(), PartType::Tip | PartType::Body);
+    };
+    let order = matches!(
+        (previous.part_type(), part.part_type()),
+        (PartType::Tip, PartType::Tip | PartType::Body)
+            | (PartType::Body, PartType::Body | PartType::Engine)
+            | (PartType::Engine | PartType::Exhaust, PartType::Exhaust)
+    );
+    order && part.top_width() == previous.bottom_width()
+}
+
+fn check_height(max_height: usize) -> Result<(), String> {
+    if max_height < 3 {
+        return Err("Cannot build a rocket shorter than 3 sections".to_string());
+    }
+    Ok(())
+}
+
+/// Turns any text, like a user name, into a seed so it always gets the same rocket. The hash is
+/// FNV-1a, which won't change, so text seeds are as stable as numeric ones.
+pub fn seed_from_text(text: &str) -> u64 {
+    fnv1a(text.bytes())
+}
+
+/// The seed for the rocket of the day on `date`: the text seed of the date as `YYYY-MM-DD`, or of
+/// the date, a space and `scope` if there is one, like `2024-05-04 standup`. Anyone can work it
+/// out for themselves, and it's as stable as text seeds.
+pub fn daily_seed(date: Date, scope: Option<&str>) -> u64 {
+    match scope {
+        Some(scope) => seed_from_text(&format!("{} {}", date, scope)),
+        None => seed_from_text(&date.to_string()),
+    }
+}
+
+/// `parts` with each one's weight multiplied by what `multiplier` says for it. Parts whose weight
+/// doesn't change are shared, not copied.
+fn reweighted(parts: &[Arc<Part>], multiplier: impl Fn(&Part) -> usize) -> Vec<Arc<Part>> {
+    parts.iter()
+        .map(|part| match multiplier(part) {
+            1 => Arc::clone(part),
+            m => Arc::new(Part { selection_weight: part.selection_weight * m, ..Part::clone(part) }),
+        })
+        .collect()
+}
+
+/// `parts` without the ones `keep` turns down, except where none of the others can go in their
+/// place, so whatever rocket could be built from `parts` still can be.
+fn prefer(parts: Vec<Arc<Part>>, keep: impl Fn(&Part) -> bool) -> Vec<Arc<Part>> {
+    let same_place = |a: &Part, b: &Part| {
+        a.type_ == b.type_ && a.top_width == b.top_width && a.bottom_width == b.bottom_width && a.height == b.height
+    };
+    parts.iter()
+        .filter(|&part| keep(part) || !parts.iter().any(|other| keep(other) && same_place(other, part)))
+        .cloned()
+        .collect()
+}
+
+/// The ones of `parts` that go with the look `seed` picks, out of the ones there's a nose for.
+fn coherent(parts: Vec<Arc<Part>>, seed: u64) -> Vec<Arc<Part>> {
+    let looks: Vec<Look> = Look::ALL.into_iter()
+        .filter(|&look| parts.iter().any(|p| p.top_width == 0 && p.look() == Some(look)))
+        .collect();
+    if looks.is_empty() {
+        return parts;
+    }
+    // Hashed, so fleets seeded counting up don't simply alternate
+    let look = looks[(fnv1a(seed.to_le_bytes().into_iter()) % looks.len() as u64) as usize];
+    parts.into_iter().filter(|p| p.look().is_none_or(|l| l == look)).collect()
+}
+
+fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf29ce484222325, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+impl fmt::Display for Rocket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_grid())
     }
 }
@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::render::{self, Format, Options};
+use crate::rocket::Rocket;
+
+/// Tallest rocket a request may ask for, so one request can't tie up the server.
+const MAX_HEIGHT: usize = 500;
+const DEFAULT_HEIGHT: usize = 20;
+/// Connections handled at once, the rest waiting their turn to be accepted
+const WORKERS: usize = 8;
+/// Longest request line or header line read, in bytes
+const MAX_LINE: usize = 8 * 1024;
+/// Most header lines read from a request
+const MAX_HEADERS: usize = 100;
+/// How long a connection may go without sending or taking anything before it's dropped
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves `GET /rocket` on `addr` until the process is stopped, handing connections to a fixed
+/// pool of [`WORKERS`] threads. `defaults` supplies the palette, renderer and coloring for
+/// requests that don't pick their own.
+pub fn serve(addr: &str, defaults: &Options) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Serving rockets on http://{}/rocket", listener.local_addr()?);
+    // No more connections wait in the queue than there are workers, so a flood of them backs up in
+    // the listener instead of in memory
+    let (sender, receiver) = mpsc::sync_channel(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let (receiver, defaults) = (Arc::clone(&receiver), defaults.clone());
+        thread::spawn(move || work(&receiver, &defaults));
+    }
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if sender.send(stream).is_err() {
+                    break;
+                }
+            }
+            Err(e) => eprintln!("error: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Handles connections from `receiver` one after another until it's closed.
+fn work(receiver: &Mutex<Receiver<TcpStream>>, defaults: &Options) {
+    loop {
+        // The lock is only held while waiting for the next connection, not while handling it
+        let stream = match receiver.lock().map(|receiver| receiver.recv()) {
+            Ok(Ok(stream)) => stream,
+            _ => return,
+        };
+        if let Err(e) = handle(stream, defaults) {
+            eprintln!("error: {}", e);
+        }
+    }
+}
+
+fn handle(stream: TcpStream, defaults: &Options) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let Some(request_line) = read_line(&mut reader)? else {
+        return Response::text("414 URI Too Long", format!("Request lines are at most {} bytes\n", MAX_LINE)).write(&stream);
+    };
+    let mut accept = String::new();
+    for count in 0.. {
+        if count == MAX_HEADERS {
+            return Response::text("431 Request Header Fields Too Large", format!("Requests have at most {} headers\n", MAX_HEADERS)).write(&stream);
+        }
+        let Some(header) = read_line(&mut reader)? else {
+            return Response::text("431 Request Header Fields Too Large", format!("Headers are at most {} bytes\n", MAX_LINE)).write(&stream);
+        };
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("accept") {
+                accept = value.trim().to_lowercase();
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let response = match (method, path) {
+        ("GET", "/rocket") => {
+            let json = accept.contains("application/json");
+            match rocket(query, json, defaults) {
+                Ok((seed, body)) => {
+                    let content_type = if json { "application/json" } else { "text/plain; charset=utf-8" };
+                    Response { status: "200 OK", content_type, seed, body }
+                }
+                Err(e) => Response::text("400 Bad Request", format!("{}\n", e)),
+            }
+        }
+        (_, "/rocket") => Response::text("405 Method Not Allowed", "Only GET is supported\n".to_string()),
+        _ => Response::text("404 Not Found", "Try GET /rocket?height=20\n".to_string()),
+    };
+    response.write(&stream)
+}
+
+/// The next line from `reader`, empty at the end of the stream, or `None` if it's longer than
+/// [`MAX_LINE`].
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64 + 1).read_line(&mut line)?;
+    Ok((line.len() <= MAX_LINE).then_some(line))
+}
+
+/// Builds and renders the rocket a query string asks for, returning its seed and the art.
+fn rocket(query: &str, json: bool, defaults: &Options) -> Result<(Option<u64>, Vec<u8>), Box<dyn Error>> {
+    let mut options = Options { format: if json { Format::Json } else { Format::Text }, ..defaults.clone() };
+    let mut height = DEFAULT_HEIGHT;
+    let mut seed = None;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode(value);
+        match key {
+            "height" => height = value.parse().map_err(|_| format!("Invalid height \"{}\"", value))?,
+            "seed" => seed = Some(value.parse().map_err(|_| format!("Invalid seed \"{}\"", value))?),
+            "palette" => options.palette = value.parse()?,
+            "render" => options.renderer = value.parse()?,
+            "color" => options.color = matches!(value.as_str(), "" | "1" | "true" | "yes"),
+            _ => return Err(format!("Unknown parameter \"{}\"", key).into()),
+        }
+    }
+    if !(3..=MAX_HEIGHT).contains(&height) {
+        return Err(format!("Height must be between 3 and {}", MAX_HEIGHT).into());
+    }
+
+    let rocket = match seed {
+        Some(seed) => Rocket::with_seed(height, seed),
+        None => Rocket::new(height),
+    };
+    let mut body = Vec::new();
+    render::write(&rocket, &options, &mut body)?;
+    Ok((rocket.seed, body))
+}
+
+/// Percent-decodes a query string value, treating `+` as a space.
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => match (hex_digit(bytes[i + 1]), hex_digit(bytes[i + 2])) {
+                (Some(high), Some(low)) => {
+                    decoded.push(high << 4 | low);
+                    i += 2;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    seed: Option<u64>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, body: String) -> Response {
+        Response { status, content_type: "text/plain; charset=utf-8", seed: None, body: body.into_bytes() }
+    }
+
+    fn write(&self, mut stream: &TcpStream) -> io::Result<()> {
+        write!(stream, "HTTP/1.1 {}\r\n", self.status)?;
+        write!(stream, "Content-Type: {}\r\n", self.content_type)?;
+        write!(stream, "Content-Length: {}\r\n", self.body.len())?;
+        if let Some(seed) = self.seed {
+            write!(stream, "X-Rocket-Seed: {}\r\n", seed)?;
+        }
+        write!(stream, "Connection: close\r\n\r\n")?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
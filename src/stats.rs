@@ -0,0 +1,208 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::json::Value;
+use crate::rocket::{Part, PartType, Rocket};
+
+/// Kilonewtons of thrust for each column of engine, per line of exhaust behind it plus one
+const THRUST_PER_COLUMN: usize = 250;
+/// Parts in fewer than this share of a fleet's rockets are called out as rare
+const RARE: f64 = 0.01;
+
+/// Numbers describing a rocket, for ranking and comparing them. Thrust and mass are made up from
+/// the parts' shapes, but bigger engines and longer exhaust do mean more thrust, and every
+/// character drawn above the engine is a tonne of mass.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stats {
+    /// Lines tall
+    pub height: usize,
+    /// Columns wide at the widest line
+    pub width: usize,
+    pub tips: usize,
+    pub bodies: usize,
+    pub engines: usize,
+    pub exhaust: usize,
+    /// Runs of body of the same width, between the parts that change it
+    pub stages: usize,
+    /// Kilonewtons
+    pub thrust: usize,
+    /// Tonnes
+    pub mass: usize,
+}
+
+impl Stats {
+    pub fn of(rocket: &Rocket) -> Stats {
+        let sections = rocket.sections();
+        let count = |part_type: PartType| sections.iter().filter(|p| p.part_type() == part_type).count();
+        let mut stages = 0;
+        let mut in_stage = false;
+        for part in sections.iter().filter(|p| p.part_type() == PartType::Body) {
+            let straight = part.top_width() > 0 && part.top_width() == part.bottom_width();
+            if straight && !in_stage {
+                stages += 1;
+            }
+            in_stage = straight;
+        }
+        let exhaust_lines: usize = sections.iter().filter(|p| p.part_type() == PartType::Exhaust).map(|p| p.height()).sum();
+        let engine_width: usize = sections.iter().filter(|p| p.part_type() == PartType::Engine).map(|p| p.top_width()).sum();
+        Stats {
+            height: sections.iter().map(|p| p.height()).sum(),
+            width: sections.iter().flat_map(|p| p.shape().lines()).map(|line| line.chars().count()).max().unwrap_or(0),
+            tips: count(PartType::Tip),
+            bodies: count(PartType::Body),
+            engines: count(PartType::Engine),
+            exhaust: count(PartType::Exhaust),
+            stages,
+            thrust: THRUST_PER_COLUMN * engine_width * (1 + exhaust_lines),
+            mass: sections.iter()
+                .filter(|p| matches!(p.part_type(), PartType::Tip | PartType::Body))
+                .flat_map(|p| p.shape().chars())
+                .filter(|ch| !ch.is_whitespace())
+                .count(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::object([
+            ("height", self.height.into()),
+            ("width", self.width.into()),
+            ("parts", Value::object([
+                ("tip", self.tips.into()),
+                ("body", self.bodies.into()),
+                ("engine", self.engines.into()),
+                ("exhaust", self.exhaust.into()),
+            ])),
+            ("stages", self.stages.into()),
+            ("thrust_kn", self.thrust.into()),
+            ("mass_t", self.mass.into()),
+        ])
+    }
+
+    /// The stats as a markdown table, a row for each of them.
+    pub fn to_markdown(&self) -> String {
+        let rows = [
+            ("Height", format!("{} lines", self.height)),
+            ("Width", format!("{} columns", self.width)),
+            ("Parts", format!("{} tip, {} body, {} engine, {} exhaust", self.tips, self.bodies, self.engines, self.exhaust)),
+            ("Stages", self.stages.to_string()),
+            ("Thrust", format!("{} kN", self.thrust)),
+            ("Mass", format!("{} t", self.mass)),
+        ];
+        let mut table = String::from("| Stat | Value |\n| --- | --- |\n");
+        for (stat, value) in rows {
+            table.push_str(&format!("| {} | {} |\n", stat, value));
+        }
+        table
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "height  {} lines", self.height)?;
+        writeln!(f, "width   {} columns", self.width)?;
+        writeln!(f, "parts   {} tip, {} body, {} engine, {} exhaust", self.tips, self.bodies, self.engines, self.exhaust)?;
+        writeln!(f, "stages  {}", self.stages)?;
+        writeln!(f, "thrust  {} kN", self.thrust)?;
+        write!(f, "mass    {} t", self.mass)
+    }
+}
+
+/// Totals for a whole fleet of rockets, for tuning the weights of a pack of parts: how tall and
+/// wide they came out, and how many of them each part was used in, down to the parts none of them
+/// used.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Summary {
+    pub rockets: usize,
+    /// How many rockets came out each height, shortest first
+    pub heights: BTreeMap<usize, usize>,
+    /// How many rockets came out each width, narrowest first
+    pub widths: BTreeMap<usize, usize>,
+    /// The parts the rockets could have been built from, each with how many rockets used it and
+    /// how many times in all, most used first
+    pub parts: Vec<(Arc<Part>, usize, usize)>,
+}
+
+impl Summary {
+    /// Sums up `rockets`, built from `parts`.
+    pub fn of(rockets: &[Rocket], parts: &[Arc<Part>]) -> Summary {
+        let mut summary = Summary {
+            rockets: rockets.len(),
+            heights: BTreeMap::new(),
+            widths: BTreeMap::new(),
+            parts: parts.iter().map(|part| (Arc::clone(part), 0, 0)).collect(),
+        };
+        for rocket in rockets {
+            let stats = Stats::of(rocket);
+            *summary.heights.entry(stats.height).or_default() += 1;
+            *summary.widths.entry(stats.width).or_default() += 1;
+            for (part, rockets, uses) in &mut summary.parts {
+                let count = rocket.sections().iter().filter(|section| ***section == **part).count();
+                *rockets += (count > 0) as usize;
+                *uses += count;
+            }
+        }
+        // Stable, so parts used equally stay in the pack's order
+        summary.parts.sort_by_key(|&(_, rockets, _)| Reverse(rockets));
+        summary
+    }
+
+    /// Whether a part used in `rockets` of them is rarely used, if at all.
+    fn rare(&self, rockets: usize) -> bool {
+        (rockets as f64) < RARE * self.rockets as f64
+    }
+
+    pub fn to_json(&self) -> Value {
+        let counts = |counts: &BTreeMap<usize, usize>| {
+            Value::Array(counts.iter().map(|(&size, &count)| Value::Array(vec![size.into(), count.into()])).collect())
+        };
+        Value::object([
+            ("rockets", self.rockets.into()),
+            ("heights", counts(&self.heights)),
+            ("widths", counts(&self.widths)),
+            ("parts", Value::Array(self.parts.iter()
+                .map(|(part, rockets, uses)| Value::object([
+                    ("id", part.id().into()),
+                    ("name", part.name().into()),
+                    ("type", part.part_type().to_string().into()),
+                    ("shape", part.shape().into()),
+                    ("rockets", (*rockets).into()),
+                    ("uses", (*uses).into()),
+                    ("rare", self.rare(*rockets).into()),
+                ]))
+                .collect())),
+        ])
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |counts: &BTreeMap<usize, usize>| {
+            counts.iter().map(|(size, count)| format!("{}: {}", size, count)).collect::<Vec<_>>().join(", ")
+        };
+        writeln!(f, "rockets  {}", self.rockets)?;
+        writeln!(f, "heights  {}", counts(&self.heights))?;
+        writeln!(f, "widths   {}", counts(&self.widths))?;
+        writeln!(f)?;
+        let id_width = self.parts.iter().map(|(part, _, _)| part.id().chars().count()).max().unwrap_or(0).max(2);
+        writeln!(f, "{:<id_width$}  {:<8} {:>7} {:>6}  SHAPE", "ID", "TYPE", "ROCKETS", "USES")?;
+        for (part, rockets, uses) in &self.parts {
+            let note = match rockets {
+                0 => "  never used",
+                _ if self.rare(*rockets) => "  rare",
+                _ => "",
+            };
+            let columns = format!("{:<id_width$}  {:<8} {:>7} {:>6}  ", part.id(), part.part_type().to_string(), rockets, uses);
+            for (i, line) in part.shape().lines().enumerate() {
+                if i == 0 && !note.is_empty() {
+                    writeln!(f, "{}{:<width$}{}", columns, line, note, width = part.width())?;
+                } else {
+                    let indent = if i == 0 { columns.clone() } else { " ".repeat(columns.len()) };
+                    writeln!(f, "{}{}", indent, line)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
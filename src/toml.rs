@@ -0,0 +1,173 @@
+use std::fmt;
+
+/// The small piece of TOML that config files and part packs are written in: `[table]` and
+/// `[[array]]` headers, `#` comments, and `key = value` lines whose value is a string, an integer
+/// or a boolean. Strings can be `"basic"` with escapes, `'literal'`, or `'''multi-line literal'''`
+/// so part shapes can be drawn as they look.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    /// Line the key is on, counting from 1
+    pub line: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Table {
+    /// Empty for the keys before the first header
+    pub name: String,
+    /// Whether the header was `[[name]]`, one of a list of tables
+    pub array: bool,
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.key == key)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Splits a document into its tables, the root table first.
+pub fn parse(text: &str) -> Result<Vec<Table>, Error> {
+    let mut tables = vec![Table { name: String::new(), array: false, line: 1, entries: Vec::new() }];
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+    while let Some((n, line)) = lines.next() {
+        let error = |message: String| Error { line: n, message };
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let (header, array) = match header.strip_prefix('[') {
+                Some(header) => (header.strip_suffix("]]"), true),
+                None => (header.strip_suffix(']'), false),
+            };
+            let name = header.ok_or_else(|| error("Unclosed table header".to_string()))?.trim();
+            tables.push(Table { name: unquote(name).to_string(), array, line: n, entries: Vec::new() });
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| error("Expected key = value".to_string()))?;
+        let value = value.trim();
+        let value = if let Some(rest) = value.strip_prefix("'''") {
+            Value::String(multi_line(rest, &mut lines).ok_or_else(|| error("Unclosed ''' string".to_string()))?)
+        } else if let Some(rest) = value.strip_prefix('\'') {
+            Value::String(rest.strip_suffix('\'').ok_or_else(|| error("Unclosed string".to_string()))?.to_string())
+        } else if let Some(rest) = value.strip_prefix('"') {
+            let s = rest.strip_suffix('"').ok_or_else(|| error("Unclosed string".to_string()))?;
+            Value::String(unescape(s).map_err(error)?)
+        } else if value == "true" || value == "false" {
+            Value::Bool(value == "true")
+        } else if let Ok(n) = value.parse() {
+            Value::Integer(n)
+        } else {
+            return Err(error(format!("Can't read value \"{}\", quote strings", value)));
+        };
+        let table = tables.last_mut().expect("there is always a root table");
+        table.entries.push(Entry { key: unquote(key.trim()).to_string(), value, line: n });
+    }
+    Ok(tables)
+}
+
+/// Reads the rest of a `'''` string whose first line, after the quotes, is `first`. A newline
+/// straight after the opening quotes isn't part of the string.
+fn multi_line<'a>(first: &'a str, lines: &mut impl Iterator<Item = (usize, &'a str)>) -> Option<String> {
+    if let Some(end) = closing_quotes(first) {
+        return Some(first[..end].to_string());
+    }
+    let mut s = first.to_string();
+    for (_, line) in lines {
+        if !s.is_empty() {
+            s.push('\n');
+        }
+        if let Some(end) = closing_quotes(line) {
+            s.push_str(&line[..end]);
+            return Some(s);
+        }
+        s.push_str(line);
+    }
+    None
+}
+
+/// Where the string ends on a line closing a `'''` string. Up to two quotes right before the
+/// closing three belong to the string, so shapes can end in `'`.
+fn closing_quotes(line: &str) -> Option<usize> {
+    let start = line.find("'''")?;
+    let quotes = line[start..].chars().take_while(|&c| c == '\'').count();
+    Some(start + quotes.min(5) - 3)
+}
+
+/// Drops a trailing `#` comment, leaving any `#` inside a string alone.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, ch) in line.char_indices() {
+        match (ch, quote) {
+            ('\\', Some('"')) if !escaped => {
+                escaped = true;
+                continue;
+            }
+            ('"' | '\'', None) => quote = Some(ch),
+            (ch, Some(q)) if ch == q && !escaped => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn unquote(key: &str) -> &str {
+    key.strip_prefix('"').and_then(|k| k.strip_suffix('"')).unwrap_or(key)
+}
+
+fn unescape(s: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some(other) => return Err(format!("Unknown escape \"\\{}\"", other)),
+            None => return Err("String ends in a lone backslash".to_string()),
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{self, Attribute, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::palette::{Color, Palette};
+use crate::quantize;
+use crate::render::ColorDepth;
+use crate::rocket::{self, Part, Rocket};
+
+const HELP: &str = "↑↓ choose  enter add  r random  ⌫ remove  t finish as text  b finish as blueprint  q quit";
+
+/// How the finished rocket should be written out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Export {
+    Text,
+    /// `--format json`, which `--learn` can read back
+    Blueprint,
+}
+
+/// A rocket being put together by hand, nose first.
+struct Builder<'a> {
+    target: usize,
+    parts: &'a [Arc<Part>],
+    sections: Vec<Arc<Part>>,
+    selected: usize,
+    message: String,
+}
+
+impl Builder<'_> {
+    fn height(&self) -> usize {
+        self.sections.iter().map(|p| p.height()).sum()
+    }
+
+    /// The parts that fit under the last section without going past the target height.
+    fn candidates(&self) -> Vec<&Arc<Part>> {
+        let last = self.sections.last().map(|p| &**p);
+        self.parts.iter()
+            .filter(|p| rocket::fits_under(last, p) && self.height() + p.height() <= self.target)
+            .collect()
+    }
+
+    fn add(&mut self, part: Arc<Part>) {
+        self.sections.push(part);
+        self.selected = 0;
+        self.message.clear();
+    }
+
+    /// Adds one of the candidates, as likely as its weight among them.
+    fn add_random(&mut self) {
+        let candidates = self.candidates();
+        let total: usize = candidates.iter().map(|p| p.selection_weight()).sum();
+        if total == 0 {
+            self.message = "Nothing fits here".to_string();
+            return;
+        }
+        let mut pick = (rand::random::<u64>() % total as u64) as usize;
+        let part = candidates.into_iter()
+            .find(|p| {
+                let found = pick < p.selection_weight();
+                pick = pick.saturating_sub(p.selection_weight());
+                found
+            })
+            .expect("the pick is less than the total weight");
+        self.add(Arc::clone(part));
+    }
+}
+
+/// Puts the terminal back the way it was, however the builder ends.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Lets the user put a rocket `target` lines tall together from `parts` in the terminal, choosing
+/// each part from the ones that fit under the last or leaving it to chance. Returns the rocket
+/// and how to write it out, or `None` if they quit. Colors are drawn at the terminal's `depth`.
+pub fn run(target: usize, parts: &[Arc<Part>], palette: &Palette, depth: ColorDepth) -> io::Result<Option<(Rocket, Export)>> {
+    let mut builder = Builder { target, parts, sections: Vec::new(), selected: 0, message: String::new() };
+    let _screen = Screen::enter()?;
+    let mut out = io::stdout();
+    loop {
+        draw(&mut out, &builder, palette, depth)?;
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let candidates = builder.candidates().len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Up | KeyCode::Char('k') => builder.selected = builder.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => builder.selected = (builder.selected + 1).min(candidates.saturating_sub(1)),
+            KeyCode::Enter => match builder.candidates().get(builder.selected) {
+                Some(&part) => builder.add(Arc::clone(part)),
+                None => builder.message = "Nothing fits here".to_string(),
+            },
+            KeyCode::Char('r') => builder.add_random(),
+            KeyCode::Backspace | KeyCode::Char('u') => {
+                builder.sections.pop();
+                builder.selected = 0;
+                builder.message.clear();
+            }
+            KeyCode::Char(ch @ ('t' | 'b')) => {
+                let rocket = Rocket::assembled(builder.sections.clone());
+                match rocket.validate() {
+                    Ok(()) => return Ok(Some((rocket, if ch == 't' { Export::Text } else { Export::Blueprint }))),
+                    Err(e) => builder.message = format!("Not finished: {}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(out: &mut impl Write, builder: &Builder, palette: &Palette, depth: ColorDepth) -> io::Result<()> {
+    let (columns, rows) = terminal::size()?;
+    let rows = rows as usize;
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+    queue!(out, Print(format!("{} of {} lines  {}", builder.height(), builder.target, builder.message)))?;
+
+    // The rocket on the left, its bottom kept in view as it grows
+    let grid = Rocket::assembled(builder.sections.clone()).to_grid();
+    let visible = rows.saturating_sub(3);
+    let first = grid.height().saturating_sub(visible);
+    for (y, row) in grid.rows().skip(first).enumerate() {
+        queue!(out, MoveTo(0, (y + 2) as u16))?;
+        for cell in row {
+            if let Some(color) = cell.foreground(palette) {
+                queue!(out, SetForegroundColor(terminal_color(color, palette, depth)))?;
+            }
+            queue!(out, Print(cell.ch), ResetColor)?;
+        }
+    }
+
+    // The parts that fit, and the whole of the selected one under them
+    let left = grid.width().max(7) as u16 + 4;
+    let candidates = builder.candidates();
+    let listed = visible.saturating_sub(6).max(1);
+    let start = builder.selected.saturating_sub(listed - 1);
+    for (i, part) in candidates.iter().enumerate().skip(start).take(listed) {
+        let line = format!("{:<8} {}→{} {}  {}", part.part_type().to_string(), part.top_width(), part.bottom_width(),
+                           part.height(), part.shape().lines().next().unwrap_or(""));
+        queue!(out, MoveTo(left, (i - start + 2) as u16))?;
+        if i == builder.selected {
+            queue!(out, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset))?;
+        } else {
+            queue!(out, Print(line))?;
+        }
+    }
+    if let Some(part) = candidates.get(builder.selected) {
+        for (y, line) in part.shape().lines().enumerate() {
+            queue!(out, MoveTo(left, (listed + 3 + y) as u16), Print(line))?;
+        }
+    }
+
+    let help: String = HELP.chars().take(columns as usize).collect();
+    queue!(out, MoveTo(0, rows.saturating_sub(1) as u16), Print(help))?;
+    out.flush()
+}
+
+fn terminal_color(color: Color, palette: &Palette, depth: ColorDepth) -> style::Color {
+    match depth {
+        ColorDepth::TrueColor => style::Color::Rgb { r: color.r, g: color.g, b: color.b },
+        ColorDepth::Ansi256 => style::Color::AnsiValue(palette.ansi256(color).unwrap_or_else(|| quantize::nearest_256(color))),
+        // In SGR order, so the 16 color codes crossterm writes match
+        ColorDepth::Ansi16 => [
+            style::Color::Black, style::Color::DarkRed, style::Color::DarkGreen, style::Color::DarkYellow,
+            style::Color::DarkBlue, style::Color::DarkMagenta, style::Color::DarkCyan, style::Color::Grey,
+            style::Color::DarkGrey, style::Color::Red, style::Color::Green, style::Color::Yellow,
+            style::Color::Blue, style::Color::Magenta, style::Color::Cyan, style::Color::White,
+        ][quantize::nearest_16(color) as usize],
+    }
+}
@@ -0,0 +1,40 @@
+use rand::Rng;
+
+use crate::grid::Grid;
+
+/// Gap kept between rockets side by side, before the random bit added to it
+const GAP: usize = 2;
+
+/// Covers a `width` by `height` screen in rockets like wrapping paper, taking them from `rockets`
+/// as they're needed. They're laid out in rows `row_height` lines apart, each row staggered from
+/// the last by about half a rocket, and nudged a little from their places by `rng` so the pattern
+/// doesn't look too regular. Rockets hanging off the edges are cut off there.
+pub fn tile<E, R: Rng + ?Sized>(
+    width: usize,
+    height: usize,
+    row_height: usize,
+    rockets: &mut impl Iterator<Item = Result<Grid, E>>,
+    rng: &mut R,
+) -> Result<Grid, E> {
+    let mut screen = Grid::new(width, height);
+    let mut y = 0;
+    for row in 0.. {
+        if y >= height as isize {
+            break;
+        }
+        let mut x = 0;
+        let mut first = true;
+        while x < width as isize {
+            let Some(rocket) = rockets.next().transpose()? else { return Ok(screen) };
+            // Every other row starts with a rocket half off the left edge
+            if first && row % 2 == 1 {
+                x -= rocket.width() as isize / 2 + 1;
+            }
+            first = false;
+            screen.overlay(x, y + rng.gen_range(0..=1), &rocket);
+            x += (rocket.width() + GAP + rng.gen_range(0..=3)) as isize;
+        }
+        y += row_height as isize + 1;
+    }
+    Ok(screen)
+}
@@ -0,0 +1,28 @@
+use wasm_bindgen::prelude::*;
+
+use crate::render::{Format, Options};
+use crate::rocket::Rocket;
+
+/// Builds the rocket for `height` and `seed` and renders it in `format`.
+fn render(height: usize, seed: u64, palette: &str, format: Format) -> Result<String, JsError> {
+    let options = Options {
+        format,
+        palette: palette.parse().map_err(|e: String| JsError::new(&e))?,
+        ..Options::default()
+    };
+    Rocket::generate_with(height, seed, &options).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Generates a rocket as an HTML `<pre>` block colored with `palette`, ready to drop into a page.
+#[wasm_bindgen]
+pub fn generate(height: usize, seed: u64, palette: &str) -> Result<String, JsError> {
+    render(height, seed, palette, Format::Html)
+}
+
+/// Generates a rocket as an object describing its seed, parts and lines of art, the same
+/// document `--format json` prints.
+#[wasm_bindgen(js_name = generateStructured)]
+pub fn generate_structured(height: usize, seed: u64, palette: &str) -> Result<JsValue, JsError> {
+    let json = render(height, seed, palette, Format::Json)?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("Generated invalid JSON"))
+}
@@ -0,0 +1,36 @@
+use std::error::Error;
+
+use crate::json::Value;
+use crate::rocket::Rocket;
+
+/// Fills in `{name}`, `{seed}` and `{height}` in `template` and puts `art` underneath in a code
+/// block, so chat clients keep it monospaced.
+pub fn message(template: &str, rocket: &Rocket, art: &str) -> String {
+    let title = template
+        .replace("{name}", &rocket.name())
+        .replace("{seed}", &rocket.seed.map_or("none".to_string(), |seed| seed.to_string()))
+        .replace("{height}", &rocket.max_height.to_string());
+    format!("{}\n```\n{}```", title, art)
+}
+
+/// The JSON body an incoming webhook at `url` expects. Slack reads the message from `text`,
+/// Discord from `content`.
+pub fn payload(url: &str, message: &str) -> Value {
+    let field = if url.contains("hooks.slack.com") { "text" } else { "content" };
+    Value::object([(field, Value::from(message))])
+}
+
+/// Posts `message` to the Discord or Slack incoming webhook at `url`.
+#[cfg(feature = "webhook")]
+pub fn post(url: &str, message: &str) -> Result<(), Box<dyn Error>> {
+    ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&payload(url, message).to_string())
+        .map_err(|e| format!("Webhook post failed: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "webhook"))]
+pub fn post(_url: &str, _message: &str) -> Result<(), Box<dyn Error>> {
+    Err("Webhooks are not available, rebuild ship_gen with `--features webhook`".into())
+}
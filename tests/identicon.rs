@@ -0,0 +1,96 @@
+use ship_gen::identicon::{self, IconSize};
+
+const SIZES: [IconSize; 3] = [IconSize::Small, IconSize::Medium, IconSize::Large];
+
+/// Names to try and, for each, names a slip of the keyboard away from it.
+fn near_misses() -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for i in 0..300 {
+        let name = format!("user{}", i);
+        pairs.push((name.clone(), format!("user{}", i + 1)));
+        pairs.push((name.clone(), format!("{}_", name)));
+        pairs.push((name.clone(), format!("User{}", i)));
+        pairs.push((name.clone(), format!("{} ", name)));
+    }
+    pairs
+}
+
+fn hue_apart(a: &str, b: &str) -> u16 {
+    let apart = identicon::hue(a).abs_diff(identicon::hue(b));
+    apart.min(360 - apart)
+}
+
+/// The lines of an identicon without its color codes.
+fn plain(icon: &str) -> Vec<String> {
+    icon.lines()
+        .map(|line| {
+            let mut plain = String::new();
+            let mut chars = line.chars();
+            while let Some(ch) = chars.next() {
+                if ch == '\x1b' {
+                    chars.by_ref().find(|&ch| ch == 'm');
+                } else {
+                    plain.push(ch);
+                }
+            }
+            plain
+        })
+        .collect()
+}
+
+#[test]
+fn the_same_input_gets_the_same_icon() {
+    for size in SIZES {
+        assert_eq!(identicon::identicon("alice@example.com", size), identicon::identicon("alice@example.com", size));
+    }
+}
+
+#[test]
+fn icons_of_a_size_are_all_the_same_size() {
+    for size in SIZES {
+        let sizes: Vec<(usize, usize)> = (0..200)
+            .map(|i| {
+                let lines = plain(&identicon::identicon(&format!("user{}", i), size));
+                assert!(lines.iter().all(|line| line.chars().count() == lines[0].chars().count()));
+                (lines[0].chars().count(), lines.len())
+            })
+            .collect();
+        assert!(sizes.iter().all(|&s| s == sizes[0]), "{:?} icons came in different sizes", size);
+        assert_eq!(sizes[0].1, size.height() + 2);
+    }
+}
+
+#[test]
+fn names_a_character_apart_get_rockets_shaped_apart() {
+    for size in [IconSize::Medium, IconSize::Large] {
+        for (a, b) in near_misses() {
+            assert_ne!(identicon::rocket(&a, size).to_string(), identicon::rocket(&b, size).to_string(), "{:?} and {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn small_icons_a_character_apart_are_shaped_or_colored_apart() {
+    // Small rockets only come in so many shapes, so a few pairs share one, but hardly any of those
+    // share a color too
+    let pairs = near_misses();
+    let alike = pairs.iter()
+        .filter(|(a, b)| {
+            identicon::rocket(a, IconSize::Small).to_string() == identicon::rocket(b, IconSize::Small).to_string()
+                && hue_apart(a, b) < 30
+        })
+        .count();
+    assert!(alike * 100 <= pairs.len(), "{} of {} pairs look alike", alike, pairs.len());
+    for (a, b) in &pairs {
+        assert_ne!(identicon::identicon(a, IconSize::Small), identicon::identicon(b, IconSize::Small));
+    }
+}
+
+#[test]
+fn hues_spread_all_the_way_round_the_wheel() {
+    let mut buckets = [0; 12];
+    for i in 0..1200 {
+        buckets[identicon::hue(&format!("user{}", i)) as usize / 30] += 1;
+    }
+    assert!(buckets.iter().all(|&count| count >= 60), "{:?}", buckets);
+}
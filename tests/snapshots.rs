@@ -0,0 +1,40 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use ship_gen::render::{Format, Options};
+use ship_gen::rocket::Rocket;
+
+/// Seeds and heights kept as golden snapshots, from the narrowest rocket up through the wide and
+/// broad parts.
+const ROCKETS: [(u64, usize); 6] = [(1, 3), (1, 10), (42, 20), (7, 29), (7, 35), (2024, 50)];
+
+/// Checks `generate_with` still draws what's in `tests/snapshots/<name>`, or writes it there when
+/// `UPDATE_SNAPSHOTS` is set, after a change that's meant to redraw rockets in a major version.
+fn check(name: &str, height: usize, seed: u64, options: &Options) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", name].iter().collect();
+    let drawn = Rocket::generate_with(height, seed, options).unwrap();
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &drawn).unwrap();
+        return;
+    }
+    let kept = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_SNAPSHOTS=1 to write it", path.display(), e));
+    assert!(drawn == kept, "seed {} at height {} no longer draws {}, it draws:\n{}", seed, height, path.display(), drawn);
+}
+
+#[test]
+fn text_rockets_match_their_snapshots() {
+    for (seed, height) in ROCKETS {
+        check(&format!("seed_{}_height_{}.txt", seed, height), height, seed, &Options::default());
+    }
+}
+
+#[test]
+fn colored_rocket_matches_its_snapshot() {
+    check("seed_42_height_20_color.txt", 20, 42, &Options { color: true, ..Options::default() });
+}
+
+#[test]
+fn json_rocket_matches_its_snapshot() {
+    check("seed_42_height_20.json", 20, 42, &Options { format: Format::Json, ..Options::default() });
+}
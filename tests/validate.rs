@@ -0,0 +1,33 @@
+use ship_gen::rocket::{BuildOptions, Profile, Rocket, Style};
+
+const STYLES: [Style; 4] = [Style::Plain, Style::Sleek, Style::Chunky, Style::Busy];
+const PROFILES: [Profile; 2] = [Profile::Standard, Profile::Chat];
+
+/// Every style and profile, at every height up to past where the widest parts come in, for a run
+/// of seeds.
+fn builds() -> impl Iterator<Item = (BuildOptions, usize, u64)> {
+    STYLES.into_iter()
+        .flat_map(|style| PROFILES.into_iter().map(move |profile| BuildOptions { style, profile, ..BuildOptions::default() }))
+        .flat_map(|options| (3..=60).map(move |height| (options.clone(), height)))
+        .flat_map(|(options, height)| (0..10).map(move |seed| (options.clone(), height, seed)))
+}
+
+#[test]
+fn every_rocket_is_valid() {
+    for (options, height, seed) in builds() {
+        let rocket = Rocket::build_with(height, seed, &options).unwrap();
+        if let Err(e) = rocket.validate() {
+            panic!("{:?} {:?} rocket {} lines tall from seed {}: {}", options.style, options.profile, height, seed, e);
+        }
+    }
+}
+
+#[test]
+fn every_rocket_is_as_tall_as_asked() {
+    for (options, height, seed) in builds() {
+        let rocket = Rocket::build_with(height, seed, &options).unwrap();
+        let lines: usize = rocket.sections().iter().map(|part| part.height()).sum();
+        assert_eq!(lines, height, "{:?} {:?} rocket from seed {}", options.style, options.profile, seed);
+        assert_eq!(rocket.to_grid().height(), height, "{:?} {:?} rocket from seed {}", options.style, options.profile, seed);
+    }
+}